@@ -0,0 +1,12 @@
+// CPUの入力の探索をメインスレッドとは別に行う Worker
+// 探索の間も描画と入力が止まらないように、wasm をもう1つ読み込んで使う
+import init, {search_cpu_input} from "./pkg/bid_of_power_lib.js?t=202410231800";
+
+const initialized = init();
+
+self.onmessage = async (event) => {
+    await initialized;
+    // 途中経過も結果も JSON の文字列でメインスレッドに送る
+    const result = search_cpu_input(event.data, (progress) => self.postMessage(progress));
+    self.postMessage(result);
+};
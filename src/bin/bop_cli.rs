@@ -0,0 +1,562 @@
+// ブラウザを使わずにルールを試すための端末版
+// cargo run --bin bop_cli -- [--hot-seat] [--players <人数>] [--seed <数値>] [--simulations <回数>] [--items <アイテムセットのJSON>]
+//     [--rules <quick|standard|high-economy>] [--deck <山札のID>] [--auction <open|sealed|second-price|dutch>]
+//     [--difficulty <easy|normal|hard|expert>] [--personality <balanced|aggressive|hoarder|item-rusher>]
+//     [--think-ms <ミリ秒>] [--cpu-seed <数値>] [--eval-weights <評価の重みのJSON>]
+use bid_of_power_lib::bop_core::cpu_player::evaluator::EvaluationWeights;
+use bid_of_power_lib::bop_core::cpu_player::mcts::SearchConfig;
+use bid_of_power_lib::bop_core::cpu_player::profile::{CPUProfile, Difficulty, Personality};
+use bid_of_power_lib::bop_core::cpu_player::search_task::SearchTask;
+use bid_of_power_lib::bop_core::cpu_player::CPUPlayer;
+use bid_of_power_lib::bop_core::mechanism::item_set::ItemSet;
+use bid_of_power_lib::bop_core::random::SeededRng;
+use bid_of_power_lib::bop_core::rules::rule_set::{AuctionMode, RuleSet, RuleSetPreset};
+use bid_of_power_lib::bop_core::rules::{apply, Command};
+use bid_of_power_lib::bop_core::state::bop_shared_state::BoPSharedState;
+use bid_of_power_lib::bop_core::state::message::{
+    AttackTargetMessage, BidMessage, GameStartIsApprovedMessage, UseItemMessage,
+};
+use bid_of_power_lib::bop_core::state::phase::PhaseType;
+use std::io::{self, BufRead, Write};
+use std::sync::Arc;
+
+// CPU対戦ではプレイヤー1以外をCPUが操作する
+const HUMAN_PLAYER_INDEX: usize = 0;
+
+struct Options {
+    is_hot_seat: bool,
+    players_len: usize,
+    seed: u64,
+    cpu_profile: CPUProfile,
+    // 以下は指定した場合だけ、CPUの強さによる設定を上書きする
+    simulations: Option<usize>,
+    think_ms: Option<f64>,
+    cpu_seed: Option<u64>,
+    item_set_path: Option<String>,
+    rule_set_preset: RuleSetPreset,
+    auction_mode: AuctionMode,
+    deck_list_id: Option<String>,
+}
+
+impl Options {
+    fn from_args() -> Options {
+        let mut options = Options {
+            is_hot_seat: false,
+            players_len: 2,
+            seed: SeededRng::random_seed(),
+            cpu_profile: CPUProfile::default(),
+            simulations: None,
+            think_ms: None,
+            cpu_seed: None,
+            item_set_path: None,
+            rule_set_preset: RuleSetPreset::Standard,
+            auction_mode: AuctionMode::Open,
+            deck_list_id: None,
+        };
+        let args = std::env::args().skip(1).collect::<Vec<String>>();
+        let mut index = 0;
+        while index < args.len() {
+            match args[index].as_str() {
+                "--hot-seat" => options.is_hot_seat = true,
+                "--players" => {
+                    index += 1;
+                    options.players_len = parse_arg(&args, index, "--players");
+                    if !(2..=4).contains(&options.players_len) {
+                        eprintln!("--players には2から4を指定してください");
+                        std::process::exit(1);
+                    }
+                }
+                "--seed" => {
+                    index += 1;
+                    options.seed = parse_arg(&args, index, "--seed");
+                }
+                "--simulations" => {
+                    index += 1;
+                    options.simulations = Some(parse_arg(&args, index, "--simulations"));
+                }
+                "--think-ms" => {
+                    index += 1;
+                    options.think_ms = Some(parse_arg(&args, index, "--think-ms"));
+                }
+                // CPUの探索を再現できるようにする（思考時間の上限は使わない）
+                "--cpu-seed" => {
+                    index += 1;
+                    options.cpu_seed = Some(parse_arg(&args, index, "--cpu-seed"));
+                }
+                "--difficulty" => {
+                    index += 1;
+                    match args.get(index).and_then(|arg| Difficulty::from_id(arg)) {
+                        Some(difficulty) => options.cpu_profile.difficulty = difficulty,
+                        None => {
+                            eprintln!(
+                                "--difficulty には easy, normal, hard, expert のいずれかを指定してください"
+                            );
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                "--personality" => {
+                    index += 1;
+                    match args.get(index).and_then(|arg| Personality::from_id(arg)) {
+                        Some(personality) => options.cpu_profile.personality = personality,
+                        None => {
+                            eprintln!(
+                                "--personality には balanced, aggressive, hoarder, item-rusher のいずれかを指定してください"
+                            );
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                "--rules" => {
+                    index += 1;
+                    match args.get(index).and_then(|arg| RuleSetPreset::from_id(arg)) {
+                        Some(preset) => options.rule_set_preset = preset,
+                        None => {
+                            eprintln!(
+                                "--rules には quick, standard, high-economy のいずれかを指定してください"
+                            );
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                "--items" => {
+                    index += 1;
+                    options.item_set_path = args.get(index).cloned();
+                    if options.item_set_path.is_none() {
+                        eprintln!("--items にはファイルのパスを指定してください");
+                        std::process::exit(1);
+                    }
+                }
+                "--auction" => {
+                    index += 1;
+                    match args.get(index).and_then(|arg| AuctionMode::from_id(arg)) {
+                        Some(auction_mode) => options.auction_mode = auction_mode,
+                        None => {
+                            eprintln!(
+                                "--auction には open, sealed, second-price, dutch のいずれかを指定してください"
+                            );
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                "--eval-weights" => {
+                    index += 1;
+                    let Some(eval_weights_path) = args.get(index) else {
+                        eprintln!("--eval-weights にはファイルのパスを指定してください");
+                        std::process::exit(1);
+                    };
+                    let evaluation_weights = std::fs::read_to_string(eval_weights_path)
+                        .map_err(|error| error.to_string())
+                        .and_then(|json| {
+                            EvaluationWeights::from_json(&json).map_err(|error| error.to_string())
+                        });
+                    match evaluation_weights {
+                        Ok(evaluation_weights) => {
+                            options.cpu_profile.evaluation_weights = evaluation_weights
+                        }
+                        Err(error) => {
+                            eprintln!("評価の重みを読み込めません: {}", error);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                "--deck" => {
+                    index += 1;
+                    options.deck_list_id = args.get(index).cloned();
+                    if options.deck_list_id.is_none() {
+                        eprintln!("--deck には山札のIDを指定してください");
+                        std::process::exit(1);
+                    }
+                }
+                arg => {
+                    eprintln!("不明な引数です: {}", arg);
+                    std::process::exit(1);
+                }
+            }
+            index += 1;
+        }
+        options
+    }
+}
+
+impl Options {
+    fn get_search_config(&self) -> SearchConfig {
+        let search_config = self.cpu_profile.get_search_config();
+        SearchConfig {
+            max_iterations: self.simulations.unwrap_or(search_config.max_iterations),
+            time_limit_ms: self.think_ms.or(search_config.time_limit_ms),
+            seed: self.cpu_seed,
+            ..search_config
+        }
+    }
+}
+
+fn parse_arg<T: std::str::FromStr>(args: &[String], index: usize, name: &str) -> T {
+    match args.get(index).and_then(|arg| arg.parse::<T>().ok()) {
+        Some(value) => value,
+        None => {
+            eprintln!("{} には数値を指定してください", name);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn main() {
+    let options = Options::from_args();
+    println!(
+        "シード: {} ルール: {} 入札: {}",
+        options.seed,
+        options.rule_set_preset.get_name(),
+        options.auction_mode.get_name()
+    );
+    let mut bop_shared_state = BoPSharedState::init_for_players(options.seed, options.players_len);
+    bop_shared_state.update_rule_set(RuleSet {
+        auction_mode: options.auction_mode,
+        ..options.rule_set_preset.get_rule_set()
+    });
+    if let Some(item_set_path) = &options.item_set_path {
+        let item_set = std::fs::read_to_string(item_set_path)
+            .map_err(|error| error.to_string())
+            .and_then(|json| ItemSet::from_json(&json).map_err(|error| error.to_string()));
+        match item_set {
+            Ok(item_set) => bop_shared_state.update_item_set(Arc::new(item_set)),
+            Err(error) => {
+                eprintln!("アイテムセットを読み込めません: {}", error);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(deck_list_id) = &options.deck_list_id {
+        if let Err(error) = bop_shared_state.select_deck_list(deck_list_id) {
+            eprintln!("山札を選べません: {}", error);
+            eprintln!(
+                "選べる山札: {}",
+                bop_shared_state
+                    .item_set
+                    .get_deck_lists()
+                    .iter()
+                    .map(|deck_list| deck_list.id.clone())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            );
+            std::process::exit(1);
+        }
+    }
+    println!("山札: {}", bop_shared_state.get_deck_list_name());
+    if !options.is_hot_seat {
+        println!(
+            "CPUの強さ: {} CPUの性格: {}",
+            options.cpu_profile.difficulty.get_name(),
+            options.cpu_profile.personality.get_name()
+        );
+    }
+    let search_config = options.get_search_config();
+    if !options.is_hot_seat {
+        for (player_index, player) in bop_shared_state.players.iter_mut().enumerate() {
+            if player_index != HUMAN_PLAYER_INDEX {
+                player.player_name = format!("CPU{}", player_index);
+            }
+        }
+    }
+    for player_index in 0..bop_shared_state.players.len() {
+        let command = Command::ApproveGameStart(GameStartIsApprovedMessage {
+            player_index,
+            game_start_is_approved: true,
+        });
+        apply(&mut bop_shared_state, command).unwrap();
+    }
+    let mut printed_log_len = 0;
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        printed_log_len = print_new_logs(&bop_shared_state, printed_log_len);
+        if bop_shared_state.phases[bop_shared_state.phase_index].phase_type == PhaseType::GameEnd {
+            print_status(&bop_shared_state);
+            break;
+        }
+        let Some(player_index) = (0..bop_shared_state.players.len())
+            .find(|player_index| bop_shared_state.is_required_input_of(*player_index))
+        else {
+            eprintln!("入力すべきプレイヤーが見つかりません");
+            std::process::exit(1);
+        };
+        let command = if !options.is_hot_seat && player_index != HUMAN_PLAYER_INDEX {
+            create_cpu_command(&bop_shared_state, player_index, &search_config)
+        } else {
+            print_status(&bop_shared_state);
+            let Some(command) = read_command(&bop_shared_state, player_index, &mut lines) else {
+                // 入力が終わった場合は中断する
+                println!();
+                return;
+            };
+            command
+        };
+        if let Err(error) = apply(&mut bop_shared_state, command) {
+            println!("{}", error);
+        }
+    }
+}
+
+fn create_cpu_command(
+    bop_shared_state: &BoPSharedState,
+    player_index: usize,
+    search_config: &SearchConfig,
+) -> Command {
+    let mut cpu_player = CPUPlayer::new(bop_shared_state);
+    cpu_player.bop_shared_state.own_player_index = player_index;
+    // 探索は別スレッドで行い、終わるまで反復の回数を表示し続ける
+    let search_task = SearchTask::start(
+        &cpu_player.bop_shared_state,
+        player_index,
+        search_config.clone(),
+    );
+    while !search_task.is_finished() {
+        print!(
+            "\r{}さんが考えています... ({}回)",
+            bop_shared_state.players[player_index].player_name,
+            search_task.get_iterations()
+        );
+        io::stdout().flush().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    let search_result = search_task.join();
+    println!(
+        "\r{}さんが考えました ({}回)      ",
+        bop_shared_state.players[player_index].player_name, search_result.iterations
+    );
+    Command::from_message(&cpu_player.create_cpu_message(search_result.input)).unwrap()
+}
+
+fn print_new_logs(bop_shared_state: &BoPSharedState, printed_log_len: usize) -> usize {
+    for game_log in bop_shared_state.game_logs.iter().skip(printed_log_len) {
+        println!(
+            "[{}] {}",
+            game_log.turn,
+            game_log.get_message(bop_shared_state)
+        );
+    }
+    bop_shared_state.game_logs.len()
+}
+
+// ブラウザ版のステータス表示と同じ内容を1行にまとめて表示する
+fn print_status(bop_shared_state: &BoPSharedState) {
+    println!("----");
+    for (player_index, player) in bop_shared_state.players.iter().enumerate() {
+        let player_status = &player.player_status;
+        println!(
+            "{} {} HP: {}/{} ATK: {} DEF: {} Money: {}(+{})",
+            player.player_name,
+            bop_shared_state.get_initiative_label(player_index),
+            player_status.current_hp,
+            player_status.max_hp,
+            player_status.attack_point,
+            player_status.defence_point,
+            player_status.current_money_amount,
+            player_status.estimated_money_amount
+        );
+        let item_names = player
+            .own_item_list
+            .iter()
+            .map(|item| bop_shared_state.item_set.get_name(&item.item_kind))
+            .collect::<Vec<String>>();
+        println!("  アイテム: {}", item_names.join(", "));
+        if !player_status.status_effects.is_empty() {
+            println!("  効果: {}", player_status.get_status_effect_labels());
+        }
+    }
+    println!("----");
+}
+
+fn read_command(
+    bop_shared_state: &BoPSharedState,
+    player_index: usize,
+    lines: &mut impl Iterator<Item = io::Result<String>>,
+) -> Option<Command> {
+    let player_name = bop_shared_state.players[player_index]
+        .player_name
+        .to_string();
+    let seq_no = bop_shared_state.get_seq_no_to_send();
+    let turn = bop_shared_state.turn;
+    loop {
+        let command = match bop_shared_state.phase_index {
+            1 => {
+                let auction_mode = bop_shared_state.rule_set.auction_mode;
+                let progress = bop_shared_state.get_auction_progress();
+                for (index, item) in bop_shared_state.items_bid_on.iter().enumerate() {
+                    // 封印入札では落札が決まるまで他のプレイヤーの入札額を表示しない
+                    let current = match progress.get_winning_bid(index) {
+                        _ if auction_mode == AuctionMode::Open => format!(
+                            "現在価格: {}",
+                            BidMessage::current_bid_amount(
+                                index,
+                                &bop_shared_state.temporary_bid_history
+                            )
+                        ),
+                        Some(winning_bid) => format!("落札済み: {}", winning_bid.price),
+                        None if auction_mode == AuctionMode::Dutch => {
+                            format!("今の価格: {}", bop_shared_state.lowest_bid_amount(index))
+                        }
+                        None => "未落札".to_string(),
+                    };
+                    println!(
+                        "{}: {} {} 最低入札価格: {} ({})",
+                        index,
+                        bop_shared_state.item_set.get_name(&item.item_kind),
+                        current,
+                        bop_shared_state.bid_input[index].bid_amount,
+                        bop_shared_state.item_set.get_description(&item.item_kind)
+                    );
+                }
+                let line = prompt(
+                    &match auction_mode {
+                        AuctionMode::Open => format!(
+                            "{}さん、入札してください（アイテム番号 [金額] / s: パス）",
+                            player_name
+                        ),
+                        AuctionMode::SealedFirstPrice | AuctionMode::SecondPrice => format!(
+                            "{}さん、封印入札してください（アイテム番号 [金額] / s: パス）",
+                            player_name
+                        ),
+                        AuctionMode::Dutch => format!(
+                            "{}さん、今の価格で落札してください（アイテム番号 / p: 見送る / s: パス）",
+                            player_name
+                        ),
+                    },
+                    lines,
+                )?;
+                let is_skipped = line.trim() == "s";
+                if is_skipped || (auction_mode == AuctionMode::Dutch && line.trim() == "p") {
+                    Some(Command::Bid(BidMessage {
+                        seq_no,
+                        player_index,
+                        bid_item_index: 0,
+                        bid_amount: 0,
+                        is_skipped,
+                    }))
+                } else {
+                    let mut words = line.split_whitespace();
+                    let bid_item_index = words.next().and_then(|word| word.parse::<usize>().ok());
+                    let bid_amount = words.next().and_then(|word| word.parse::<u32>().ok());
+                    bid_item_index.map(|bid_item_index| {
+                        Command::Bid(BidMessage {
+                            seq_no,
+                            player_index,
+                            bid_item_index,
+                            bid_amount: bid_amount.unwrap_or_else(|| {
+                                bop_shared_state
+                                    .bid_input
+                                    .get(bid_item_index)
+                                    .map(|bid_input| bid_input.bid_amount)
+                                    .unwrap_or(0)
+                            }),
+                            is_skipped: false,
+                        })
+                    })
+                }
+            }
+            2 => {
+                let own_item_list = &bop_shared_state.players[player_index].own_item_list;
+                for (index, item) in own_item_list.iter().enumerate() {
+                    println!(
+                        "{}: {} ({})",
+                        index,
+                        bop_shared_state.item_set.get_name(&item.item_kind),
+                        bop_shared_state.item_set.get_description(&item.item_kind)
+                    );
+                }
+                print_opponents(bop_shared_state, player_index);
+                let line = prompt(
+                    &format!(
+                        "{}さん、使用するアイテムを選んでください（番号 [対象のプレイヤー番号] / s: スキップ）",
+                        player_name
+                    ),
+                    lines,
+                )?;
+                let is_skipped = line.trim() == "s";
+                let mut words = line.split_whitespace();
+                let use_item_index = if is_skipped {
+                    Some(own_item_list.len())
+                } else {
+                    words.next().and_then(|word| word.parse::<usize>().ok())
+                };
+                // 対象を省略した場合はルール側で決まる
+                let args_usize = words
+                    .next()
+                    .and_then(|word| word.parse::<usize>().ok())
+                    .into_iter()
+                    .collect::<Vec<usize>>();
+                use_item_index.map(|use_item_index| {
+                    Command::UseItem(UseItemMessage {
+                        seq_no,
+                        turn,
+                        check_is_blocked: false,
+                        player_index,
+                        use_item_index,
+                        is_skipped,
+                        args_i32: vec![],
+                        args_usize,
+                    })
+                })
+            }
+            3 => {
+                print_opponents(bop_shared_state, player_index);
+                let line = prompt(
+                    &format!(
+                        "{}さん、攻撃しますか？（a [対象のプレイヤー番号]: 攻撃 / s: 攻撃せずに{} Money得る）",
+                        player_name, bop_shared_state.rule_set.attack_skip_money
+                    ),
+                    lines,
+                )?;
+                let mut words = line.split_whitespace();
+                let is_skipped = match words.next() {
+                    Some("a") => Some(false),
+                    Some("s") => Some(true),
+                    _ => None,
+                };
+                let attack_target_player_index = words
+                    .next()
+                    .and_then(|word| word.parse::<usize>().ok())
+                    .unwrap_or_else(|| bop_shared_state.opponent_player_index(player_index));
+                is_skipped.map(|is_skipped| {
+                    Command::AttackTarget(AttackTargetMessage {
+                        seq_no,
+                        turn,
+                        player_index,
+                        check_is_blocked: false,
+                        attack_target_player_index,
+                        is_skipped,
+                    })
+                })
+            }
+            // 入力を受け付けないフェーズでは続けられないので終了する
+            phase_index => {
+                eprintln!("入力できないフェーズです: {}", phase_index);
+                std::process::exit(1);
+            }
+        };
+        match command {
+            Some(command) => return Some(command),
+            None => println!("入力の形式が正しくありません"),
+        }
+    }
+}
+
+// 対象として指定できる相手をプレイヤー番号つきで表示する
+fn print_opponents(bop_shared_state: &BoPSharedState, player_index: usize) {
+    let opponents = bop_shared_state
+        .get_opponent_player_indexes(player_index)
+        .iter()
+        .map(|opponent_player_index| {
+            format!(
+                "{}: {}",
+                opponent_player_index, bop_shared_state.players[*opponent_player_index].player_name
+            )
+        })
+        .collect::<Vec<String>>();
+    println!("相手: {}", opponents.join(" / "));
+}
+
+fn prompt(message: &str, lines: &mut impl Iterator<Item = io::Result<String>>) -> Option<String> {
+    print!("{} > ", message);
+    io::stdout().flush().unwrap();
+    lines.next().and_then(|line| line.ok())
+}
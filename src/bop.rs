@@ -1,83 +1,42 @@
+use crate::bop::bind::get_binds;
 use crate::bop::scenes::game_main::GameMainState;
-use crate::bop::state::bind::get_binds;
-use crate::bop::state::bop_shared_state::BoPPlayer;
-use crate::bop::state::message::{GameStartIsApprovedMessage, GameStateMessage};
+use crate::bop_core::cpu_player::mcts::SearchConfig;
+use crate::bop_core::cpu_player::CPUPlayer;
+use crate::bop_core::rules::{apply, get_commands_to_resend, Command, GameEvent, MoneySource};
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+use crate::bop_core::state::message::{GameStateMessage, SnapshotMessage, StateHashMessage};
+use crate::bop_core::state::snapshot::GameplaySnapshot;
 use crate::engine::application_types::StateType::BoPShared;
 use crate::engine::state::{Primitives, References, State};
 use crate::engine::Engine;
 use crate::features::animation::Animation;
+use crate::features::cpu_worker::CPUSearchResponse;
 use crate::features::websocket::{ChannelMessage, MessageType, WebSocketWrapper};
+use crate::svg::svg_renderer::get_element_by_id;
 use crate::svg::SharedElements;
-use mechanism::item::Item;
-use mechanism::player_status::PlayerStatus;
 use rand::Rng;
 use scenes::title::TitleState;
-use state::bop_shared_state::BoPSharedState;
-use state::message::{AttackTargetMessage, BidMessage, UseItemMessage};
-use state::phase::Phase;
 use std::cell::RefCell;
 use std::rc::Rc;
 use wasm_bindgen_test::console_log;
-use crate::bop::cpu_player::CPUPlayer;
 
-mod cpu_player;
-pub mod mechanism;
+mod bind;
+pub mod save;
 pub mod scenes;
-pub mod state;
 
 pub fn mount() -> Engine {
     let mut rng = rand::thread_rng();
     let random_number = rng.random::<u16>();
     let user_name = random_number.to_string();
 
-    let rpg_shared_state = BoPSharedState {
-        players: vec![
-            BoPPlayer {
-                player_name: "プレイヤー1".to_string(),
-                game_start_is_approved: false,
-                battle_is_viewed: false,
-                own_item_list: vec![],
-                player_status: PlayerStatus::init(),
-            },
-            BoPPlayer {
-                player_name: "プレイヤー2".to_string(),
-                game_start_is_approved: false,
-                battle_is_viewed: false,
-                own_item_list: vec![],
-                player_status: PlayerStatus::init(),
-            },
-        ],
-        players_len: 2,
-        own_player_index: 0,
-        items_bid_on: vec![],
-        bid_input: vec![
-            BidMessage::init(0),
-            BidMessage::init(1),
-            BidMessage::init(2),
-        ],
-        bid_scheduled_items: Item::item_set_default(),
-        temporary_bid_history: vec![],
-        bid_history: vec![],
-        use_item_input: UseItemMessage::empty(),
-        use_item_history: vec![],
-        attack_target_input: AttackTargetMessage::empty(),
-        attack_target_history: vec![],
-        initiatives_to_player_index: vec![0, 1],
-        game_logs: vec![],
-        turn: 0,
-        phase_index: 0,
-        phases: Phase::get_phases(),
-        simple_binders: get_binds(),
-        input_is_guard: false,
-        consumed_seq_no: 0,
-        has_cpu: false,
-    };
+    let rpg_shared_state = BoPSharedState::init();
     let mut shared_state = State {
         user_name: user_name.to_owned(),
         to_send_channel_messages: vec![],
         elements: SharedElements::new(),
         interrupt_animations: vec![vec![Animation::always_blink()]],
-        state_type: BoPShared(rpg_shared_state.clone()),
+        state_type: BoPShared(Box::new(rpg_shared_state.clone())),
+        simple_binders: get_binds(),
         primitives: Primitives {
             scene_index: 0,
             requested_scene_index: 0,
@@ -93,6 +52,8 @@ pub fn mount() -> Engine {
         keep_connection_request: false,
         has_cpu_task: false,
         cpu_task_start_step: -1.0,
+        cpu_thinking: None,
+        replay_player: None,
     };
 
     let mut scenes = vec![
@@ -103,30 +64,86 @@ pub fn mount() -> Engine {
     init_func(&mut scenes[0], &mut shared_state);
     let web_socket_wrapper =
         WebSocketWrapper::new(shared_state.user_name.to_owned(), "bop".to_string());
-    Engine::new(shared_state, scenes, web_socket_wrapper)
+    let mut engine = Engine::new(shared_state, scenes, web_socket_wrapper);
+    engine.load_game();
+    engine
 }
 
 impl State {
-    pub fn start_cpu_task(&mut self) {
-        if let State {
-            state_type: BoPShared(bop_shared_state),
-            ..
-        } = self {
+    // 自分以外で入力待ちになっているプレイヤーをCPUが操作する
+    pub fn get_cpu_player_index(&mut self) -> Option<usize> {
+        if let BoPShared(bop_shared_state) = &mut self.state_type {
+            let own_player_index = bop_shared_state.own_player_index;
+            (0..bop_shared_state.players.len())
+                .filter(|player_index| *player_index != own_player_index)
+                .find(|player_index| bop_shared_state.is_required_input_of(*player_index))
+        } else {
+            None
+        }
+    }
+    // メインスレッドで探索する。探索が終わるまで描画と入力は止まる
+    // Worker が使えない場合と、Worker の探索が時間内に終わらなかった場合に使う
+    pub fn start_cpu_task(&mut self, search_config: &SearchConfig) {
+        let cpu_player_index = self.get_cpu_player_index();
+        if let (
+            State {
+                state_type: BoPShared(bop_shared_state),
+                ..
+            },
+            Some(cpu_player_index),
+        ) = (&mut *self, cpu_player_index)
+        {
             let cpu_player = &mut CPUPlayer::new(bop_shared_state);
-            cpu_player.bop_shared_state.own_player_index = 1;
+            cpu_player.bop_shared_state.own_player_index = cpu_player_index;
             cpu_player.bop_shared_state.has_cpu = false;
-            let player_index = bop_shared_state.own_player_index;
-            let opponent_player_index =
-                bop_shared_state.opponent_player_index(player_index);
-            let index =
-                cpu_player.simulate_multiple_times(opponent_player_index, 40000);
-            console_log!("cpu index is... {}", index);
-            self
-                .to_send_channel_messages
-                .push(cpu_player.create_cpu_message(index));
+            let search_result = cpu_player.search(cpu_player_index, search_config);
+            console_log!(
+                "cpu index is... {} ({} iterations)",
+                search_result.input,
+                search_result.iterations
+            );
+            self.to_send_channel_messages
+                .push(cpu_player.create_cpu_message(search_result.input));
         }
         self.has_cpu_task = false;
     }
+    // Worker から届いた途中経過を思考中の表示に反映し、結果が届けば送信する
+    pub fn consume_cpu_search_response(&mut self, response: CPUSearchResponse) {
+        let State {
+            state_type: BoPShared(bop_shared_state),
+            cpu_thinking: Some(cpu_thinking),
+            ..
+        } = self
+        else {
+            return;
+        };
+        // 中断した依頼の結果は使わない
+        if response.request_id != cpu_thinking.request_id {
+            return;
+        }
+        let Some(message) = response.message else {
+            render_cpu_thinking(Some(format!(
+                "{}さんが考えています… ({}回)",
+                bop_shared_state.players[cpu_thinking.player_index].player_name,
+                response.iterations
+            )));
+            return;
+        };
+        console_log!(
+            "cpu message is... {} ({} iterations)",
+            message,
+            response.iterations
+        );
+        if cpu_thinking.consumed_seq_no == bop_shared_state.consumed_seq_no {
+            self.to_send_channel_messages.push(message);
+            self.has_cpu_task = false;
+        } else {
+            // 考えている間に state が進んだので、考え直す
+            self.cpu_task_start_step = -1.0;
+        }
+        self.cpu_thinking = None;
+        render_cpu_thinking(None);
+    }
     pub fn consume_channel_message(&mut self, message: &ChannelMessage) {
         console_log!("consume_channel_message start {}", message.message);
         if let State {
@@ -156,65 +173,62 @@ impl State {
                     if let Ok(message) = serde_json::from_str::<GameStateMessage>(&message.message)
                     {
                         console_log!("enter game state message logic {:?}", message);
-                        if message.player_index == bop_shared_state.phase_index
+                        if message.player_index == bop_shared_state.own_player_index
                             || bop_shared_state.consumed_seq_no == message.last_consumed_seq_no
                         {
                             // 自分のメッセージ、または同期が取れているものは無視
                             // empty
                         } else {
-                            let last_consumed = message.last_consumed_seq_no;
-                            for n in last_consumed + 1..bop_shared_state.consumed_seq_no + 1 {
-                                if let Some(found) = bop_shared_state
-                                    .temporary_bid_history
-                                    .iter()
-                                    .find(|message| message.seq_no == n)
-                                {
-                                    self.to_send_channel_messages
-                                        .push(serde_json::to_string(found).unwrap());
-                                };
-                                if let Some(found) = bop_shared_state
-                                    .bid_history
-                                    .iter()
-                                    .find(|message| message.seq_no == n)
-                                {
-                                    self.to_send_channel_messages
-                                        .push(serde_json::to_string(found).unwrap());
-                                };
-                                if let Some(found) = bop_shared_state
-                                    .use_item_history
-                                    .iter()
-                                    .find(|message| message.seq_no == n)
-                                {
-                                    self.to_send_channel_messages
-                                        .push(serde_json::to_string(found).unwrap());
-                                };
-                                if let Some(found) = bop_shared_state
-                                    .attack_target_history
-                                    .iter()
-                                    .find(|message| message.seq_no == n)
-                                {
-                                    self.to_send_channel_messages
-                                        .push(serde_json::to_string(found).unwrap());
-                                };
+                            for command in get_commands_to_resend(
+                                bop_shared_state,
+                                message.last_consumed_seq_no,
+                            ) {
+                                self.to_send_channel_messages.push(command.to_message());
                             }
                         }
                         console_log!("complete game state message logic {:?}", message);
                     } else if let Ok(message) =
-                        serde_json::from_str::<GameStartIsApprovedMessage>(&message.message)
+                        serde_json::from_str::<StateHashMessage>(&message.message)
                     {
-                        console_log!("enter game start is approved message logic {:?}", message);
-                        bop_shared_state.players[message.player_index].game_start_is_approved =
-                            message.game_start_is_approved;
-                        console_log!(
-                            "complete game start is approved message logic {:?}",
-                            message
-                        );
+                        if message.player_index != bop_shared_state.own_player_index {
+                            let events = bop_shared_state
+                                .receive_peer_state_hash(message.seq_no, message.state_hash);
+                            consume_game_events(
+                                bop_shared_state,
+                                &events,
+                                &mut self.to_send_channel_messages,
+                                interrupt_animations,
+                            );
+                        }
+                    } else if let Ok(message) =
+                        serde_json::from_str::<SnapshotMessage>(&message.message)
+                    {
+                        if message.player_index != bop_shared_state.own_player_index
+                            && message.player_index == bop_shared_state.host_player_index
+                        {
+                            console_log!("restore snapshot {:?}", message.snapshot);
+                            if let Err(error) = message.snapshot.restore_to(bop_shared_state) {
+                                // 不正なスナップショットは適用せずに破棄する
+                                console_log!("snapshot rejected: {}", error);
+                            }
+                        }
                     } else {
-                        bop_shared_state.update_game_state_by_message(
-                            message.message.clone(),
-                            interrupt_animations,
-                            false,
-                        );
+                        match Command::from_message(&message.message)
+                            .and_then(|command| apply(bop_shared_state, command))
+                        {
+                            Ok(events) => {
+                                consume_game_events(
+                                    bop_shared_state,
+                                    &events,
+                                    &mut self.to_send_channel_messages,
+                                    interrupt_animations,
+                                );
+                            }
+                            Err(error) => {
+                                // ルール上許されない操作は適用せずに破棄する
+                                console_log!("message rejected: {} {}", error, message.message);
+                            }
+                        }
                     }
                 }
                 _ => {}
@@ -222,3 +236,92 @@ impl State {
         }
     }
 }
+
+// None の場合は思考中の表示を隠す
+pub fn render_cpu_thinking(text: Option<String>) {
+    let element = get_element_by_id("cpu-thinking".to_string());
+    match text {
+        Some(text) => {
+            element.set_inner_html(&text);
+            element.set_attribute("display", "block").unwrap();
+        }
+        None => element.set_attribute("display", "none").unwrap(),
+    }
+}
+
+// ルールの適用結果を、通信とメッセージ表示に反映する
+fn consume_game_events(
+    bop_shared_state: &BoPSharedState,
+    events: &[GameEvent],
+    to_send_channel_messages: &mut Vec<String>,
+    interrupt_animations: &mut Vec<Vec<Animation>>,
+) {
+    for event in events.iter() {
+        match event {
+            GameEvent::Desynced { .. } => {
+                // 届いていないメッセージの再送信を相手に依頼する
+                to_send_channel_messages.push(
+                    serde_json::to_string(&GameStateMessage {
+                        player_index: bop_shared_state.own_player_index,
+                        last_consumed_seq_no: bop_shared_state.consumed_seq_no,
+                    })
+                    .unwrap(),
+                );
+            }
+            GameEvent::StateHashed { seq_no, state_hash } => {
+                to_send_channel_messages.push(
+                    serde_json::to_string(&StateHashMessage {
+                        player_index: bop_shared_state.own_player_index,
+                        seq_no: *seq_no,
+                        state_hash: *state_hash,
+                    })
+                    .unwrap(),
+                );
+            }
+            GameEvent::StateHashMismatched { seq_no } => {
+                console_log!("state hash mismatched at seq no {}", seq_no);
+                // ホストの state を正として相手に送る
+                if bop_shared_state.own_player_index == bop_shared_state.host_player_index {
+                    to_send_channel_messages.push(
+                        serde_json::to_string(&SnapshotMessage {
+                            player_index: bop_shared_state.own_player_index,
+                            snapshot: GameplaySnapshot::from_state(bop_shared_state),
+                        })
+                        .unwrap(),
+                    );
+                }
+            }
+            _ => {}
+        }
+        if let Some(event_message) = get_event_message(bop_shared_state, event) {
+            interrupt_animations.push(vec![Animation::create_message(event_message, true)]);
+        }
+    }
+}
+
+// イベントのうち、メッセージとして表示するもの
+fn get_event_message(bop_shared_state: &BoPSharedState, event: &GameEvent) -> Option<String> {
+    match event {
+        GameEvent::DamageDealt {
+            target_player_index,
+            damage,
+            remaining_hp,
+            ..
+        } => Some(format!(
+            "{}さんに{}のダメージ（残りHP: {}）",
+            bop_shared_state.players[*target_player_index].player_name, damage, remaining_hp,
+        )),
+        GameEvent::MoneyGained {
+            player_index,
+            amount,
+            source: MoneySource::AttackSkipped,
+        } => Some(format!(
+            "{}さんは {} Moneyを得た",
+            bop_shared_state.players[*player_index].player_name, amount
+        )),
+        GameEvent::StateHashMismatched { .. } => {
+            Some("相手との状態の不一致を検出しました。ホストの状態に合わせます".to_string())
+        }
+        _ => None,
+    }
+}
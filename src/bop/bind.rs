@@ -1,5 +1,6 @@
-use crate::bop::state::bop_shared_state::BoPSharedState;
-use crate::bop::state::message::BidMessage;
+use crate::bop_core::rules::rule_set::AuctionMode;
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+use crate::bop_core::state::message::BidMessage;
 use crate::svg::simple_binder::SimpleBinder;
 use crate::svg::svg_renderer::get_element_by_id;
 
@@ -14,29 +15,61 @@ pub fn get_binds() -> Vec<SimpleBinder> {
         .unwrap();
     fn required_input_func(bop_shared_state: &mut BoPSharedState, _: usize) -> String {
         if bop_shared_state.phase_index == 4 {
-            return if bop_shared_state.players[0].is_lose() {
-                format!("{}さんの勝利です", bop_shared_state.players[1].player_name)
-            } else {
-                format!("{}さんの勝利です", bop_shared_state.players[0].player_name)
-            }
+            let winner_names = bop_shared_state
+                .players
+                .iter()
+                .filter(|player| !player.is_lose())
+                .map(|player| player.player_name.to_string())
+                .collect::<Vec<String>>();
+            return format!("{}さんの勝利です", winner_names.join("さん、"));
+        }
+        if bop_shared_state.is_desynced() {
+            return "通信を再同期しています...".to_string();
         }
         if bop_shared_state.input_is_guard {
+            // 3人以上の場合は隣のプレイヤーとは限らないので、入力待ちのプレイヤーを探す
+            let own_player_index = bop_shared_state.own_player_index;
+            let thinking_player_index = (0..bop_shared_state.players.len())
+                .filter(|player_index| *player_index != own_player_index)
+                .find(|player_index| bop_shared_state.is_required_input_of(*player_index))
+                .unwrap_or_else(|| bop_shared_state.opponent_player_index(own_player_index));
             return format!(
                 "{}{}が考えています...",
-                bop_shared_state.players
-                    [bop_shared_state.opponent_player_index(bop_shared_state.own_player_index)]
-                .player_name,
-                if bop_shared_state.has_cpu && bop_shared_state.own_player_index == 0 {
+                bop_shared_state.players[thinking_player_index].player_name,
+                if bop_shared_state.has_cpu && own_player_index == 0 {
                     "(CPU)"
                 } else {
                     "さん"
                 }
             );
         }
+        let own_player_index = bop_shared_state.own_player_index;
         match bop_shared_state.phase_index {
-            1 => format!(
-                "{}さん、入札してください。上下: 選択　左右: 金額変更　A: 決定",
-                bop_shared_state.players[bop_shared_state.own_player_index].player_name
+            1 => match bop_shared_state.rule_set.auction_mode {
+                AuctionMode::Open => format!(
+                    "{}さん、入札してください。上下: 選択　左右: 金額変更　A: 決定　X: パス",
+                    bop_shared_state.players[own_player_index].player_name
+                ),
+                AuctionMode::SealedFirstPrice | AuctionMode::SecondPrice => format!(
+                    "{}さん、封印入札してください（全員の入札後に公開）。上下: 選択　左右: 金額変更　A: 決定　X: パス",
+                    bop_shared_state.players[own_player_index].player_name
+                ),
+                AuctionMode::Dutch if bop_shared_state.can_wait_for_lower_price() => format!(
+                    "{}さん、今の価格で落札するか選んでください。上下: 選択　A: 落札　Z: 見送る　X: パス",
+                    bop_shared_state.players[own_player_index].player_name
+                ),
+                AuctionMode::Dutch => format!(
+                    "{}さん、落札するアイテムを選んでください。上下: 選択　A: 落札　X: パス",
+                    bop_shared_state.players[own_player_index].player_name
+                ),
+            },
+            2 if bop_shared_state.is_chaining(own_player_index) => format!(
+                "{}さん、続けて使用するアイテムを選んでください。A: 決定　Z: 使用を終える",
+                bop_shared_state.players[own_player_index].player_name
+            ),
+            3 if bop_shared_state.is_chaining(own_player_index) => format!(
+                "{}さん、続けて攻撃する対象を選んでください。",
+                bop_shared_state.players[own_player_index].player_name
             ),
             2 => format!(
                 "{}さん、使用するアイテムを選んでください。上下: 選択　A: 決定　Z: スキップ",
@@ -60,17 +93,49 @@ pub fn get_binds() -> Vec<SimpleBinder> {
         }
     }
 
+    // 封印入札では落札が決まるまで他のプレイヤーの入札額を表示しない
+    // ダッチ方式では現在の価格を表示する
     fn current_amount_func(bop_shared_state: &mut BoPSharedState, args_usize: usize) -> String {
-        let amount =
-            BidMessage::current_bid_amount(args_usize, &bop_shared_state.temporary_bid_history);
-        if amount == 0 {
-            "-".to_string()
+        if bop_shared_state.rule_set.auction_mode == AuctionMode::Open {
+            let amount =
+                BidMessage::current_bid_amount(args_usize, &bop_shared_state.temporary_bid_history);
+            return if amount == 0 {
+                "-".to_string()
+            } else {
+                amount.to_string()
+            };
+        }
+        if args_usize >= bop_shared_state.items_bid_on.len() {
+            return "-".to_string();
+        }
+        let progress = bop_shared_state.get_auction_progress();
+        if let Some(winning_bid) = progress.get_winning_bid(args_usize) {
+            format!("済{}", winning_bid.price)
+        } else if bop_shared_state.rule_set.auction_mode == AuctionMode::Dutch {
+            progress
+                .get_dutch_price(&bop_shared_state.rule_set)
+                .to_string()
         } else {
-            amount.to_string()
+            "?".to_string()
         }
     }
 
-    for n in 0..3 {
+    fn current_amount_label_func(bop_shared_state: &mut BoPSharedState, _: usize) -> String {
+        match bop_shared_state.rule_set.auction_mode {
+            AuctionMode::Open => "現在価格",
+            AuctionMode::SealedFirstPrice | AuctionMode::SecondPrice => "落札価格",
+            AuctionMode::Dutch => "今の価格",
+        }
+        .to_string()
+    }
+    binds.push(SimpleBinder::new(
+        get_element_by_id("simple-binder-current-amount-label".to_string()),
+        0,
+        current_amount_label_func,
+    ));
+
+    // 入札対象は最大でプレイヤー数 + RuleSet の extra_items_on_offer 個（4人・ハイエコノミーで6個）
+    for n in 0..6 {
         binds.push(SimpleBinder::new(
             get_element_by_id(format!("simple-binder-input-amount-{}", n + 1)),
             n,
@@ -85,7 +150,7 @@ pub fn get_binds() -> Vec<SimpleBinder> {
     for n in 0..11 {
         fn item_list_a(bop_shared_state: &mut BoPSharedState, args_usize: usize) -> String {
             if let Some(item) = bop_shared_state.players[0].own_item_list.get(args_usize) {
-                item.item_kind.get_name()
+                bop_shared_state.item_set.get_name(&item.item_kind)
             } else {
                 "".to_string()
             }
@@ -100,7 +165,7 @@ pub fn get_binds() -> Vec<SimpleBinder> {
             args_usize: usize,
         ) -> String {
             if let Some(item) = bop_shared_state.players[0].own_item_list.get(args_usize) {
-                item.item_kind.get_description()
+                bop_shared_state.item_set.get_description(&item.item_kind)
             } else {
                 "".to_string()
             }
@@ -114,7 +179,7 @@ pub fn get_binds() -> Vec<SimpleBinder> {
     for n in 0..10 {
         fn bop_list_b(bop_shared_state: &mut BoPSharedState, args_usize: usize) -> String {
             if let Some(item) = bop_shared_state.players[1].own_item_list.get(args_usize) {
-                item.item_kind.get_name()
+                bop_shared_state.item_set.get_name(&item.item_kind)
             } else {
                 "".to_string()
             }
@@ -129,7 +194,7 @@ pub fn get_binds() -> Vec<SimpleBinder> {
             args_usize: usize,
         ) -> String {
             if let Some(item) = bop_shared_state.players[1].own_item_list.get(args_usize) {
-                item.item_kind.get_description()
+                bop_shared_state.item_set.get_description(&item.item_kind)
             } else {
                 "".to_string()
             }
@@ -160,7 +225,7 @@ pub fn get_binds() -> Vec<SimpleBinder> {
     for n in 0..19 {
         fn scheduled_item(bop_shared_state: &mut BoPSharedState, args_usize: usize) -> String {
             if let Some(item) = bop_shared_state.bid_scheduled_items.get(args_usize) {
-                item.item_kind.get_name()
+                bop_shared_state.item_set.get_name(&item.item_kind)
             } else {
                 "".to_string()
             }
@@ -177,7 +242,7 @@ pub fn get_binds() -> Vec<SimpleBinder> {
             args_usize: usize,
         ) -> String {
             if let Some(item) = bop_shared_state.bid_scheduled_items.get(args_usize) {
-                item.item_kind.get_description()
+                bop_shared_state.item_set.get_description(&item.item_kind)
             } else {
                 "".to_string()
             }
@@ -261,12 +326,7 @@ pub fn get_binds() -> Vec<SimpleBinder> {
 
     for n in 0..2 {
         fn initiative(bop_shared_state: &mut BoPSharedState, args_usize: usize) -> String {
-            if bop_shared_state.initiatives_to_player_index[0] == args_usize {
-                "先攻"
-            } else {
-                "後攻"
-            }
-            .to_string()
+            bop_shared_state.get_initiative_label(args_usize)
         }
         binds.push(SimpleBinder::new(
             get_element_by_id(format!(
@@ -299,6 +359,22 @@ pub fn get_binds() -> Vec<SimpleBinder> {
         ));
     }
 
+    for n in 0..2 {
+        fn status_effects(bop_shared_state: &mut BoPSharedState, args_usize: usize) -> String {
+            bop_shared_state.players[args_usize]
+                .player_status
+                .get_status_effect_labels()
+        }
+        binds.push(SimpleBinder::new(
+            get_element_by_id(format!(
+                "simple-binder-status-effects-{}",
+                if n == 0 { "a" } else { "b" }
+            )),
+            n,
+            status_effects,
+        ));
+    }
+
     fn bid_cursor(bop_shared_state: &mut BoPSharedState, _: usize) -> String {
         if bop_shared_state.phase_index == 1 {
             "👉".to_string()
@@ -311,5 +387,65 @@ pub fn get_binds() -> Vec<SimpleBinder> {
         0,
         bid_cursor,
     ));
+
+    // 新しいログほど上に表示する。古いログはスクロールして見る
+    fn battle_log(bop_shared_state: &mut BoPSharedState, _: usize) -> String {
+        bop_shared_state
+            .game_logs
+            .iter()
+            .rev()
+            .map(|game_log| {
+                format!(
+                    "<div>[{}] {}</div>",
+                    game_log.turn,
+                    escape_html(&game_log.get_message(bop_shared_state))
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("")
+    }
+    binds.push(SimpleBinder::new(
+        get_element_by_id("simple-binder-battle-log".to_string()),
+        0,
+        battle_log,
+    ));
+
+    // 3人目以降のプレイヤーは1行にまとめて表示する
+    for n in 2..4 {
+        fn player_summary(bop_shared_state: &mut BoPSharedState, args_usize: usize) -> String {
+            let Some(player) = bop_shared_state.players.get(args_usize) else {
+                return "".to_string();
+            };
+            let player_status = &player.player_status;
+            format!(
+                "{} {} HP:{}/{} ATK:{} DEF:{} Money:{}(+{}) アイテム:{} {}",
+                player.player_name,
+                bop_shared_state.get_initiative_label(args_usize),
+                player_status.current_hp,
+                player_status.max_hp,
+                player_status.attack_point,
+                player_status.defence_point,
+                player_status.current_money_amount,
+                player_status.estimated_money_amount,
+                player.own_item_list.len(),
+                player_status.get_status_effect_labels()
+            )
+        }
+        binds.push(SimpleBinder::new(
+            get_element_by_id(format!(
+                "simple-binder-player-summary-{}",
+                if n == 2 { "c" } else { "d" }
+            )),
+            n,
+            player_summary,
+        ));
+    }
     binds
 }
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
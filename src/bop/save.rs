@@ -0,0 +1,68 @@
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+use crate::bop_core::state::phase::PhaseType;
+use crate::bop_core::state::save_data::SaveData;
+use crate::engine::application_types::StateType::BoPShared;
+use crate::engine::state::State;
+use crate::features::storage;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen_test::console_log;
+
+const SAVE_DATA_KEY: &str = "bop-save-data";
+
+// タブの再読み込みなどから対戦に復帰するための情報
+// 接続先のチャンネルとユーザー名が同じでないと、相手に再送信を依頼できない
+#[derive(Serialize, Deserialize)]
+pub struct SavedSession {
+    pub user_name: String,
+    pub channel_name: String,
+    pub is_matched: bool,
+    // バージョンは SaveData 側で管理する
+    save_data: String,
+}
+
+impl SavedSession {
+    // 対戦中であれば保存し、対戦が終わっていれば破棄する
+    pub fn save(state: &State, channel_name: &str) {
+        if let BoPShared(bop_shared_state) = &state.state_type {
+            if bop_shared_state.phases[bop_shared_state.phase_index].phase_type
+                == PhaseType::GameEnd
+            {
+                storage::remove_item(SAVE_DATA_KEY);
+                return;
+            }
+            if bop_shared_state.consumed_seq_no == 0 {
+                return;
+            }
+            let saved_session = SavedSession {
+                user_name: state.user_name.to_string(),
+                channel_name: channel_name.to_string(),
+                is_matched: state.is_matched,
+                save_data: SaveData::new(bop_shared_state).to_json(),
+            };
+            storage::set_item(
+                SAVE_DATA_KEY,
+                &serde_json::to_string(&saved_session).unwrap(),
+            );
+        }
+    }
+
+    pub fn load() -> Option<(SavedSession, BoPSharedState)> {
+        let json = storage::get_item(SAVE_DATA_KEY)?;
+        let loaded = serde_json::from_str::<SavedSession>(&json)
+            .map_err(|error| error.to_string())
+            .and_then(|saved_session| {
+                SaveData::from_json(&saved_session.save_data)
+                    .map(|save_data| (saved_session, save_data.bop_shared_state))
+                    .map_err(|error| error.to_string())
+            });
+        match loaded {
+            Ok(loaded) => Some(loaded),
+            Err(error) => {
+                // 読み込めないセーブデータは残しておいても仕方がないので破棄する
+                console_log!("save data is discarded: {}", error);
+                storage::remove_item(SAVE_DATA_KEY);
+                None
+            }
+        }
+    }
+}
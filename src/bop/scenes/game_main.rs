@@ -1,4 +1,5 @@
-use crate::bop::state::message::{
+use crate::bop_core::rules::rule_set::AuctionMode;
+use crate::bop_core::state::message::{
     AttackTargetMessage, BidMessage, GameStartIsApprovedMessage, UseItemMessage,
 };
 use crate::engine::application_types::SceneType::BoPGameMain;
@@ -8,14 +9,18 @@ use crate::engine::scene::Scene;
 use crate::engine::state::State;
 use crate::features::animation::Animation;
 use crate::svg::element_wrapper::ElementWrapper;
-use crate::svg::svg_renderer::{Cursor, SvgRenderer};
+use crate::svg::svg_renderer::{get_element_by_id, Cursor, SvgRenderer};
 use wasm_bindgen_test::console_log;
 
 pub struct GameMainState {
     renderers: Vec<SvgRenderer>,
     is_bid_confirm_opened: bool,
+    is_bid_pass_confirm_opened: bool,
     is_item_use_confirm_opened: bool,
     is_item_use_skip_confirm_opened: bool,
+    // 対象を選ぶアイテムの場合、使用の確認後に開く
+    is_item_target_select_opened: bool,
+    item_target_candidates: Vec<usize>,
     use_item_cursors: Vec<Cursor>,
 }
 
@@ -31,8 +36,11 @@ impl GameMainState {
                 SvgRenderer::new("game-main-battle".to_string(), 30.0),
             ],
             is_bid_confirm_opened: false,
+            is_bid_pass_confirm_opened: false,
             is_item_use_confirm_opened: false,
             is_item_use_skip_confirm_opened: false,
+            is_item_target_select_opened: false,
+            item_target_candidates: vec![],
             use_item_cursors: vec![
                 Cursor::new(
                     &shared_state.elements.document,
@@ -104,7 +112,11 @@ impl GameMainState {
                         let cursor_index = game_main_state.renderers[0].cursor.chose_index;
                         match input {
                             Input::ArrowRight => {
-                                if game_main_state.is_bid_confirm_opened {
+                                // ダッチ方式では現在の価格でのみ落札できる
+                                if game_main_state.is_bid_confirm_opened
+                                    || game_main_state.is_bid_pass_confirm_opened
+                                    || bop_shared_state.rule_set.auction_mode == AuctionMode::Dutch
+                                {
                                     return;
                                 }
                                 let bid_amount =
@@ -118,28 +130,23 @@ impl GameMainState {
                                     (bid_amount + 1).min(player_money.max(bid_amount));
                             }
                             Input::ArrowLeft => {
-                                if game_main_state.is_bid_confirm_opened {
+                                if game_main_state.is_bid_confirm_opened
+                                    || game_main_state.is_bid_pass_confirm_opened
+                                {
                                     return;
                                 }
                                 let bid_amount =
                                     bop_shared_state.bid_input[cursor_index].bid_amount;
-                                let current_bid_amount = BidMessage::current_bid_amount(
-                                    cursor_index,
-                                    &bop_shared_state.temporary_bid_history,
-                                );
-                                // 現在価格が0なら最低入札価格は1
-                                // 現在価格が1以上なら、最低入札価格は現在価格+2
-                                let lowest_amount = if current_bid_amount == 0 {
-                                    1
-                                } else {
-                                    current_bid_amount + 2
-                                };
+                                let lowest_amount =
+                                    bop_shared_state.lowest_bid_amount(cursor_index);
                                 bop_shared_state.bid_input[cursor_index].bid_amount =
                                     (bid_amount - 1).max(lowest_amount);
                             }
 
                             Input::ArrowDown | Input::ArrowUp => {
-                                let renderer_index = if game_main_state.is_bid_confirm_opened {
+                                let renderer_index = if game_main_state.is_bid_confirm_opened
+                                    || game_main_state.is_bid_pass_confirm_opened
+                                {
                                     1
                                 } else {
                                     0
@@ -159,6 +166,7 @@ impl GameMainState {
                                                 bid_amount: bop_shared_state.bid_input
                                                     [cursor_index]
                                                     .bid_amount,
+                                                is_skipped: false,
                                             })
                                             .unwrap(),
                                         );
@@ -166,10 +174,37 @@ impl GameMainState {
                                     game_main_state.is_bid_confirm_opened = false;
                                     game_main_state.renderers[1].hide();
                                     game_main_state.renderers[1].cursor.reset();
+                                } else if game_main_state.is_bid_pass_confirm_opened {
+                                    if game_main_state.renderers[1].cursor.chose_index == 0 {
+                                        to_send_channel_messages.push(
+                                            serde_json::to_string(&BidMessage {
+                                                seq_no: bop_shared_state.get_seq_no_to_send(),
+                                                player_index: bop_shared_state.own_player_index,
+                                                bid_item_index: 0,
+                                                bid_amount: 0,
+                                                is_skipped: true,
+                                            })
+                                            .unwrap(),
+                                        );
+                                    }
+                                    game_main_state.is_bid_pass_confirm_opened = false;
+                                    game_main_state.renderers[1].hide();
+                                    game_main_state.renderers[1].cursor.reset();
                                 } else {
-                                    let item_name = bop_shared_state.items_bid_on[cursor_index]
-                                        .item_kind
-                                        .get_name();
+                                    if bop_shared_state.rule_set.auction_mode != AuctionMode::Open
+                                        && bop_shared_state
+                                            .get_auction_progress()
+                                            .is_won(cursor_index)
+                                    {
+                                        interrupt_animations.push(vec![Animation::create_message(
+                                            "そのアイテムは落札済みです".to_string(),
+                                            true,
+                                        )]);
+                                        return;
+                                    }
+                                    let item_name = bop_shared_state.item_set.get_name(
+                                        &bop_shared_state.items_bid_on[cursor_index].item_kind,
+                                    );
                                     let amount =
                                         bop_shared_state.bid_input[cursor_index].bid_amount;
                                     if bop_shared_state.players[bop_shared_state.own_player_index]
@@ -186,21 +221,80 @@ impl GameMainState {
                                     game_main_state.renderers[1].render(
                                         vec!["はい".to_string(), "いいえ".to_string()],
                                         vec![],
-                                        format!(
-                                            "{} を {} Moneyで入札しますか？",
-                                            item_name, amount
-                                        )
+                                        match bop_shared_state.rule_set.auction_mode {
+                                            AuctionMode::Open => format!(
+                                                "{} を {} Moneyで入札しますか？",
+                                                item_name, amount
+                                            ),
+                                            AuctionMode::SealedFirstPrice
+                                            | AuctionMode::SecondPrice => format!(
+                                                "{} に {} Moneyで封印入札しますか？",
+                                                item_name, amount
+                                            ),
+                                            AuctionMode::Dutch => format!(
+                                                "{} を {} Moneyで落札しますか？",
+                                                item_name, amount
+                                            ),
+                                        }
                                         .as_str(),
                                     );
                                     game_main_state.is_bid_confirm_opened = true;
                                 }
                             }
+                            // ダッチ方式では、価格が下がるのを待って見送ることができる
+                            Input::Cancel => {
+                                if game_main_state.is_bid_confirm_opened
+                                    || game_main_state.is_bid_pass_confirm_opened
+                                {
+                                    game_main_state.is_bid_confirm_opened = false;
+                                    game_main_state.is_bid_pass_confirm_opened = false;
+                                    game_main_state.renderers[1].hide();
+                                    game_main_state.renderers[1].cursor.reset();
+                                    return;
+                                }
+                                if bop_shared_state.rule_set.auction_mode != AuctionMode::Dutch {
+                                    return;
+                                }
+                                if !bop_shared_state.can_wait_for_lower_price() {
+                                    interrupt_animations.push(vec![Animation::create_message(
+                                        "これ以上価格は下がりません".to_string(),
+                                        true,
+                                    )]);
+                                    return;
+                                }
+                                to_send_channel_messages.push(
+                                    serde_json::to_string(&BidMessage {
+                                        seq_no: bop_shared_state.get_seq_no_to_send(),
+                                        player_index: bop_shared_state.own_player_index,
+                                        bid_item_index: cursor_index,
+                                        bid_amount: 0,
+                                        is_skipped: false,
+                                    })
+                                    .unwrap(),
+                                );
+                            }
+                            // このフェーズでは何も落札せず、獲得Moneyだけを得る
+                            Input::Context => {
+                                if game_main_state.is_bid_confirm_opened
+                                    || game_main_state.is_bid_pass_confirm_opened
+                                {
+                                    return;
+                                }
+                                game_main_state.renderers[1].render(
+                                    vec!["はい".to_string(), "いいえ".to_string()],
+                                    vec![],
+                                    "このフェーズの入札をパスしますか？",
+                                );
+                                game_main_state.is_bid_pass_confirm_opened = true;
+                            }
                             _ => {}
                         }
                     } else if bop_shared_state.phase_index == 2 {
                         match input {
                             Input::ArrowUp | Input::ArrowDown => {
-                                if game_main_state.is_item_use_confirm_opened
+                                if game_main_state.is_item_target_select_opened {
+                                    game_main_state.renderers[2].cursor.consume(input);
+                                } else if game_main_state.is_item_use_confirm_opened
                                     || game_main_state.is_item_use_skip_confirm_opened
                                 {
                                     game_main_state.renderers[1].cursor.consume(input);
@@ -216,26 +310,84 @@ impl GameMainState {
                                 }
                             }
                             Input::Enter => {
-                                if game_main_state.is_item_use_confirm_opened {
+                                if game_main_state.is_item_target_select_opened {
+                                    let player_index = bop_shared_state.own_player_index;
+                                    let cursor_index =
+                                        game_main_state.use_item_cursors[player_index].chose_index;
+                                    let target_player_index = game_main_state
+                                        .item_target_candidates
+                                        [game_main_state.renderers[2].cursor.chose_index];
+                                    game_main_state.use_item_cursors[player_index].reset();
+                                    to_send_channel_messages.push(
+                                        serde_json::to_string(&UseItemMessage {
+                                            seq_no: bop_shared_state.get_seq_no_to_send(),
+                                            turn: bop_shared_state.turn,
+                                            check_is_blocked: false,
+                                            player_index,
+                                            use_item_index: cursor_index,
+                                            is_skipped: false,
+                                            args_i32: vec![],
+                                            args_usize: vec![target_player_index],
+                                        })
+                                        .unwrap(),
+                                    );
+                                    game_main_state.is_item_target_select_opened = false;
+                                    game_main_state.renderers[2].hide();
+                                    game_main_state.renderers[2].cursor.reset();
+                                } else if game_main_state.is_item_use_confirm_opened {
                                     if game_main_state.renderers[1].cursor.chose_index == 0 {
                                         let player_index = bop_shared_state.own_player_index;
                                         let cursor_index = game_main_state.use_item_cursors
                                             [player_index]
                                             .chose_index;
-                                        game_main_state.use_item_cursors[player_index].reset();
-                                        to_send_channel_messages.push(
-                                            serde_json::to_string(&UseItemMessage {
-                                                seq_no: bop_shared_state.get_seq_no_to_send(),
-                                                turn: bop_shared_state.turn,
-                                                check_is_blocked: false,
-                                                player_index: bop_shared_state.own_player_index,
-                                                use_item_index: cursor_index,
-                                                is_skipped: false,
-                                                args_i32: vec![],
-                                                args_usize: vec![],
-                                            })
-                                            .unwrap(),
-                                        );
+                                        let item = &bop_shared_state.players[player_index]
+                                            .own_item_list[cursor_index];
+                                        let candidates = bop_shared_state
+                                            .get_item_target_candidates(
+                                                player_index,
+                                                &bop_shared_state
+                                                    .item_set
+                                                    .get_target(&item.item_kind),
+                                            );
+                                        if candidates.len() > 1 {
+                                            // 対象が複数いる場合は選んでから送信する
+                                            game_main_state.renderers[2].render(
+                                                candidates
+                                                    .iter()
+                                                    .map(|candidate| {
+                                                        bop_shared_state.players[*candidate]
+                                                            .player_name
+                                                            .to_string()
+                                                    })
+                                                    .collect(),
+                                                vec![],
+                                                format!(
+                                                    "{} の対象を選んでください",
+                                                    bop_shared_state
+                                                        .item_set
+                                                        .get_name(&item.item_kind)
+                                                )
+                                                .as_str(),
+                                            );
+                                            update_battle_background_height(candidates.len());
+                                            game_main_state.item_target_candidates = candidates;
+                                            game_main_state.is_item_target_select_opened = true;
+                                        } else {
+                                            game_main_state.use_item_cursors[player_index].reset();
+                                            to_send_channel_messages.push(
+                                                serde_json::to_string(&UseItemMessage {
+                                                    seq_no: bop_shared_state.get_seq_no_to_send(),
+                                                    turn: bop_shared_state.turn,
+                                                    check_is_blocked: false,
+                                                    player_index,
+                                                    use_item_index: cursor_index,
+                                                    is_skipped: false,
+                                                    args_i32: vec![],
+                                                    args_usize: candidates,
+                                                })
+                                                .unwrap(),
+                                            );
+                                        }
                                     }
                                     game_main_state.is_item_use_confirm_opened = false;
                                     game_main_state.renderers[1].hide();
@@ -268,14 +420,21 @@ impl GameMainState {
                                     game_main_state.renderers[1].render(
                                         vec!["はい".to_string(), "いいえ".to_string()],
                                         vec![],
-                                        format!("{} を使用しますか？", item.item_kind.get_name())
-                                            .as_str(),
+                                        format!(
+                                            "{} を使用しますか？",
+                                            bop_shared_state.item_set.get_name(&item.item_kind)
+                                        )
+                                        .as_str(),
                                     );
                                     game_main_state.is_item_use_confirm_opened = true;
                                 }
                             }
                             Input::Cancel => {
-                                if game_main_state.is_item_use_confirm_opened
+                                if game_main_state.is_item_target_select_opened {
+                                    game_main_state.is_item_target_select_opened = false;
+                                    game_main_state.renderers[2].hide();
+                                    game_main_state.renderers[2].cursor.reset();
+                                } else if game_main_state.is_item_use_confirm_opened
                                     || game_main_state.is_item_use_skip_confirm_opened
                                 {
                                     game_main_state.is_item_use_confirm_opened = false;
@@ -283,10 +442,17 @@ impl GameMainState {
                                     game_main_state.renderers[1].hide();
                                     game_main_state.renderers[1].cursor.reset();
                                 } else {
+                                    let message = if bop_shared_state
+                                        .is_chaining(bop_shared_state.own_player_index)
+                                    {
+                                        "アイテムの使用を終えますか？"
+                                    } else {
+                                        "アイテム使用をスキップしますか？"
+                                    };
                                     game_main_state.renderers[1].render(
                                         vec!["はい".to_string(), "いいえ".to_string()],
                                         vec![],
-                                        "アイテム使用をスキップしますか？",
+                                        message,
                                     );
                                     game_main_state.is_item_use_skip_confirm_opened = true;
                                 }
@@ -300,16 +466,21 @@ impl GameMainState {
                             }
                             Input::Enter => {
                                 let player_index = bop_shared_state.own_player_index;
-                                let opponent_player_index =
-                                    bop_shared_state.opponent_player_index(player_index);
-                                let is_skipped =
-                                    game_main_state.renderers[2].cursor.chose_index == 1;
+                                let opponent_player_indexes =
+                                    bop_shared_state.get_opponent_player_indexes(player_index);
+                                // 最後の選択肢は「攻撃しない」
+                                let chose_index = game_main_state.renderers[2].cursor.chose_index;
+                                let is_skipped = chose_index >= opponent_player_indexes.len();
                                 let attack_target_message = AttackTargetMessage {
                                     seq_no: bop_shared_state.get_seq_no_to_send(),
                                     turn: bop_shared_state.turn,
                                     player_index,
                                     check_is_blocked: false,
-                                    attack_target_player_index: opponent_player_index,
+                                    attack_target_player_index: if is_skipped {
+                                        bop_shared_state.opponent_player_index(player_index)
+                                    } else {
+                                        opponent_player_indexes[chose_index]
+                                    },
                                     is_skipped,
                                 };
                                 to_send_channel_messages
@@ -368,36 +539,55 @@ impl GameMainState {
                     let item_names = bop_shared_state
                         .items_bid_on
                         .iter()
-                        .map(|item| item.item_kind.get_name())
+                        .map(|item| bop_shared_state.item_set.get_name(&item.item_kind))
                         .collect();
                     let item_descriptions = bop_shared_state
                         .items_bid_on
                         .iter()
-                        .map(|item| item.item_kind.get_description())
+                        .map(|item| bop_shared_state.item_set.get_description(&item.item_kind))
                         .collect();
                     game_main_state.renderers[0].render(item_names, item_descriptions, "");
-                    match bop_shared_state.phase_index {
-                        3 => {
-                            let opponent_player_name = &bop_shared_state.players[bop_shared_state
-                                .opponent_player_index(bop_shared_state.own_player_index)]
-                            .player_name;
-                            if !bop_shared_state.input_is_guard {
-                                game_main_state.renderers[2].render(
-                                    vec![
-                                        opponent_player_name.to_owned(),
-                                        "攻撃しない(Money+1)".to_string(),
-                                    ],
-                                    vec![],
-                                    format!(
-                                        "{}さん、攻撃対象を選んでください。",
-                                        bop_shared_state.players[bop_shared_state.own_player_index]
-                                            .player_name
-                                    )
-                                    .as_str(),
-                                );
-                            }
+                    // 入札対象の数はプレイヤー数によって変わるので、枠の高さを合わせる
+                    get_element_by_id("render-game-main-bid-background".to_string())
+                        .set_attribute(
+                            "height",
+                            &(45 * bop_shared_state.items_bid_on.len() + 25).to_string(),
+                        )
+                        .unwrap();
+                    if bop_shared_state.phase_index == 3 {
+                        let mut labels = bop_shared_state
+                            .get_opponent_player_indexes(bop_shared_state.own_player_index)
+                            .iter()
+                            .map(|opponent_player_index| {
+                                bop_shared_state.players[*opponent_player_index]
+                                    .player_name
+                                    .to_string()
+                            })
+                            .collect::<Vec<String>>();
+                        labels.push(format!(
+                            "攻撃しない(Money+{})",
+                            bop_shared_state.rule_set.attack_skip_money
+                        ));
+                        update_battle_background_height(labels.len());
+                        if !bop_shared_state.input_is_guard {
+                            game_main_state.renderers[2].render(
+                                labels,
+                                vec![],
+                                format!(
+                                    "{}さん、{}攻撃対象を選んでください。",
+                                    bop_shared_state.players[bop_shared_state.own_player_index]
+                                        .player_name,
+                                    if bop_shared_state
+                                        .is_chaining(bop_shared_state.own_player_index)
+                                    {
+                                        "続けて"
+                                    } else {
+                                        ""
+                                    }
+                                )
+                                .as_str(),
+                            );
                         }
-                        _ => {}
                     }
                 }
             }
@@ -405,3 +595,10 @@ impl GameMainState {
         on_update_state_func
     }
 }
+
+// 選択肢の数は生存している相手の数によって変わるので、枠の高さを合わせる
+fn update_battle_background_height(choice_length: usize) {
+    get_element_by_id("render-game-main-battle-background".to_string())
+        .set_attribute("height", &(30 * choice_length + 25).to_string())
+        .unwrap();
+}
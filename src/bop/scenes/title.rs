@@ -1,4 +1,7 @@
-use crate::bop::state::message::GameStartIsApprovedMessage;
+use crate::bop_core::random::SeededRng;
+use crate::bop_core::rules::rule_set::RuleSet;
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+use crate::bop_core::state::message::GameStartIsApprovedMessage;
 use crate::engine::application_types::SceneType::BoPTitle;
 use crate::engine::application_types::StateType::BoPShared;
 use crate::engine::input::Input;
@@ -7,7 +10,7 @@ use crate::engine::state::State;
 use crate::features::animation::Animation;
 use crate::features::websocket::{ChannelMessage, MessageType};
 use crate::svg::element_wrapper::ElementWrapper;
-use crate::svg::svg_renderer::Cursor;
+use crate::svg::svg_renderer::{get_element_by_id, Cursor};
 
 pub struct TitleState {
     cursor: Cursor,
@@ -17,7 +20,7 @@ impl TitleState {
     pub fn create_title_scene(shared_state: &mut State) -> Scene {
         let document = &shared_state.elements.document;
         let title_state = TitleState {
-            cursor: Cursor::new(document, "title-cursor", 3, 60.0),
+            cursor: Cursor::new(document, "title-cursor", 9, 50.0),
         };
         let consume_func = title_state.create_consume_func();
         let init_func = title_state.create_init_func();
@@ -96,6 +99,21 @@ impl TitleState {
                                     .unwrap(),
                                 );
                             } else if title_state.cursor.chose_index == 2 {
+                                // 相手が同じアイテムセットを持っているとは限らないので、同梱のものに戻す
+                                if bop_shared_state.reset_item_set_for_online() {
+                                    get_element_by_id("title-deck-list".to_string())
+                                        .set_inner_html(&format!(
+                                            "山札: {}",
+                                            bop_shared_state.get_deck_list_name()
+                                        ));
+                                    shared_state.interrupt_animations.push(vec![
+                                        Animation::create_message(
+                                            "オンライン対戦では同梱のアイテムセットと山札を使います"
+                                                .to_string(),
+                                            false,
+                                        ),
+                                    ]);
+                                }
                                 to_send_channel_messages.push(
                                     serde_json::to_string(&ChannelMessage {
                                         user_name: shared_state.user_name.to_string(),
@@ -112,6 +130,91 @@ impl TitleState {
                                     ),
                                 ]);
                                 return;
+                            } else if title_state.cursor.chose_index == 3 {
+                                // プレイヤー1以外の3人をCPUが操作する
+                                // 読み込み済みのアイテムセットと選んだルール・山札・CPUの設定は引き継ぐ
+                                let item_set = bop_shared_state.item_set.clone();
+                                let rule_set = bop_shared_state.rule_set.clone();
+                                let deck_list_id = bop_shared_state.deck_list_id.clone();
+                                let cpu_profile = bop_shared_state.cpu_profile;
+                                **bop_shared_state =
+                                    BoPSharedState::init_for_players(SeededRng::random_seed(), 4);
+                                bop_shared_state.update_item_set(item_set);
+                                bop_shared_state.update_rule_set(rule_set);
+                                let _ = bop_shared_state.select_deck_list(&deck_list_id);
+                                bop_shared_state.cpu_profile = cpu_profile;
+                                bop_shared_state.has_cpu = true;
+                                shared_state.primitives.requested_scene_index = 1;
+                                for player_index in 0..bop_shared_state.players.len() {
+                                    to_send_channel_messages.push(
+                                        serde_json::to_string(&GameStartIsApprovedMessage {
+                                            player_index,
+                                            game_start_is_approved: true,
+                                        })
+                                        .unwrap(),
+                                    );
+                                }
+                            } else if title_state.cursor.chose_index == 4 {
+                                // 選ぶたびに次のルールに切り替える
+                                // オンライン対戦ではマッチングを受けた側のルールになる
+                                // 入札の方式は別に選ぶので引き継ぐ
+                                let preset = bop_shared_state.rule_set.preset.next();
+                                bop_shared_state.update_rule_set(RuleSet {
+                                    auction_mode: bop_shared_state.rule_set.auction_mode,
+                                    ..preset.get_rule_set()
+                                });
+                                get_element_by_id("title-rule-set".to_string())
+                                    .set_inner_html(&format!("ルール: {}", preset.get_name()));
+                                return;
+                            } else if title_state.cursor.chose_index == 5 {
+                                // 選ぶたびにアイテムセットの次の山札に切り替える
+                                // オンライン対戦ではマッチングを受けた側の山札になる
+                                let deck_lists = bop_shared_state.item_set.get_deck_lists();
+                                let index = deck_lists
+                                    .iter()
+                                    .position(|deck_list| {
+                                        deck_list.id == bop_shared_state.deck_list_id
+                                    })
+                                    .map_or(0, |index| (index + 1) % deck_lists.len());
+                                let text = match bop_shared_state
+                                    .select_deck_list(&deck_lists[index].id)
+                                {
+                                    Ok(()) => format!("山札: {}", deck_lists[index].get_name()),
+                                    Err(error) => format!("山札: {}", error),
+                                };
+                                get_element_by_id("title-deck-list".to_string())
+                                    .set_inner_html(&text);
+                                return;
+                            } else if title_state.cursor.chose_index == 6 {
+                                // 選ぶたびに次の入札の方式に切り替える
+                                let auction_mode = bop_shared_state.rule_set.auction_mode.next();
+                                bop_shared_state.update_rule_set(RuleSet {
+                                    auction_mode,
+                                    ..bop_shared_state.rule_set.clone()
+                                });
+                                get_element_by_id("title-auction-mode".to_string())
+                                    .set_inner_html(&format!("入札: {}", auction_mode.get_name()));
+                                return;
+                            } else if title_state.cursor.chose_index == 7 {
+                                // 選ぶたびに次の強さに切り替える
+                                let difficulty = bop_shared_state.cpu_profile.difficulty.next();
+                                bop_shared_state.cpu_profile.difficulty = difficulty;
+                                get_element_by_id("title-cpu-difficulty".to_string())
+                                    .set_inner_html(&format!(
+                                        "CPUの強さ: {}",
+                                        difficulty.get_name()
+                                    ));
+                                return;
+                            } else if title_state.cursor.chose_index == 8 {
+                                // 選ぶたびに次の性格に切り替える
+                                let personality = bop_shared_state.cpu_profile.personality.next();
+                                bop_shared_state.cpu_profile.personality = personality;
+                                get_element_by_id("title-cpu-personality".to_string())
+                                    .set_inner_html(&format!(
+                                        "CPUの性格: {}",
+                                        personality.get_name()
+                                    ));
+                                return;
                             }
                             shared_state
                                 .interrupt_animations
@@ -0,0 +1,8 @@
+// ゲームのルール本体
+// web_sys / wasm_bindgen に依存しないので、ネイティブのテストやサーバー、ツールからも利用できる
+pub mod cpu_player;
+pub mod mechanism;
+pub mod random;
+pub mod replay;
+pub mod rules;
+pub mod state;
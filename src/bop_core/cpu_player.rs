@@ -0,0 +1,394 @@
+use crate::bop_core::cpu_player::item_value::estimate_fair_bid_amount;
+use crate::bop_core::cpu_player::mcts::{SearchConfig, SearchResult};
+use crate::bop_core::random::SeededRng;
+use crate::bop_core::rules::rule_set::AuctionMode;
+use crate::bop_core::rules::{apply, validate, Command};
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+use crate::bop_core::state::message::{AttackTargetMessage, BidMessage, UseItemMessage};
+use crate::bop_core::state::phase::PhaseType::GameEnd;
+
+pub mod evaluator;
+pub mod item_value;
+pub mod mcts;
+pub mod profile;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod search_task;
+
+// 決着がつかないプレイアウトを打ち切る入力の数（通常は数百以内で決着する）
+pub const MAX_PLAYOUT_STEPS: usize = 3000;
+
+pub struct CPUPlayer {
+    pub bop_shared_state: BoPSharedState,
+}
+
+impl CPUPlayer {
+    pub fn create_cpu_message(&mut self, index: usize) -> String {
+        create_command(&self.bop_shared_state, index).to_message()
+    }
+    pub fn new(bop_shared_state: &BoPSharedState) -> CPUPlayer {
+        let bop_shared_state_cloned = &mut bop_shared_state.clone();
+        for player in bop_shared_state_cloned.players.iter_mut() {
+            player.game_start_is_approved = true;
+        }
+        bop_shared_state_cloned.advance_phase();
+        CPUPlayer {
+            bop_shared_state: bop_shared_state_cloned.clone(),
+        }
+    }
+    // 指定したプレイヤーの入力を MCTS で選ぶ
+    // 結果の input を create_cpu_message に渡すと送信するメッセージになる
+    pub fn search(&self, simulating_player: usize, config: &SearchConfig) -> SearchResult {
+        mcts::search(&self.bop_shared_state, simulating_player, config)
+    }
+}
+
+// 入札するアイテムごとに選べる金額の決め方
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BidAmountKind {
+    // 最低入札価格
+    Minimum,
+    // 最低入札価格に、入札単位の N 倍を上乗せする
+    Raise(u32),
+    // アイテムの価値の見積もり（item_value::estimate_fair_bid_amount）
+    FairValue,
+    // 持っている Money すべて
+    AllIn,
+}
+
+pub const BID_AMOUNT_KINDS: [BidAmountKind; 5] = [
+    BidAmountKind::Minimum,
+    BidAmountKind::Raise(2),
+    BidAmountKind::Raise(5),
+    BidAmountKind::FairValue,
+    BidAmountKind::AllIn,
+];
+
+// 入札フェーズの入力の意味
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BidInput {
+    Bid {
+        item_index: usize,
+        amount_kind: BidAmountKind,
+    },
+    // ダッチ方式で、今の価格では落札せずに見送る
+    Wait,
+    Pass,
+}
+
+impl BidInput {
+    // アイテムごとに BID_AMOUNT_KINDS の数だけ入力が並び、その後に見送りとパスが続く
+    pub fn from_input(bop_shared_state: &BoPSharedState, input: usize) -> BidInput {
+        let bid_inputs_len = bop_shared_state.items_bid_on.len() * BID_AMOUNT_KINDS.len();
+        if input < bid_inputs_len {
+            BidInput::Bid {
+                item_index: input / BID_AMOUNT_KINDS.len(),
+                amount_kind: BID_AMOUNT_KINDS[input % BID_AMOUNT_KINDS.len()],
+            }
+        } else if input == bid_inputs_len {
+            BidInput::Wait
+        } else {
+            BidInput::Pass
+        }
+    }
+
+    pub fn to_input(&self, bop_shared_state: &BoPSharedState) -> usize {
+        let bid_inputs_len = bop_shared_state.items_bid_on.len() * BID_AMOUNT_KINDS.len();
+        match self {
+            BidInput::Bid {
+                item_index,
+                amount_kind,
+            } => {
+                item_index * BID_AMOUNT_KINDS.len()
+                    + BID_AMOUNT_KINDS
+                        .iter()
+                        .position(|kind| kind == amount_kind)
+                        .unwrap()
+            }
+            BidInput::Wait => bid_inputs_len,
+            BidInput::Pass => bid_inputs_len + 1,
+        }
+    }
+}
+
+// 金額の決め方から実際の入札額を決める
+// 持っている Money を超える場合もあり、その入力はルール上選べない
+pub fn get_bid_amount(
+    bop_shared_state: &BoPSharedState,
+    player_index: usize,
+    item_index: usize,
+    amount_kind: BidAmountKind,
+) -> u32 {
+    let lowest_bid_amount = bop_shared_state.lowest_bid_amount(item_index);
+    let current_money_amount = bop_shared_state.players[player_index]
+        .player_status
+        .current_money_amount;
+    match amount_kind {
+        BidAmountKind::Minimum => lowest_bid_amount,
+        BidAmountKind::Raise(steps) => {
+            lowest_bid_amount + steps * bop_shared_state.rule_set.bid_increment.max(1)
+        }
+        BidAmountKind::FairValue => estimate_fair_bid_amount(
+            bop_shared_state,
+            player_index,
+            &bop_shared_state.items_bid_on[item_index].item_kind,
+        )
+        .max(lowest_bid_amount),
+        BidAmountKind::AllIn => current_money_amount.max(lowest_bid_amount),
+    }
+}
+
+// CPUの入力はフェーズごとのインデックスで表す
+// Bid: BidInput を参照
+// UseItem: 使用するアイテム。所持数と同じならスキップ
+// AttackTarget: 攻撃する相手。相手の数と同じならスキップ
+pub fn create_command(bop_shared_state: &BoPSharedState, index: usize) -> Command {
+    let player_index = bop_shared_state.own_player_index;
+    let turn = bop_shared_state.turn;
+    let seq_no = bop_shared_state.consumed_seq_no + 1;
+    match bop_shared_state.phase_index {
+        1 => {
+            let (bid_item_index, bid_amount, is_skipped) =
+                match BidInput::from_input(bop_shared_state, index) {
+                    BidInput::Bid {
+                        item_index,
+                        amount_kind,
+                    } => (
+                        item_index,
+                        get_bid_amount(bop_shared_state, player_index, item_index, amount_kind),
+                        false,
+                    ),
+                    BidInput::Wait => (0, 0, false),
+                    BidInput::Pass => (0, 0, true),
+                };
+            Command::Bid(BidMessage {
+                seq_no,
+                player_index,
+                bid_item_index,
+                bid_amount,
+                is_skipped,
+            })
+        }
+        2 => {
+            let item_len = bop_shared_state.players[player_index].own_item_list.len();
+            Command::UseItem(UseItemMessage {
+                seq_no,
+                turn,
+                check_is_blocked: false,
+                player_index,
+                use_item_index: index,
+                is_skipped: index == item_len,
+                args_i32: vec![],
+                args_usize: vec![],
+            })
+        }
+        3 => {
+            let opponent_player_indexes =
+                bop_shared_state.get_opponent_player_indexes(player_index);
+            Command::AttackTarget(AttackTargetMessage {
+                seq_no,
+                turn,
+                player_index,
+                check_is_blocked: false,
+                attack_target_player_index: *opponent_player_indexes
+                    .get(index)
+                    .unwrap_or(&bop_shared_state.opponent_player_index(player_index)),
+                is_skipped: index == opponent_player_indexes.len(),
+            })
+        }
+        _ => {
+            panic!()
+        }
+    }
+}
+
+// 入力待ちのプレイヤーを own_player_index にする。決着がついていれば false
+pub fn ready_next_input(bop_shared_state: &mut BoPSharedState) -> bool {
+    let game_end_phase_index = GameEnd as i32 as usize;
+    let check_result = bop_shared_state.check_phase_complete(false);
+    if bop_shared_state.phase_index == game_end_phase_index
+        || check_result.next_phase_index == Some(game_end_phase_index)
+    {
+        bop_shared_state.phase_index = game_end_phase_index;
+        return false;
+    }
+    true
+}
+
+// own_player_index のプレイヤーがルール上選べる入力
+// パスやスキップは常に選べるので、空になることはない
+// 同じアイテムに同じ金額で入札する入力は、最初の1つだけにする
+pub fn get_legal_inputs(bop_shared_state: &mut BoPSharedState) -> Vec<usize> {
+    let player_index = bop_shared_state.own_player_index;
+    let inputs_len = match bop_shared_state.phase_index {
+        1 => bop_shared_state.items_bid_on.len() * BID_AMOUNT_KINDS.len() + 2,
+        2 => bop_shared_state.players[player_index].own_item_list.len() + 1,
+        3 => {
+            bop_shared_state
+                .get_opponent_player_indexes(player_index)
+                .len()
+                + 1
+        }
+        _ => 0,
+    };
+    let mut legal_inputs = vec![];
+    let mut bids = vec![];
+    for index in 0..inputs_len {
+        let command = create_command(bop_shared_state, index);
+        if let Command::Bid(message) = &command {
+            let bid = (
+                message.bid_item_index,
+                message.bid_amount,
+                message.is_skipped,
+            );
+            if bids.contains(&bid) {
+                continue;
+            }
+            bids.push(bid);
+        }
+        if validate(bop_shared_state, &command).is_ok() {
+            legal_inputs.push(index);
+        }
+    }
+    legal_inputs
+}
+
+// 入力を適用する。ルール上選べない入力だった場合は、選べる入力のうち最初のものにする
+pub fn apply_input(bop_shared_state: &mut BoPSharedState, index: usize) {
+    if apply(bop_shared_state, create_command(bop_shared_state, index)).is_ok() {
+        return;
+    }
+    let legal_inputs = get_legal_inputs(bop_shared_state);
+    apply(
+        bop_shared_state,
+        create_command(bop_shared_state, legal_inputs[0]),
+    )
+    .unwrap();
+}
+
+// プレイアウトで使う入力の乱択。完全な乱択より少しだけ実際の対戦に近づける
+pub fn choose_random_input(bop_shared_state: &mut BoPSharedState, rng: &mut SeededRng) -> usize {
+    let player_index = bop_shared_state.own_player_index;
+    match bop_shared_state.phase_index {
+        1 => {
+            let mut biddable = bop_shared_state
+                .get_biddable_item_indexes(player_index)
+                .into_iter()
+                .map(Some)
+                .collect::<Vec<Option<usize>>>();
+            // None はダッチ方式での見送り
+            if bop_shared_state.can_wait_for_lower_price() {
+                biddable.push(None);
+            }
+            // 他にできることがない場合だけパスする
+            if biddable.is_empty() {
+                return BidInput::Pass.to_input(bop_shared_state);
+            }
+            // 競り上げ方式では、既に入札されているアイテムを優先して競り合う
+            let has_bid = biddable.iter().copied().flatten().find(|bid_item_index| {
+                bop_shared_state.rule_set.auction_mode == AuctionMode::Open
+                    && bop_shared_state.bid_input[*bid_item_index].bid_amount > 1
+            });
+            let bid_item_index = biddable[rng.gen_index(biddable.len())];
+            let bid_item_index = match has_bid {
+                Some(has_bid) if rng.gen_bool(0.3) => has_bid,
+                _ => match bid_item_index {
+                    Some(bid_item_index) => bid_item_index,
+                    None => return BidInput::Wait.to_input(bop_shared_state),
+                },
+            };
+            // アイテムの価値の見積もりを超えない範囲で、上乗せする額を選ぶ
+            let current_money_amount = bop_shared_state.players[player_index]
+                .player_status
+                .current_money_amount;
+            let fair_bid_amount = get_bid_amount(
+                bop_shared_state,
+                player_index,
+                bid_item_index,
+                BidAmountKind::FairValue,
+            );
+            let amount_kinds = [
+                BidAmountKind::Minimum,
+                BidAmountKind::Raise(2),
+                BidAmountKind::Raise(5),
+            ]
+            .iter()
+            .copied()
+            .filter(|amount_kind| {
+                let bid_amount =
+                    get_bid_amount(bop_shared_state, player_index, bid_item_index, *amount_kind);
+                *amount_kind == BidAmountKind::Minimum
+                    || bid_amount <= fair_bid_amount.min(current_money_amount)
+            })
+            .collect::<Vec<BidAmountKind>>();
+            BidInput::Bid {
+                item_index: bid_item_index,
+                amount_kind: amount_kinds[rng.gen_index(amount_kinds.len())],
+            }
+            .to_input(bop_shared_state)
+        }
+        // 使用できないアイテムを選ばないよう、ルール上選べる入力から選ぶ
+        2 => {
+            let legal_inputs = get_legal_inputs(bop_shared_state);
+            legal_inputs[rng.gen_index(legal_inputs.len())]
+        }
+        3 => {
+            let opponent_player_indexes_len = bop_shared_state
+                .get_opponent_player_indexes(player_index)
+                .len();
+            if rng.gen_bool(0.8) {
+                rng.gen_index(opponent_player_indexes_len)
+            } else {
+                opponent_player_indexes_len
+            }
+        }
+        _ => 0,
+    }
+}
+
+// 決着がつくまで、または max_steps 回の入力まで全員の入力を乱択で進める
+pub fn playout(bop_shared_state: &mut BoPSharedState, rng: &mut SeededRng, max_steps: usize) {
+    for _ in 0..max_steps {
+        if !ready_next_input(bop_shared_state) {
+            return;
+        }
+        let index = choose_random_input(bop_shared_state, rng);
+        apply_input(bop_shared_state, index);
+    }
+}
+
+// 封印入札では、まだ公開されていない他のプレイヤーの入札を知らないものとして扱う
+// 探索のたびにありうる入札を引き直すことで、見えていない入札に依存した手を選ばないようにする
+pub fn redraw_sealed_bids(
+    bop_shared_state: &mut BoPSharedState,
+    simulating_player: usize,
+    rng: &mut SeededRng,
+) {
+    if !bop_shared_state.rule_set.auction_mode.is_sealed() {
+        return;
+    }
+    let progress = bop_shared_state.get_auction_progress();
+    let hidden_seq_nos = progress
+        .round_bids
+        .iter()
+        .filter(|bid| bid.player_index != simulating_player)
+        .map(|bid| bid.seq_no)
+        .collect::<Vec<usize>>();
+    let item_indexes = (0..bop_shared_state.items_bid_on.len())
+        .filter(|item_index| !progress.is_won(*item_index))
+        .collect::<Vec<usize>>();
+    let reserve_price = bop_shared_state.rule_set.reserve_price;
+    for bid in bop_shared_state.temporary_bid_history.iter_mut() {
+        if !hidden_seq_nos.contains(&bid.seq_no) {
+            continue;
+        }
+        let current_money_amount = bop_shared_state.players[bid.player_index]
+            .player_status
+            .current_money_amount;
+        // 最低落札価格を払えないプレイヤーはパスしたものとする
+        bid.is_skipped = current_money_amount < reserve_price;
+        bid.bid_item_index = item_indexes[rng.gen_index(item_indexes.len())];
+        bid.bid_amount = reserve_price
+            + rng.gen_index((current_money_amount.max(reserve_price) - reserve_price) as usize + 1)
+                as u32;
+    }
+}
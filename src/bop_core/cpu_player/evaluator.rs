@@ -0,0 +1,244 @@
+use crate::bop_core::cpu_player::item_value::estimate_item_value;
+use crate::bop_core::cpu_player::{apply_input, get_bid_amount, BidInput};
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// 決着がついていない state を評価するための重み。JSON から読み込んで調整できる
+// JSON で省略した重みは既定の値になる
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EvaluationWeights {
+    // 最大HPに対する現在のHPの割合
+    pub hp_ratio: f64,
+    // 1ターンに相手へ与えるダメージと相手から受けるダメージの差（それぞれ受ける側の最大HPに対する割合）
+    pub damage_per_turn: f64,
+    pub money: f64,
+    // 毎ターンの獲得 Money
+    pub gain: f64,
+    // 持っているアイテムの価値の合計（Money 換算）
+    pub item_value: f64,
+}
+
+impl Default for EvaluationWeights {
+    fn default() -> EvaluationWeights {
+        EvaluationWeights {
+            hp_ratio: 3.0,
+            damage_per_turn: 8.0,
+            money: 0.05,
+            gain: 0.3,
+            item_value: 0.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvaluationWeightsError {
+    MalformedWeights(String),
+}
+
+impl fmt::Display for EvaluationWeightsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvaluationWeightsError::MalformedWeights(message) => {
+                write!(f, "評価の重みを解釈できません: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EvaluationWeightsError {}
+
+impl EvaluationWeights {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<EvaluationWeights, EvaluationWeightsError> {
+        serde_json::from_str::<EvaluationWeights>(json)
+            .map_err(|error| EvaluationWeightsError::MalformedWeights(error.to_string()))
+    }
+}
+
+// 重みを使った既定の評価。生存しているプレイヤーについて、高いほど有利
+pub fn evaluate_by_weights(
+    bop_shared_state: &BoPSharedState,
+    player_index: usize,
+    weights: &EvaluationWeights,
+) -> f64 {
+    let player_status = &bop_shared_state.players[player_index].player_status;
+    let opponent_player_indexes = bop_shared_state.get_opponent_player_indexes(player_index);
+    let opponents_len = opponent_player_indexes.len().max(1) as f64;
+    // 攻撃は1ターンに1人にしかできないので、相手ごとの平均で比べる
+    let damage_per_turn = opponent_player_indexes
+        .iter()
+        .map(|opponent_player_index| {
+            let opponent_status = &bop_shared_state.players[*opponent_player_index].player_status;
+            let dealt = opponent_status.get_damage(player_status.attack_point) as f64
+                / opponent_status.max_hp.max(1) as f64;
+            let taken = player_status.get_damage(opponent_status.attack_point) as f64
+                / player_status.max_hp.max(1) as f64;
+            dealt - taken
+        })
+        .sum::<f64>()
+        / opponents_len;
+    let item_value = bop_shared_state.players[player_index]
+        .own_item_list
+        .iter()
+        .map(|item| {
+            estimate_item_value(
+                bop_shared_state,
+                player_index,
+                &item.item_kind,
+                player_status.current_money_amount,
+            )
+        })
+        .sum::<f64>();
+    weights.hp_ratio * player_status.current_hp as f64 / player_status.max_hp.max(1) as f64
+        + weights.damage_per_turn * damage_per_turn
+        + weights.money * player_status.current_money_amount as f64
+        + weights.gain * player_status.estimated_money_amount as f64
+        + weights.item_value * item_value
+}
+
+// 探索で使う評価関数。evaluate_func を差し替えると重み以外の評価も試せる
+#[derive(Clone, Debug)]
+pub struct Evaluator {
+    pub weights: EvaluationWeights,
+    pub evaluate_func: fn(&BoPSharedState, usize, &EvaluationWeights) -> f64,
+}
+
+impl Default for Evaluator {
+    fn default() -> Evaluator {
+        Evaluator::init(EvaluationWeights::default())
+    }
+}
+
+impl Evaluator {
+    pub fn init(weights: EvaluationWeights) -> Evaluator {
+        Evaluator {
+            weights,
+            evaluate_func: evaluate_by_weights,
+        }
+    }
+
+    pub fn evaluate(&self, bop_shared_state: &BoPSharedState, player_index: usize) -> f64 {
+        (self.evaluate_func)(bop_shared_state, player_index, &self.weights)
+    }
+
+    // 決着がついていない state での、勝ちを 1.0 とした報酬の見込み
+    // 脱落したプレイヤーは 0.0 で、生存しているプレイヤーが評価値に応じて 1.0 を分け合う（softmax）
+    pub fn get_rewards(&self, bop_shared_state: &BoPSharedState) -> Vec<f64> {
+        let scores = (0..bop_shared_state.players.len())
+            .map(|player_index| {
+                (!bop_shared_state.players[player_index].is_lose())
+                    .then(|| self.evaluate(bop_shared_state, player_index))
+            })
+            .collect::<Vec<Option<f64>>>();
+        let max_score = scores.iter().flatten().copied().fold(f64::MIN, f64::max);
+        let exps = scores
+            .iter()
+            .map(|score| score.map_or(0.0, |score| (score - max_score).exp()))
+            .collect::<Vec<f64>>();
+        let total = exps.iter().sum::<f64>();
+        if total == 0.0 {
+            return vec![1.0 / exps.len() as f64; exps.len()];
+        }
+        exps.iter().map(|exp| exp / total).collect()
+    }
+
+    // own_player_index のプレイヤーが input を選んだ直後の報酬の見込み（1手先だけを読む）
+    // 入札は結果が出るまで state に表れないので、その金額で落札できたものとして評価する
+    pub fn evaluate_input(&self, bop_shared_state: &BoPSharedState, input: usize) -> f64 {
+        let player_index = bop_shared_state.own_player_index;
+        let mut bop_shared_state = bop_shared_state.clone();
+        match (
+            bop_shared_state.phase_index,
+            BidInput::from_input(&bop_shared_state, input),
+        ) {
+            (
+                1,
+                BidInput::Bid {
+                    item_index,
+                    amount_kind,
+                },
+            ) => {
+                let bid_amount =
+                    get_bid_amount(&bop_shared_state, player_index, item_index, amount_kind);
+                let item = bop_shared_state.items_bid_on[item_index].clone();
+                let player = &mut bop_shared_state.players[player_index];
+                player.player_status.current_money_amount = player
+                    .player_status
+                    .current_money_amount
+                    .saturating_sub(bid_amount);
+                player.own_item_list.push(item);
+            }
+            _ => apply_input(&mut bop_shared_state, input),
+        }
+        self.get_rewards(&bop_shared_state)[player_index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bop_core::rules::tests::start_game;
+
+    // player_index のプレイヤーが HP と ATK で大きく上回っている state
+    fn create_winning_state(player_index: usize) -> BoPSharedState {
+        let mut state = start_game(1, 2);
+        for (index, player) in state.players.iter_mut().enumerate() {
+            let player_status = &mut player.player_status;
+            if index == player_index {
+                player_status.current_hp = player_status.max_hp;
+                player_status.attack_point += 10;
+            } else {
+                player_status.current_hp = 1;
+            }
+        }
+        state
+    }
+
+    #[test]
+    fn winning_position_is_ranked_above_losing_position() {
+        let evaluator = Evaluator::default();
+        let winning_state = create_winning_state(0);
+        let losing_state = create_winning_state(1);
+        assert!(evaluator.evaluate(&winning_state, 0) > evaluator.evaluate(&losing_state, 0));
+        assert!(evaluator.evaluate(&winning_state, 0) > evaluator.evaluate(&winning_state, 1));
+
+        let winning_rewards = evaluator.get_rewards(&winning_state);
+        let losing_rewards = evaluator.get_rewards(&losing_state);
+        assert!(winning_rewards[0] > 0.5);
+        assert!(winning_rewards[0] > losing_rewards[0]);
+        assert!((winning_rewards.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn eliminated_player_gets_no_reward() {
+        let evaluator = Evaluator::default();
+        let mut state = create_winning_state(0);
+        state.players[1].player_status.current_hp = 0;
+        assert_eq!(evaluator.get_rewards(&state), vec![1.0, 0.0]);
+    }
+
+    #[test]
+    fn omitted_weights_are_default() {
+        let weights = EvaluationWeights::from_json("{\"money\": 1.0}").unwrap();
+        assert_eq!(
+            weights,
+            EvaluationWeights {
+                money: 1.0,
+                ..EvaluationWeights::default()
+            }
+        );
+        assert_eq!(
+            EvaluationWeights::from_json(&weights.to_json()),
+            Ok(weights)
+        );
+        assert!(matches!(
+            EvaluationWeights::from_json("{\"unknown\": 1.0}"),
+            Err(EvaluationWeightsError::MalformedWeights(_))
+        ));
+    }
+}
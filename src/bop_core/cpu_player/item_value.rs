@@ -0,0 +1,261 @@
+use crate::bop_core::mechanism::item::ItemKind;
+use crate::bop_core::mechanism::item_set::{EffectTarget, ItemEffect};
+use crate::bop_core::mechanism::player_status::{PlayerStatus, Stat};
+use crate::bop_core::mechanism::status_effect::StatusEffectKind;
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+
+// 受けるダメージ 1 あたりの Money 換算。HP の回復や、1回だけの攻撃・防御の効果に使う
+const DAMAGE_VALUE: f64 = 0.3;
+
+// ステータス 1 あたりの Money 換算
+// ATK・DEF は対戦の終わりまで効くので、1回だけのダメージより高くする
+fn get_stat_value(stat: Stat, player_status: &PlayerStatus) -> f64 {
+    match stat {
+        Stat::Attack => 1.0,
+        Stat::Defence => 0.8,
+        Stat::Hp => DAMAGE_VALUE,
+        Stat::MaxHp => 0.2,
+        Stat::Money => 1.0,
+        // 毎ターンの獲得 Money なので、数ターン分の Money に相当する
+        Stat::Gain => 3.0,
+        Stat::ExtraItemUse => 3.0,
+        // 追加の攻撃1回分のダメージ
+        Stat::ExtraAttack => player_status.attack_point as f64 * DAMAGE_VALUE,
+    }
+}
+
+// 効果量・ターンあたりの Money 換算。相手にかかると損になる効果は負の値
+fn get_status_effect_value(kind: StatusEffectKind) -> f64 {
+    match kind {
+        StatusEffectKind::Regen | StatusEffectKind::Shield | StatusEffectKind::Thorns => {
+            DAMAGE_VALUE
+        }
+        StatusEffectKind::Poison | StatusEffectKind::Vulnerable => -DAMAGE_VALUE,
+        StatusEffectKind::MoneyDrain => -1.0,
+    }
+}
+
+// アイテムを player_index のプレイヤーが使った場合の価値を Money に換算する（CPUの入札用）
+// money_at_use は使う時点の Money。入札で払った後の Money を渡すと、
+// ゴールデンダガーのように Money に比例する効果は払うほど価値が下がる
+// 対象はCPUが使う場合と同じく、指定しなかった場合の対象で見積もる
+pub fn estimate_item_value(
+    bop_shared_state: &BoPSharedState,
+    player_index: usize,
+    item_kind: &ItemKind,
+    money_at_use: u32,
+) -> f64 {
+    let Some(item_definition) = bop_shared_state.item_set.get(item_kind) else {
+        return 0.0;
+    };
+    let Some(target_player_indexes) = bop_shared_state.resolve_item_target_player_indexes(
+        player_index,
+        &item_definition.target,
+        None,
+    ) else {
+        return 0.0;
+    };
+    // 相手1人への効果は、相手が多いほど自分の有利につながりにくい
+    let opponents_len = bop_shared_state
+        .get_opponent_player_indexes(player_index)
+        .len()
+        .max(1) as f64;
+    let get_sign = |target_player_index: usize| {
+        if target_player_index == player_index {
+            1.0
+        } else {
+            -1.0 / opponents_len
+        }
+    };
+    let get_status = |target_player_index: usize| {
+        let mut player_status = bop_shared_state.players[target_player_index]
+            .player_status
+            .clone();
+        if target_player_index == player_index {
+            player_status.current_money_amount = money_at_use;
+        }
+        player_status
+    };
+    let get_effect_player_indexes = |to: &EffectTarget| match to {
+        EffectTarget::Own => vec![player_index],
+        EffectTarget::Targets => target_player_indexes.clone(),
+        EffectTarget::OwnAndTargets => [vec![player_index], target_player_indexes.clone()].concat(),
+    };
+    // 1人のステータスの変化量を Money に換算する。HP は実際に増減できる分だけ数える
+    let get_change_value = |target_player_index: usize, stat: Stat, amount: f64| {
+        let player_status = get_status(target_player_index);
+        let amount = match stat {
+            Stat::Hp if amount > 0.0 => amount.min(player_status.max_hp as f64),
+            Stat::Hp => amount.max(-(player_status.current_hp as f64)),
+            _ => amount,
+        };
+        get_sign(target_player_index) * amount * get_stat_value(stat, &player_status)
+    };
+    let mut value = 0.0;
+    for effect in item_definition.effects.iter() {
+        let effect_value: f64 = match effect {
+            ItemEffect::UpdateStatus { to, status, amount } => get_effect_player_indexes(to)
+                .into_iter()
+                .map(|target| get_change_value(target, *status, *amount as f64))
+                .sum(),
+            ItemEffect::UpdateStatusByMoney { to, status, scale } => get_effect_player_indexes(to)
+                .into_iter()
+                .map(|target| {
+                    let money = get_status(target).current_money_amount;
+                    get_change_value(target, *status, (money as f64 * scale).floor())
+                })
+                .sum(),
+            ItemEffect::CutStatus { to, status } => get_effect_player_indexes(to)
+                .into_iter()
+                .map(|target| {
+                    let amount = get_status(target).get_amount(*status) as f64;
+                    get_change_value(target, *status, -(amount * 0.5).ceil())
+                })
+                .sum(),
+            ItemEffect::SwapStatus { status } => target_player_indexes
+                .iter()
+                .map(|target| {
+                    let own_amount = get_status(player_index).get_amount(*status) as f64;
+                    let target_amount = get_status(*target).get_amount(*status) as f64;
+                    get_change_value(player_index, *status, target_amount - own_amount)
+                        + get_change_value(*target, *status, own_amount - target_amount)
+                })
+                .sum(),
+            ItemEffect::Balance {
+                to,
+                status_a,
+                status_b,
+                is_balance,
+                modifier,
+            } => get_effect_player_indexes(to)
+                .into_iter()
+                .map(|target| {
+                    let player_status = get_status(target);
+                    let amount_a = player_status.get_amount(*status_a) as f64;
+                    let amount_b = player_status.get_amount(*status_b) as f64;
+                    let new_amount = (if *is_balance {
+                        amount_a.max(amount_b)
+                    } else {
+                        amount_a.min(amount_b)
+                    } + *modifier as f64)
+                        .max(0.0);
+                    get_change_value(target, *status_a, new_amount - amount_a)
+                        + get_change_value(target, *status_b, new_amount - amount_b)
+                })
+                .sum(),
+            ItemEffect::AddStatusEffect {
+                to,
+                kind,
+                amount,
+                turns,
+            } => get_effect_player_indexes(to)
+                .into_iter()
+                .map(|target| {
+                    get_sign(target) * (*amount * *turns) as f64 * get_status_effect_value(*kind)
+                })
+                .sum(),
+        };
+        value += effect_value;
+    }
+    value
+}
+
+// 払った後の Money での価値を超えない、最も高い入札額
+// 価値は使う時点の Money に対して1次式とみなし、入札額 = 価値(所持金 - 入札額) を解く
+pub fn estimate_fair_bid_amount(
+    bop_shared_state: &BoPSharedState,
+    player_index: usize,
+    item_kind: &ItemKind,
+) -> u32 {
+    let current_money_amount = bop_shared_state.players[player_index]
+        .player_status
+        .current_money_amount;
+    let value_at_zero = estimate_item_value(bop_shared_state, player_index, item_kind, 0);
+    let value_at_current = estimate_item_value(
+        bop_shared_state,
+        player_index,
+        item_kind,
+        current_money_amount,
+    );
+    // Money 1 あたりの価値の増え方
+    let slope = if current_money_amount == 0 {
+        0.0
+    } else {
+        ((value_at_current - value_at_zero) / current_money_amount as f64).max(0.0)
+    };
+    let fair_bid_amount = (value_at_zero + slope * current_money_amount as f64) / (1.0 + slope);
+    fair_bid_amount
+        .max(0.0)
+        .floor()
+        .min(current_money_amount as f64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bop_core::rules::tests::start_game;
+
+    fn get_value(state: &BoPSharedState, item_kind: &str, money_at_use: u32) -> f64 {
+        estimate_item_value(state, 0, &ItemKind(item_kind.to_string()), money_at_use)
+    }
+
+    #[test]
+    fn stronger_item_is_valued_higher() {
+        let state = start_game(1, 2);
+        let money_amount = state.players[0].player_status.current_money_amount;
+        let orderings = [
+            ["Dagger", "LongSword", "Excalibur"],
+            ["LeatherArmour", "ChainMail", "Excalibur"],
+        ];
+        for item_kinds in orderings {
+            for pair in item_kinds.windows(2) {
+                assert!(
+                    get_value(&state, pair[0], money_amount)
+                        < get_value(&state, pair[1], money_amount),
+                    "{} < {}",
+                    pair[0],
+                    pair[1]
+                );
+            }
+        }
+        // 定義のないアイテムには価値がない
+        assert_eq!(get_value(&state, "Undefined", money_amount), 0.0);
+    }
+
+    #[test]
+    fn value_does_not_decrease_with_money() {
+        let state = start_game(1, 2);
+        for item_kind in ["GoldenDagger", "GoldenSkin", "GoldenHeal", "LongSword"] {
+            let values = (0..=60)
+                .map(|money_at_use| get_value(&state, item_kind, money_at_use))
+                .collect::<Vec<f64>>();
+            assert!(
+                values.windows(2).all(|pair| pair[0] <= pair[1]),
+                "{}",
+                item_kind
+            );
+        }
+        // Money に比例する効果だけが Money で変わる
+        assert!(get_value(&state, "GoldenDagger", 0) < get_value(&state, "GoldenDagger", 30));
+        assert_eq!(
+            get_value(&state, "LongSword", 0),
+            get_value(&state, "LongSword", 30)
+        );
+    }
+
+    #[test]
+    fn fair_bid_amount_does_not_decrease_with_money() {
+        let mut state = start_game(1, 2);
+        for item_kind in ["GoldenDagger", "LongSword", "Cure"] {
+            let item_kind = ItemKind(item_kind.to_string());
+            let mut last_bid_amount = 0;
+            for money_amount in 0..=60 {
+                state.players[0].player_status.current_money_amount = money_amount;
+                let bid_amount = estimate_fair_bid_amount(&state, 0, &item_kind);
+                assert!(bid_amount <= money_amount);
+                assert!(bid_amount >= last_bid_amount, "{:?}", item_kind);
+                last_bid_amount = bid_amount;
+            }
+        }
+    }
+}
@@ -0,0 +1,369 @@
+use crate::bop_core::cpu_player::evaluator::Evaluator;
+use crate::bop_core::cpu_player::{
+    apply_input, get_legal_inputs, playout, ready_next_input, redraw_sealed_bids, MAX_PLAYOUT_STEPS,
+};
+use crate::bop_core::random::SeededRng;
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+
+// モンテカルロ木探索（UCT）の設定
+#[derive(Clone, Debug)]
+pub struct SearchConfig {
+    // 反復の回数の上限
+    pub max_iterations: usize,
+    // 思考時間の上限（ミリ秒）。None の場合は回数だけで打ち切る
+    pub time_limit_ms: Option<f64>,
+    // 思考時間を測る時計（ミリ秒）
+    // wasm では std の時計が使えないので、ブラウザ側で差し替える
+    pub now_ms: fn() -> f64,
+    // UCB の探索項の係数。大きいほど訪問の少ない入力を試す
+    pub exploration: f64,
+    // 木を展開する深さの上限。それより先はプレイアウトだけで評価する
+    // 1 の場合は、最初の入力ごとにプレイアウトを比べるだけになる
+    pub max_depth: Option<usize>,
+    // 探索結果を無視して、選べる入力から無作為に選ぶ確率（弱いCPU用）
+    pub blunder_rate: f64,
+    // 最後に入力を選ぶときに、平均の報酬に加える値（CPUの性格用）
+    // 引数は探索したプレイヤーの手番の state と入力
+    pub input_bias: fn(&BoPSharedState, usize) -> f64,
+    // 決着がついていない state の評価。None の場合は全員に均等な報酬を配る
+    // Some の場合は、展開する入力を評価の高い順に選ぶ（move ordering）のにも使う
+    pub evaluator: Option<Evaluator>,
+    // プレイアウトをこの入力の数で打ち切り、evaluator で評価する
+    pub playout_cutoff: Option<usize>,
+    // true の場合は木を作らず、1手先の評価だけで選ぶ（軽いCPU用。evaluator が必要）
+    pub is_greedy: bool,
+    // Some の場合はこのシードの乱数だけを使い、思考時間の上限も無視する
+    // 同じ状態と設定からは常に同じ入力を選ぶので、テストや不具合の再現に使う
+    pub seed: Option<u64>,
+}
+
+impl Default for SearchConfig {
+    fn default() -> SearchConfig {
+        SearchConfig {
+            max_iterations: 20000,
+            time_limit_ms: Some(3000.0),
+            now_ms: system_now_ms,
+            exploration: 0.7,
+            max_depth: None,
+            blunder_rate: 0.0,
+            input_bias: no_input_bias,
+            evaluator: None,
+            playout_cutoff: None,
+            is_greedy: false,
+            seed: None,
+        }
+    }
+}
+
+impl SearchConfig {
+    pub fn deterministic(max_iterations: usize, seed: u64) -> SearchConfig {
+        SearchConfig {
+            max_iterations,
+            time_limit_ms: None,
+            seed: Some(seed),
+            ..SearchConfig::default()
+        }
+    }
+}
+
+fn no_input_bias(_: &BoPSharedState, _: usize) -> f64 {
+    0.0
+}
+
+fn system_now_ms() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs_f64() * 1000.0)
+        .unwrap_or(0.0)
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    // 選んだ入力（CPUPlayer::create_cpu_message に渡すインデックス）
+    pub input: usize,
+    pub iterations: usize,
+    // 探索したプレイヤーが選べた入力ごとの結果
+    pub input_stats: Vec<InputStat>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputStat {
+    pub input: usize,
+    pub visits: u32,
+    // 探索したプレイヤーから見た平均の報酬（勝ちが 1.0）
+    pub mean_reward: f64,
+}
+
+// 木のノードは Vec に並べ、子はインデックスで持つ
+struct Node {
+    // 親からこのノードに進んだ入力。ルートは None
+    input: Option<usize>,
+    // その入力をしたプレイヤー。報酬はこのプレイヤーから見た値を積む
+    player_index: usize,
+    children: Vec<usize>,
+    visits: u32,
+    // 親を選択したときに、この入力がルール上選べた回数
+    // 封印入札の引き直しによって選べる入力が変わるので、UCB の親の訪問回数の代わりに使う
+    availability: u32,
+    total_reward: f64,
+}
+
+impl Node {
+    fn init(input: Option<usize>, player_index: usize) -> Node {
+        Node {
+            input,
+            player_index,
+            children: vec![],
+            visits: 0,
+            availability: 1,
+            total_reward: 0.0,
+        }
+    }
+}
+
+// 全員の入力をルールエンジンで進めながら木を育て、十分に訪問した入力のうち報酬の最も高い入力を選ぶ
+// 他のプレイヤーのまだ公開されていない封印入札は、反復ごとに引き直して決めてから木を辿る
+// （情報集合モンテカルロ木探索。1つの決め方に依存した入力を選ばないようにする）
+pub fn search(
+    bop_shared_state: &BoPSharedState,
+    simulating_player: usize,
+    config: &SearchConfig,
+) -> SearchResult {
+    search_until(bop_shared_state, simulating_player, config, &mut |_| false)
+}
+
+// search と同じだが、反復のたびにそれまでの反復の回数を should_stop に渡す
+// should_stop が true を返したら打ち切り、それまでの結果から入力を選ぶ
+// 思考中の表示の更新や、別スレッド・Web Worker からの中断に使う
+pub fn search_until(
+    bop_shared_state: &BoPSharedState,
+    simulating_player: usize,
+    config: &SearchConfig,
+    should_stop: &mut dyn FnMut(usize) -> bool,
+) -> SearchResult {
+    let mut rng = SeededRng::from_seed(config.seed.unwrap_or_else(SeededRng::random_seed));
+    let mut root_state = bop_shared_state.clone();
+    // 探索中は全員の入力を順に進めるので、CPU対戦用の入力者の絞り込みは使わない
+    root_state.has_cpu = false;
+    root_state.state_hash_interval = 0;
+    root_state.own_player_index = simulating_player;
+    let legal_inputs = get_legal_inputs(&mut root_state);
+    // 選べる入力が1つしかなければ探索しない
+    if legal_inputs.len() <= 1 {
+        return SearchResult {
+            input: legal_inputs.first().copied().unwrap_or(0),
+            iterations: 0,
+            input_stats: vec![],
+        };
+    }
+    let (iterations, input_stats) = match &config.evaluator {
+        // 探索せずに1手先の評価だけで選ぶ。入力1つの評価を反復1回と数える
+        Some(evaluator) if config.is_greedy => {
+            let input_stats = legal_inputs
+                .iter()
+                .map(|input| InputStat {
+                    input: *input,
+                    visits: 1,
+                    mean_reward: evaluator.evaluate_input(&root_state, *input),
+                })
+                .collect::<Vec<InputStat>>();
+            (input_stats.len(), input_stats)
+        }
+        _ => search_tree(
+            &root_state,
+            simulating_player,
+            config,
+            &mut rng,
+            should_stop,
+        ),
+    };
+    if rng.gen_bool(config.blunder_rate) {
+        return SearchResult {
+            input: legal_inputs[rng.gen_index(legal_inputs.len())],
+            iterations,
+            input_stats,
+        };
+    }
+    // 訪問回数が少なく評価の定まっていない入力は選ばない
+    // 残った入力のうち、平均の報酬に性格による値を加えたものが最も高い入力を選ぶ
+    let max_visits = input_stats
+        .iter()
+        .map(|input_stat| input_stat.visits)
+        .max()
+        .unwrap_or(0);
+    let input = input_stats
+        .iter()
+        .filter(|input_stat| input_stat.visits * 4 >= max_visits)
+        .map(|input_stat| {
+            (
+                input_stat.input,
+                input_stat.mean_reward + (config.input_bias)(&root_state, input_stat.input),
+            )
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(legal_inputs[0], |(input, _)| input);
+    SearchResult {
+        input,
+        iterations,
+        input_stats,
+    }
+}
+
+// 思考時間・反復の回数の上限まで木を育て、(反復の回数, ルートの子ごとの結果) を返す
+fn search_tree(
+    root_state: &BoPSharedState,
+    simulating_player: usize,
+    config: &SearchConfig,
+    rng: &mut SeededRng,
+    should_stop: &mut dyn FnMut(usize) -> bool,
+) -> (usize, Vec<InputStat>) {
+    let deadline_ms = config
+        .time_limit_ms
+        .filter(|_| config.seed.is_none())
+        .map(|time_limit_ms| (config.now_ms)() + time_limit_ms);
+    let mut nodes = vec![Node::init(None, simulating_player)];
+    let mut iterations = 0;
+    while iterations < config.max_iterations {
+        if matches!(deadline_ms, Some(deadline_ms) if (config.now_ms)() >= deadline_ms)
+            || should_stop(iterations)
+        {
+            break;
+        }
+        iterate(&mut nodes, root_state, simulating_player, config, rng);
+        iterations += 1;
+    }
+    let input_stats = nodes[0]
+        .children
+        .iter()
+        .map(|child_index| {
+            let child = &nodes[*child_index];
+            InputStat {
+                input: child.input.unwrap(),
+                visits: child.visits,
+                mean_reward: child.total_reward / child.visits.max(1) as f64,
+            }
+        })
+        .collect::<Vec<InputStat>>();
+    (iterations, input_stats)
+}
+
+// 選択・展開・プレイアウト・逆伝播を1回行う
+fn iterate(
+    nodes: &mut Vec<Node>,
+    root_state: &BoPSharedState,
+    simulating_player: usize,
+    config: &SearchConfig,
+    rng: &mut SeededRng,
+) {
+    let mut bop_shared_state = root_state.clone();
+    redraw_sealed_bids(&mut bop_shared_state, simulating_player, rng);
+    let mut path = vec![0];
+    let mut node_index = 0;
+    while config
+        .max_depth
+        .is_none_or(|max_depth| path.len() <= max_depth)
+        && ready_next_input(&mut bop_shared_state)
+    {
+        let player_index = bop_shared_state.own_player_index;
+        let legal_inputs = get_legal_inputs(&mut bop_shared_state);
+        let untried_inputs = legal_inputs
+            .iter()
+            .copied()
+            .filter(|input| {
+                !nodes[node_index]
+                    .children
+                    .iter()
+                    .any(|child_index| nodes[*child_index].input == Some(*input))
+            })
+            .collect::<Vec<usize>>();
+        // まだ試していない入力があれば、1つ展開してプレイアウトに移る
+        // evaluator があれば1手先の評価が最も高い入力から、なければ無作為に展開する
+        if !untried_inputs.is_empty() {
+            let offset = rng.gen_index(untried_inputs.len());
+            let mut input = untried_inputs[offset];
+            if let Some(evaluator) = &config.evaluator {
+                let mut best_value = f64::MIN;
+                for position in 0..untried_inputs.len() {
+                    let untried_input = untried_inputs[(offset + position) % untried_inputs.len()];
+                    let value = evaluator.evaluate_input(&bop_shared_state, untried_input);
+                    if value > best_value {
+                        best_value = value;
+                        input = untried_input;
+                    }
+                }
+            }
+            apply_input(&mut bop_shared_state, input);
+            nodes.push(Node::init(Some(input), player_index));
+            let child_index = nodes.len() - 1;
+            nodes[node_index].children.push(child_index);
+            path.push(child_index);
+            break;
+        }
+        let mut selected_index = None;
+        let mut selected_value = f64::MIN;
+        for position in 0..nodes[node_index].children.len() {
+            let child_index = nodes[node_index].children[position];
+            let child = &mut nodes[child_index];
+            if !legal_inputs.contains(&child.input.unwrap()) {
+                continue;
+            }
+            child.availability += 1;
+            let value = child.total_reward / child.visits as f64
+                + config.exploration
+                    * ((child.availability as f64).ln() / child.visits as f64).sqrt();
+            if value > selected_value {
+                selected_value = value;
+                selected_index = Some(child_index);
+            }
+        }
+        node_index = selected_index.unwrap();
+        apply_input(&mut bop_shared_state, nodes[node_index].input.unwrap());
+        path.push(node_index);
+    }
+    playout(
+        &mut bop_shared_state,
+        rng,
+        config.playout_cutoff.unwrap_or(MAX_PLAYOUT_STEPS),
+    );
+    let rewards = get_rewards(&bop_shared_state, config.evaluator.as_ref());
+    for node_index in path {
+        let node = &mut nodes[node_index];
+        node.visits += 1;
+        node.total_reward += rewards[node.player_index];
+    }
+}
+
+// 生き残ったプレイヤーが 1.0、脱落したプレイヤーが 0.0
+// プレイアウトが打ち切られて決着していない場合は evaluator で評価し、なければ全員に均等に配る
+fn get_rewards(bop_shared_state: &BoPSharedState, evaluator: Option<&Evaluator>) -> Vec<f64> {
+    if !bop_shared_state.game_is_end() {
+        if let Some(evaluator) = evaluator {
+            return evaluator.get_rewards(bop_shared_state);
+        }
+        let reward = 1.0 / bop_shared_state.players.len() as f64;
+        return vec![reward; bop_shared_state.players.len()];
+    }
+    bop_shared_state
+        .players
+        .iter()
+        .map(|player| if player.is_lose() { 0.0 } else { 1.0 })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bop_core::cpu_player::CPUPlayer;
+
+    #[test]
+    fn deterministic_search_chooses_same_input() {
+        let cpu_player = CPUPlayer::new(&BoPSharedState::init_for_players(1, 2));
+        let config = SearchConfig::deterministic(200, 42);
+        let first = cpu_player.search(0, &config);
+        let second = cpu_player.search(0, &config);
+        assert!(!first.input_stats.is_empty());
+        assert_eq!(first.input, second.input);
+        assert_eq!(first.iterations, second.iterations);
+        assert_eq!(first.input_stats, second.input_stats);
+    }
+}
@@ -0,0 +1,357 @@
+use crate::bop_core::cpu_player::evaluator::{EvaluationWeights, Evaluator};
+use crate::bop_core::cpu_player::mcts::SearchConfig;
+use crate::bop_core::cpu_player::{get_bid_amount, BidInput};
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+use serde::{Deserialize, Serialize};
+
+// CPUの強さ
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Difficulty {
+    // 木を作らず1手先の評価だけで選び、ときどき無作為な入力をする
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    // 思考時間の上限まで探索し、わざと間違えることはない
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Expert,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "かんたん",
+            Difficulty::Normal => "ふつう",
+            Difficulty::Hard => "むずかしい",
+            Difficulty::Expert => "達人",
+        }
+    }
+
+    // コマンドライン引数などで使う名前
+    pub fn get_id(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Expert => "expert",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Difficulty> {
+        Difficulty::ALL
+            .iter()
+            .find(|difficulty| difficulty.get_id() == id)
+            .copied()
+    }
+
+    // タイトル画面で選び直す際の次の強さ
+    pub fn next(&self) -> Difficulty {
+        let index = Difficulty::ALL
+            .iter()
+            .position(|difficulty| difficulty == self)
+            .unwrap();
+        Difficulty::ALL[(index + 1) % Difficulty::ALL.len()]
+    }
+
+    // かんたんは1手先の評価だけで選ぶ
+    // それ以外は反復の回数、思考時間、木の深さ、プレイアウトを打ち切る入力の数、
+    // わざと間違える確率を強さごとに決める。展開する入力の順序にも評価を使う
+    pub fn get_search_config(&self) -> SearchConfig {
+        let create_tree_search_config =
+            |max_iterations, time_limit_ms, max_depth, playout_cutoff, blunder_rate| SearchConfig {
+                max_iterations,
+                time_limit_ms: Some(time_limit_ms),
+                max_depth,
+                playout_cutoff,
+                blunder_rate,
+                evaluator: Some(Evaluator::default()),
+                ..SearchConfig::default()
+            };
+        match self {
+            Difficulty::Easy => SearchConfig {
+                evaluator: Some(Evaluator::default()),
+                is_greedy: true,
+                blunder_rate: 0.15,
+                ..SearchConfig::default()
+            },
+            Difficulty::Normal => create_tree_search_config(3000, 1000.0, Some(4), Some(40), 0.08),
+            Difficulty::Hard => create_tree_search_config(12000, 3000.0, Some(12), Some(120), 0.02),
+            Difficulty::Expert => create_tree_search_config(40000, 6000.0, None, None, 0.0),
+        }
+    }
+}
+
+// CPUの性格
+// 探索の結果が近い入力のうち、どれを好むかが変わる
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Personality {
+    // 探索の結果だけで選ぶ
+    #[default]
+    Balanced,
+    // 入札を見送らず、他のプレイヤーと競り合うアイテムを好む。攻撃もためらわない
+    AggressiveBidder,
+    // Money を貯める。高い入札を避け、パスや攻撃のスキップで Money を得ることを好む
+    Hoarder,
+    // アイテムをできるだけ多く落札し、手に入れたらすぐに使う
+    ItemRusher,
+}
+
+impl Personality {
+    pub const ALL: [Personality; 4] = [
+        Personality::Balanced,
+        Personality::AggressiveBidder,
+        Personality::Hoarder,
+        Personality::ItemRusher,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Personality::Balanced => "バランス",
+            Personality::AggressiveBidder => "強気な入札",
+            Personality::Hoarder => "貯め込み",
+            Personality::ItemRusher => "アイテム好き",
+        }
+    }
+
+    // コマンドライン引数などで使う名前
+    pub fn get_id(&self) -> &'static str {
+        match self {
+            Personality::Balanced => "balanced",
+            Personality::AggressiveBidder => "aggressive",
+            Personality::Hoarder => "hoarder",
+            Personality::ItemRusher => "item-rusher",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Personality> {
+        Personality::ALL
+            .iter()
+            .find(|personality| personality.get_id() == id)
+            .copied()
+    }
+
+    // タイトル画面で選び直す際の次の性格
+    pub fn next(&self) -> Personality {
+        let index = Personality::ALL
+            .iter()
+            .position(|personality| personality == self)
+            .unwrap();
+        Personality::ALL[(index + 1) % Personality::ALL.len()]
+    }
+
+    pub fn get_input_bias(&self) -> fn(&BoPSharedState, usize) -> f64 {
+        fn balanced_bias(_: &BoPSharedState, _: usize) -> f64 {
+            0.0
+        }
+        fn aggressive_bidder_bias(bop_shared_state: &BoPSharedState, input: usize) -> f64 {
+            match InputKind::from_input(bop_shared_state, input) {
+                // 既に他のプレイヤーが入札しているアイテムや、上乗せする入札ほど好む
+                InputKind::Bid {
+                    item_index,
+                    bid_amount,
+                } => {
+                    let lowest_bid_amount = bop_shared_state.lowest_bid_amount(item_index);
+                    let contested_bias =
+                        if lowest_bid_amount > bop_shared_state.rule_set.reserve_price {
+                            0.12
+                        } else {
+                            0.06
+                        };
+                    let raise_bias = if bid_amount > lowest_bid_amount {
+                        0.04
+                    } else {
+                        0.0
+                    };
+                    contested_bias + raise_bias
+                }
+                InputKind::Wait | InputKind::Pass => -0.08,
+                InputKind::Attack => 0.05,
+                InputKind::SkipAttack => -0.05,
+                _ => 0.0,
+            }
+        }
+        fn hoarder_bias(bop_shared_state: &BoPSharedState, input: usize) -> f64 {
+            let current_money_amount = bop_shared_state.players[bop_shared_state.own_player_index]
+                .player_status
+                .current_money_amount;
+            match InputKind::from_input(bop_shared_state, input) {
+                // 持ち金に対して高い入札ほど避ける
+                InputKind::Bid { bid_amount, .. } => {
+                    -0.12 * bid_amount as f64 / current_money_amount.max(1) as f64
+                }
+                InputKind::Wait | InputKind::Pass => 0.06,
+                InputKind::SkipItem => 0.04,
+                InputKind::SkipAttack => 0.06,
+                _ => 0.0,
+            }
+        }
+        fn item_rusher_bias(bop_shared_state: &BoPSharedState, input: usize) -> f64 {
+            match InputKind::from_input(bop_shared_state, input) {
+                InputKind::Bid { .. } => 0.05,
+                InputKind::Pass => -0.1,
+                InputKind::UseItem => 0.08,
+                InputKind::SkipItem => -0.06,
+                _ => 0.0,
+            }
+        }
+        match self {
+            Personality::Balanced => balanced_bias,
+            Personality::AggressiveBidder => aggressive_bidder_bias,
+            Personality::Hoarder => hoarder_bias,
+            Personality::ItemRusher => item_rusher_bias,
+        }
+    }
+}
+
+// 性格による値を決めるための、CPUの入力の大まかな種類
+enum InputKind {
+    Bid { item_index: usize, bid_amount: u32 },
+    Wait,
+    Pass,
+    UseItem,
+    SkipItem,
+    Attack,
+    SkipAttack,
+}
+
+impl InputKind {
+    // 入力のインデックスの意味は create_command と同じ
+    fn from_input(bop_shared_state: &BoPSharedState, input: usize) -> InputKind {
+        let player_index = bop_shared_state.own_player_index;
+        match bop_shared_state.phase_index {
+            1 => match BidInput::from_input(bop_shared_state, input) {
+                BidInput::Bid {
+                    item_index,
+                    amount_kind,
+                } => InputKind::Bid {
+                    item_index,
+                    bid_amount: get_bid_amount(
+                        bop_shared_state,
+                        player_index,
+                        item_index,
+                        amount_kind,
+                    ),
+                },
+                BidInput::Wait => InputKind::Wait,
+                BidInput::Pass => InputKind::Pass,
+            },
+            2 => {
+                if input < bop_shared_state.players[player_index].own_item_list.len() {
+                    InputKind::UseItem
+                } else {
+                    InputKind::SkipItem
+                }
+            }
+            _ => {
+                if input
+                    < bop_shared_state
+                        .get_opponent_player_indexes(player_index)
+                        .len()
+                {
+                    InputKind::Attack
+                } else {
+                    InputKind::SkipAttack
+                }
+            }
+        }
+    }
+}
+
+// タイトル画面で選ぶCPUの設定。CPUが操作するプレイヤー全員に使う
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CPUProfile {
+    pub difficulty: Difficulty,
+    pub personality: Personality,
+    // 端末版では JSON から読み込める
+    #[serde(default)]
+    pub evaluation_weights: EvaluationWeights,
+}
+
+impl CPUProfile {
+    pub fn get_search_config(&self) -> SearchConfig {
+        SearchConfig {
+            input_bias: self.personality.get_input_bias(),
+            evaluator: Some(Evaluator::init(self.evaluation_weights)),
+            ..self.difficulty.get_search_config()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn difficulty_id_round_trips() {
+        for difficulty in Difficulty::ALL {
+            assert_eq!(Difficulty::from_id(difficulty.get_id()), Some(difficulty));
+        }
+        assert_eq!(Difficulty::from_id("unknown"), None);
+    }
+
+    #[test]
+    fn personality_id_round_trips() {
+        for personality in Personality::ALL {
+            assert_eq!(
+                Personality::from_id(personality.get_id()),
+                Some(personality)
+            );
+        }
+        assert_eq!(Personality::from_id("unknown"), None);
+    }
+
+    #[test]
+    fn next_visits_all_and_wraps_around() {
+        let mut difficulty = Difficulty::ALL[0];
+        for expected in Difficulty::ALL
+            .iter()
+            .cycle()
+            .skip(1)
+            .take(Difficulty::ALL.len())
+        {
+            difficulty = difficulty.next();
+            assert_eq!(difficulty, *expected);
+        }
+        assert_eq!(difficulty, Difficulty::ALL[0]);
+
+        let mut personality = Personality::ALL[0];
+        for expected in Personality::ALL
+            .iter()
+            .cycle()
+            .skip(1)
+            .take(Personality::ALL.len())
+        {
+            personality = personality.next();
+            assert_eq!(personality, *expected);
+        }
+        assert_eq!(personality, Personality::ALL[0]);
+    }
+
+    #[test]
+    fn harder_difficulty_searches_more_and_blunders_less() {
+        let search_configs = Difficulty::ALL
+            .iter()
+            .map(|difficulty| difficulty.get_search_config())
+            .collect::<Vec<SearchConfig>>();
+        assert!(search_configs[0].is_greedy);
+        for pair in search_configs[1..].windows(2) {
+            assert!(!pair[1].is_greedy);
+            assert!(pair[0].max_iterations < pair[1].max_iterations);
+            assert!(pair[0].blunder_rate > pair[1].blunder_rate);
+        }
+        assert_eq!(search_configs[3].blunder_rate, 0.0);
+    }
+
+    #[test]
+    fn balanced_personality_has_no_bias() {
+        let state = BoPSharedState::init_for_players(1, 2);
+        let input_bias = Personality::Balanced.get_input_bias();
+        assert_eq!(input_bias(&state, 0), 0.0);
+    }
+}
@@ -0,0 +1,75 @@
+use crate::bop_core::cpu_player::mcts::{search_until, SearchConfig, SearchResult};
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+// 別スレッドで MCTS を行う（ヘッドレス版用）
+// 探索中も呼び出し側は入力や表示を続けられ、途中で打ち切ることもできる
+// wasm ではスレッドが使えないので、ブラウザでは Web Worker で同じことを行う
+pub struct SearchTask {
+    handle: Option<JoinHandle<SearchResult>>,
+    iterations: Arc<AtomicUsize>,
+    is_cancelled: Arc<AtomicBool>,
+}
+
+impl SearchTask {
+    pub fn start(
+        bop_shared_state: &BoPSharedState,
+        simulating_player: usize,
+        config: SearchConfig,
+    ) -> SearchTask {
+        let bop_shared_state = bop_shared_state.clone();
+        let iterations = Arc::new(AtomicUsize::new(0));
+        let is_cancelled = Arc::new(AtomicBool::new(false));
+        let iterations_cloned = iterations.clone();
+        let is_cancelled_cloned = is_cancelled.clone();
+        let handle = std::thread::spawn(move || {
+            search_until(
+                &bop_shared_state,
+                simulating_player,
+                &config,
+                &mut |iterations| {
+                    iterations_cloned.store(iterations, Ordering::Relaxed);
+                    is_cancelled_cloned.load(Ordering::Relaxed)
+                },
+            )
+        });
+        SearchTask {
+            handle: Some(handle),
+            iterations,
+            is_cancelled,
+        }
+    }
+
+    // これまでに終えた反復の回数（思考中の表示用）
+    pub fn get_iterations(&self) -> usize {
+        self.iterations.load(Ordering::Relaxed)
+    }
+
+    // 探索を打ち切る。結果はそれまでの反復から選ばれ、join で受け取れる
+    pub fn cancel(&self) {
+        self.is_cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle
+            .as_ref()
+            .is_none_or(|handle| handle.is_finished())
+    }
+
+    // 探索の終了を待って結果を受け取る
+    pub fn join(mut self) -> SearchResult {
+        self.handle.take().unwrap().join().unwrap()
+    }
+}
+
+impl Drop for SearchTask {
+    // 結果を受け取らずに破棄された場合は、探索を止めてスレッドを残さない
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.cancel();
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod deck_list;
+pub mod item;
+pub mod item_set;
+pub mod player_status;
+pub mod status_effect;
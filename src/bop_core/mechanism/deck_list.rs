@@ -0,0 +1,244 @@
+use crate::bop_core::mechanism::item::{Item, ItemKind};
+use crate::bop_core::mechanism::item_set::{ItemSet, DEFAULT_LOCALE};
+use crate::bop_core::random::SeededRng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+// ItemSet の deck / final_items から作る、標準の山札の ID
+pub const STANDARD_DECK_LIST_ID: &str = "standard";
+
+pub fn standard_deck_list_id() -> String {
+    STANDARD_DECK_LIST_ID.to_string()
+}
+
+// 名前付きの山札の構成
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeckList {
+    pub id: String,
+    pub name: BTreeMap<String, String>,
+    // シードを元にシャッフルするアイテム。同じアイテムを複数入れてもよい
+    pub items: Vec<ItemKind>,
+    // シャッフルせずに山札の最後に入れるアイテム（フィナーレ）
+    #[serde(default)]
+    pub final_items: Vec<ItemKind>,
+}
+
+// 山札の構成の制限
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeckRules {
+    // フィナーレを除いたアイテム数
+    pub min_size: usize,
+    pub max_size: usize,
+    // 同じアイテムを入れられる数（フィナーレを含む）
+    pub max_copies: usize,
+    // フィナーレのアイテムが必ず1つ以上あること
+    pub requires_finale: bool,
+}
+
+impl Default for DeckRules {
+    fn default() -> DeckRules {
+        DeckRules {
+            min_size: 10,
+            max_size: 60,
+            max_copies: 3,
+            requires_finale: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeckError {
+    UnknownDeckList(String),
+    UndefinedItem(ItemKind),
+    // (アイテム数, 制限)
+    TooSmall(usize, usize),
+    TooLarge(usize, usize),
+    // (アイテム, 入っている数, 制限)
+    TooManyCopies(ItemKind, usize, usize),
+    MissingFinale,
+}
+
+impl fmt::Display for DeckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeckError::UnknownDeckList(id) => write!(f, "存在しない山札です: {}", id),
+            DeckError::UndefinedItem(item_kind) => {
+                write!(f, "定義されていないアイテムです: {}", item_kind.0)
+            }
+            DeckError::TooSmall(size, min_size) => write!(
+                f,
+                "山札のアイテムが少なすぎます: {} (最低 {})",
+                size, min_size
+            ),
+            DeckError::TooLarge(size, max_size) => write!(
+                f,
+                "山札のアイテムが多すぎます: {} (最大 {})",
+                size, max_size
+            ),
+            DeckError::TooManyCopies(item_kind, count, max_copies) => write!(
+                f,
+                "{} が {} 枚入っています (最大 {})",
+                item_kind.0, count, max_copies
+            ),
+            DeckError::MissingFinale => write!(f, "フィナーレのアイテムがありません"),
+        }
+    }
+}
+
+impl std::error::Error for DeckError {}
+
+impl DeckList {
+    pub fn get_name(&self) -> String {
+        self.name
+            .get(DEFAULT_LOCALE)
+            .cloned()
+            .unwrap_or_else(|| self.id.clone())
+    }
+
+    pub fn validate(&self, item_set: &ItemSet, deck_rules: &DeckRules) -> Result<(), DeckError> {
+        let mut item_kind_to_count: BTreeMap<&String, usize> = BTreeMap::new();
+        for item_kind in self.items.iter().chain(self.final_items.iter()) {
+            if item_set.get(item_kind).is_none() {
+                return Err(DeckError::UndefinedItem(item_kind.clone()));
+            }
+            let count = item_kind_to_count.entry(&item_kind.0).or_insert(0);
+            *count += 1;
+            if *count > deck_rules.max_copies {
+                return Err(DeckError::TooManyCopies(
+                    item_kind.clone(),
+                    *count,
+                    deck_rules.max_copies,
+                ));
+            }
+        }
+        if self.items.len() < deck_rules.min_size {
+            return Err(DeckError::TooSmall(self.items.len(), deck_rules.min_size));
+        }
+        if self.items.len() > deck_rules.max_size {
+            return Err(DeckError::TooLarge(self.items.len(), deck_rules.max_size));
+        }
+        if deck_rules.requires_finale && self.final_items.is_empty() {
+            return Err(DeckError::MissingFinale);
+        }
+        Ok(())
+    }
+
+    // シードが同じなら同じ順序の山札になる
+    pub fn create_deck(&self, seed: u64) -> Vec<Item> {
+        let mut rng = SeededRng::from_seed(seed);
+        let mut items = self
+            .items
+            .iter()
+            .map(|item_kind| Item::from(item_kind.clone()))
+            .collect::<Vec<Item>>();
+        rng.shuffle(&mut items);
+        items.extend(
+            self.final_items
+                .iter()
+                .map(|item_kind| Item::from(item_kind.clone())),
+        );
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bop_core::state::bop_shared_state::BoPSharedState;
+
+    // 定義済みのアイテムを順に size 個入れた山札
+    fn create_deck_list(item_set: &ItemSet, size: usize) -> DeckList {
+        DeckList {
+            id: "test".to_string(),
+            name: BTreeMap::new(),
+            items: item_set
+                .items
+                .iter()
+                .cycle()
+                .take(size)
+                .map(|item| item.id.clone())
+                .collect(),
+            final_items: vec![],
+        }
+    }
+
+    #[test]
+    fn bundled_deck_lists_are_valid() {
+        let item_set = ItemSet::default_set();
+        for deck_list in item_set.get_deck_lists() {
+            assert_eq!(deck_list.validate(&item_set, &DeckRules::default()), Ok(()));
+        }
+    }
+
+    #[test]
+    fn validate_rejects_undefined_item() {
+        let item_set = ItemSet::default_set();
+        let mut deck_list = create_deck_list(&item_set, 10);
+        deck_list
+            .final_items
+            .push(ItemKind("Undefined".to_string()));
+        assert_eq!(
+            deck_list.validate(&item_set, &DeckRules::default()),
+            Err(DeckError::UndefinedItem(ItemKind("Undefined".to_string())))
+        );
+    }
+
+    #[test]
+    fn validate_rejects_item_count_out_of_rules() {
+        let item_set = ItemSet::default_set();
+        let deck_rules = DeckRules {
+            min_size: 3,
+            max_size: 5,
+            max_copies: 2,
+            requires_finale: true,
+        };
+        let deck_list = create_deck_list(&item_set, 2);
+        assert_eq!(
+            deck_list.validate(&item_set, &deck_rules),
+            Err(DeckError::TooSmall(2, 3))
+        );
+        let deck_list = create_deck_list(&item_set, 6);
+        assert_eq!(
+            deck_list.validate(&item_set, &deck_rules),
+            Err(DeckError::TooLarge(6, 5))
+        );
+        let mut deck_list = create_deck_list(&item_set, 4);
+        assert_eq!(
+            deck_list.validate(&item_set, &deck_rules),
+            Err(DeckError::MissingFinale)
+        );
+        // フィナーレも同じアイテムの数に含める
+        let item_kind = deck_list.items[0].clone();
+        deck_list.items.push(item_kind.clone());
+        deck_list.final_items.push(item_kind.clone());
+        assert_eq!(
+            deck_list.validate(&item_set, &deck_rules),
+            Err(DeckError::TooManyCopies(item_kind, 3, 2))
+        );
+        deck_list.final_items = vec![deck_list.items[1].clone()];
+        assert_eq!(deck_list.validate(&item_set, &deck_rules), Ok(()));
+    }
+
+    #[test]
+    fn select_deck_list_rejects_unknown_id() {
+        let mut state = BoPSharedState::init_for_players(1, 2);
+        let bid_scheduled_items = state.bid_scheduled_items.clone();
+        assert_eq!(
+            state.select_deck_list("unknown"),
+            Err(DeckError::UnknownDeckList("unknown".to_string()))
+        );
+        assert_eq!(state.deck_list_id, STANDARD_DECK_LIST_ID);
+        assert_eq!(
+            state
+                .bid_scheduled_items
+                .iter()
+                .map(|item| &item.item_kind)
+                .collect::<Vec<&ItemKind>>(),
+            bid_scheduled_items
+                .iter()
+                .map(|item| &item.item_kind)
+                .collect::<Vec<&ItemKind>>()
+        );
+    }
+}
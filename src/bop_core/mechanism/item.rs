@@ -0,0 +1,192 @@
+use crate::bop_core::mechanism::player_status::Stat;
+use crate::bop_core::mechanism::status_effect::StatusEffect;
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+use serde::{Deserialize, Serialize};
+
+// アイテムの効果を state に適用する関数
+pub type ItemUseFunc = Box<dyn FnMut(&mut BoPSharedState)>;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Item {
+    // 使用済みを表すフラグなどあったが参照箇所がないので一旦消している
+    pub item_kind: ItemKind,
+}
+
+impl Item {
+    pub fn from(item_kind: ItemKind) -> Item {
+        Item { item_kind }
+    }
+    pub fn create_update_status_func(
+        target_player_index: usize,
+        status: Stat,
+        amount: i32,
+    ) -> impl FnMut(&mut BoPSharedState) {
+        move |bop_shared_state: &mut BoPSharedState| {
+            bop_shared_state.players[target_player_index]
+                .player_status
+                .capped_update_status(status, amount);
+        }
+    }
+
+    pub fn create_update_status_golden_func(
+        target_player_index: usize,
+        status: Stat,
+        scale: f64,
+    ) -> impl FnMut(&mut BoPSharedState) {
+        move |bop_shared_state: &mut BoPSharedState| {
+            let amount = (bop_shared_state.players[target_player_index]
+                .player_status
+                .current_money_amount as f64
+                * scale) as i32;
+            bop_shared_state.players[target_player_index]
+                .player_status
+                .capped_update_status(status, amount);
+        }
+    }
+    // HP/MHP は一度に処理する
+    pub fn create_swap_status_func(
+        player_a_index: usize,
+        player_b_index: usize,
+        status: Stat,
+    ) -> impl FnMut(&mut BoPSharedState) {
+        move |bop_shared_state: &mut BoPSharedState| match status {
+            Stat::Hp | Stat::MaxHp => {
+                let state_a_amount = bop_shared_state.players[player_a_index]
+                    .player_status
+                    .max_hp;
+                let state_b_amount = bop_shared_state.players[player_b_index]
+                    .player_status
+                    .max_hp;
+                bop_shared_state.players[player_a_index]
+                    .player_status
+                    .max_hp = state_b_amount;
+                bop_shared_state.players[player_b_index]
+                    .player_status
+                    .max_hp = state_a_amount;
+                let state_a_amount = bop_shared_state.players[player_a_index]
+                    .player_status
+                    .current_hp;
+                let state_b_amount = bop_shared_state.players[player_b_index]
+                    .player_status
+                    .current_hp;
+                bop_shared_state.players[player_a_index]
+                    .player_status
+                    .current_hp = state_b_amount;
+                bop_shared_state.players[player_b_index]
+                    .player_status
+                    .current_hp = state_a_amount;
+            }
+            _ => {
+                let state_a_amount = bop_shared_state.players[player_a_index]
+                    .player_status
+                    .get_amount(status);
+                let state_b_amount = bop_shared_state.players[player_b_index]
+                    .player_status
+                    .get_amount(status);
+                bop_shared_state.players[player_b_index]
+                    .player_status
+                    .set_amount(status, state_a_amount);
+                bop_shared_state.players[player_a_index]
+                    .player_status
+                    .set_amount(status, state_b_amount);
+            }
+        }
+    }
+    pub fn create_cut_status_func(
+        target_player_index: usize,
+        status: Stat,
+    ) -> impl FnMut(&mut BoPSharedState) {
+        move |bop_shared_state: &mut BoPSharedState| {
+            // 変化量は半分よりも多い（端数切り上げ）
+            let amount = -((bop_shared_state.players[target_player_index]
+                .player_status
+                .get_amount(status) as f64
+                * 0.5)
+                .ceil() as i32);
+            bop_shared_state.players[target_player_index]
+                .player_status
+                .capped_update_status(status, amount);
+        }
+    }
+
+    pub fn create_balance_func(
+        target_player_index: usize,
+        status_a: Stat,
+        status_b: Stat,
+        is_balance: bool,
+        modifier: i32,
+    ) -> impl FnMut(&mut BoPSharedState) {
+        move |bop_shared_state: &mut BoPSharedState| {
+            let target_player_status =
+                &mut bop_shared_state.players[target_player_index].player_status;
+            let status_a_amount = target_player_status.get_amount(status_a);
+            let status_b_amount = target_player_status.get_amount(status_b);
+            let new_amount = if is_balance {
+                status_a_amount.max(status_b_amount)
+            } else {
+                status_a_amount.min(status_b_amount)
+            };
+            for status in [status_a, status_b] {
+                target_player_status
+                    .set_amount(status, (new_amount as i32 + modifier).max(0) as u32);
+            }
+        }
+    }
+    pub fn create_add_status_effect_func(
+        target_player_index: usize,
+        status_effect: StatusEffect,
+    ) -> impl FnMut(&mut BoPSharedState) {
+        move |bop_shared_state: &mut BoPSharedState| {
+            bop_shared_state.players[target_player_index]
+                .player_status
+                .add_status_effect(status_effect.clone());
+        }
+    }
+    pub fn combine_func(mut functions: Vec<ItemUseFunc>) -> impl FnMut(&mut BoPSharedState) {
+        move |bop_shared_state: &mut BoPSharedState| {
+            for func in functions.iter_mut() {
+                func(bop_shared_state)
+            }
+        }
+    }
+    // 対象のプレイヤーそれぞれに同じ効果を与える
+    pub fn create_for_each_target_func<F: FnMut(&mut BoPSharedState) + 'static>(
+        target_player_indexes: &[usize],
+        create_func: impl Fn(usize) -> F,
+    ) -> impl FnMut(&mut BoPSharedState) {
+        Item::combine_func(
+            target_player_indexes
+                .iter()
+                .map(|target_player_index| {
+                    Box::new(create_func(*target_player_index)) as ItemUseFunc
+                })
+                .collect(),
+        )
+    }
+}
+
+// アイテムの効果の対象
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum ItemTarget {
+    // 使用したプレイヤー自身
+    #[default]
+    Own,
+    // 相手1人。使用時に選ぶ（選ばなかった場合は席順で次の相手）
+    Opponent,
+    // 生存している相手全員
+    AllOpponents,
+    // 自分を含めた生存しているプレイヤーから1人選ぶ（選ばなかった場合は自分）
+    Choice,
+}
+
+// アイテムの種類を表す ID
+// 効果や名前は ItemSet の定義（JSON）から引く
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ItemKind(pub String);
+
+impl ItemKind {
+    pub fn from(id: &str) -> ItemKind {
+        ItemKind(id.to_string())
+    }
+}
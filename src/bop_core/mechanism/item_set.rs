@@ -0,0 +1,427 @@
+use crate::bop_core::mechanism::deck_list::{DeckList, STANDARD_DECK_LIST_ID};
+use crate::bop_core::mechanism::item::{Item, ItemKind, ItemTarget, ItemUseFunc};
+use crate::bop_core::mechanism::player_status::Stat;
+use crate::bop_core::mechanism::status_effect::{StatusEffect, StatusEffectKind};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+// 名前・説明文で、指定した言語の文言がない場合に使う言語
+pub const DEFAULT_LOCALE: &str = "ja";
+
+// アイテムの効果を受けるプレイヤー
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum EffectTarget {
+    // 使用したプレイヤー自身
+    #[default]
+    Own,
+    // ItemDefinition.target で決まったプレイヤー
+    Targets,
+    // 使用したプレイヤー自身と、ItemDefinition.target で決まったプレイヤー
+    OwnAndTargets,
+}
+
+// 効果の種類ごとの値。status には Stat の名前（ATK, DEF, HP など）を指定する
+// 存在しない名前は読み込み時にエラーになる
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ItemEffect {
+    // ステータスを amount だけ増減する
+    UpdateStatus {
+        #[serde(default)]
+        to: EffectTarget,
+        status: Stat,
+        amount: i32,
+    },
+    // ステータスを、効果を受けるプレイヤーの現在の Money × scale だけ増やす
+    UpdateStatusByMoney {
+        #[serde(default)]
+        to: EffectTarget,
+        status: Stat,
+        scale: f64,
+    },
+    // ステータスを半減する（端数切り上げで減らす）
+    CutStatus {
+        #[serde(default)]
+        to: EffectTarget,
+        status: Stat,
+    },
+    // 使用したプレイヤーと対象のプレイヤーでステータスを入れ替える
+    // HP を指定した場合は MHP も入れ替える
+    SwapStatus {
+        status: Stat,
+    },
+    // 2つのステータスを、高い方（is_balance が false の場合は低い方）に合わせてから modifier を加える
+    Balance {
+        #[serde(default)]
+        to: EffectTarget,
+        status_a: Stat,
+        status_b: Stat,
+        is_balance: bool,
+        modifier: i32,
+    },
+    // turns ターンの間続く効果を与える
+    AddStatusEffect {
+        #[serde(default)]
+        to: EffectTarget,
+        kind: StatusEffectKind,
+        amount: u32,
+        turns: u32,
+    },
+}
+
+impl ItemEffect {
+    fn get_use_func(
+        &self,
+        use_player_index: usize,
+        target_player_indexes: &[usize],
+    ) -> ItemUseFunc {
+        let own = use_player_index;
+        let effect_player_indexes = |to: &EffectTarget| match to {
+            EffectTarget::Own => vec![own],
+            EffectTarget::Targets => target_player_indexes.to_vec(),
+            EffectTarget::OwnAndTargets => [vec![own], target_player_indexes.to_vec()].concat(),
+        };
+        match self.clone() {
+            ItemEffect::UpdateStatus { to, status, amount } => Box::new(
+                Item::create_for_each_target_func(&effect_player_indexes(&to), move |target| {
+                    Item::create_update_status_func(target, status, amount)
+                }),
+            ),
+            ItemEffect::UpdateStatusByMoney { to, status, scale } => Box::new(
+                Item::create_for_each_target_func(&effect_player_indexes(&to), move |target| {
+                    Item::create_update_status_golden_func(target, status, scale)
+                }),
+            ),
+            ItemEffect::CutStatus { to, status } => Box::new(Item::create_for_each_target_func(
+                &effect_player_indexes(&to),
+                move |target| Item::create_cut_status_func(target, status),
+            )),
+            ItemEffect::SwapStatus { status } => Box::new(Item::create_for_each_target_func(
+                target_player_indexes,
+                move |target| Item::create_swap_status_func(own, target, status),
+            )),
+            ItemEffect::Balance {
+                to,
+                status_a,
+                status_b,
+                is_balance,
+                modifier,
+            } => Box::new(Item::create_for_each_target_func(
+                &effect_player_indexes(&to),
+                move |target| {
+                    Item::create_balance_func(target, status_a, status_b, is_balance, modifier)
+                },
+            )),
+            ItemEffect::AddStatusEffect {
+                to,
+                kind,
+                amount,
+                turns,
+            } => Box::new(Item::create_for_each_target_func(
+                &effect_player_indexes(&to),
+                move |target| {
+                    Item::create_add_status_effect_func(
+                        target,
+                        StatusEffect::new(kind, amount, turns),
+                    )
+                },
+            )),
+        }
+    }
+}
+
+// アイテム1種類の定義
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ItemDefinition {
+    pub id: ItemKind,
+    // 言語ごとの名前・説明文（"ja" は必須）
+    pub name: BTreeMap<String, String>,
+    pub description: BTreeMap<String, String>,
+    #[serde(default)]
+    pub target: ItemTarget,
+    // 定義した順に適用する
+    pub effects: Vec<ItemEffect>,
+}
+
+impl ItemDefinition {
+    pub fn get_name(&self) -> String {
+        self.get_name_for(DEFAULT_LOCALE)
+    }
+
+    pub fn get_name_for(&self, locale: &str) -> String {
+        ItemDefinition::get_localized(&self.name, locale).unwrap_or_else(|| self.id.0.clone())
+    }
+
+    pub fn get_description(&self) -> String {
+        self.get_description_for(DEFAULT_LOCALE)
+    }
+
+    pub fn get_description_for(&self, locale: &str) -> String {
+        ItemDefinition::get_localized(&self.description, locale).unwrap_or_default()
+    }
+
+    fn get_localized(texts: &BTreeMap<String, String>, locale: &str) -> Option<String> {
+        texts
+            .get(locale)
+            .or_else(|| texts.get(DEFAULT_LOCALE))
+            .cloned()
+    }
+
+    // target_player_indexes は BoPSharedState::resolve_item_target_player_indexes で決めた対象
+    pub fn get_use_func(
+        &self,
+        use_player_index: usize,
+        target_player_indexes: &[usize],
+    ) -> ItemUseFunc {
+        Box::new(Item::combine_func(
+            self.effects
+                .iter()
+                .map(|effect| effect.get_use_func(use_player_index, target_player_indexes))
+                .collect(),
+        ))
+    }
+}
+
+// 対戦で使うアイテムの定義と山札の構成
+// JSON から読み込むので、再コンパイルせずにアイテムの追加や調整ができる
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ItemSet {
+    pub items: Vec<ItemDefinition>,
+    // 山札に入れるアイテム。同じアイテムを複数入れてもよい
+    // シードを元にシャッフルする
+    pub deck: Vec<ItemKind>,
+    // シャッフルせずに山札の最後に入れるアイテム
+    #[serde(default)]
+    pub final_items: Vec<ItemKind>,
+    // deck / final_items の他に選べる山札
+    #[serde(default)]
+    pub deck_lists: Vec<DeckList>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItemSetError {
+    MalformedItemSet(String),
+    DuplicatedItem(ItemKind),
+    UndefinedItem(ItemKind),
+    MissingDefaultLocale(ItemKind),
+    DuplicatedDeckList(String),
+}
+
+impl fmt::Display for ItemSetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemSetError::MalformedItemSet(message) => {
+                write!(f, "アイテムセットを解釈できません: {}", message)
+            }
+            ItemSetError::DuplicatedItem(item_kind) => {
+                write!(f, "アイテムの定義が重複しています: {}", item_kind.0)
+            }
+            ItemSetError::UndefinedItem(item_kind) => {
+                write!(f, "定義されていないアイテムです: {}", item_kind.0)
+            }
+            ItemSetError::MissingDefaultLocale(item_kind) => {
+                write!(
+                    f,
+                    "{} に {} の名前・説明文がありません",
+                    item_kind.0, DEFAULT_LOCALE
+                )
+            }
+            ItemSetError::DuplicatedDeckList(id) => {
+                write!(f, "山札の定義が重複しています: {}", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ItemSetError {}
+
+impl ItemSet {
+    // 同梱しているアイテムセット
+    // 何度も使うので、一度だけ解釈して共有する
+    pub fn default_set() -> Arc<ItemSet> {
+        static DEFAULT_ITEM_SET: OnceLock<Arc<ItemSet>> = OnceLock::new();
+        DEFAULT_ITEM_SET
+            .get_or_init(|| {
+                Arc::new(ItemSet::from_json(include_str!("default_item_set.json")).unwrap())
+            })
+            .clone()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<ItemSet, ItemSetError> {
+        let item_set = serde_json::from_str::<ItemSet>(json)
+            .map_err(|error| ItemSetError::MalformedItemSet(error.to_string()))?;
+        item_set.validate()?;
+        Ok(item_set)
+    }
+
+    fn validate(&self) -> Result<(), ItemSetError> {
+        let mut item_kinds = HashSet::new();
+        for item in self.items.iter() {
+            if !item_kinds.insert(&item.id) {
+                return Err(ItemSetError::DuplicatedItem(item.id.clone()));
+            }
+            if !item.name.contains_key(DEFAULT_LOCALE)
+                || !item.description.contains_key(DEFAULT_LOCALE)
+            {
+                return Err(ItemSetError::MissingDefaultLocale(item.id.clone()));
+            }
+        }
+        let mut deck_list_ids = HashSet::new();
+        for deck_list in self.get_deck_lists() {
+            if !deck_list_ids.insert(deck_list.id.clone()) {
+                return Err(ItemSetError::DuplicatedDeckList(deck_list.id));
+            }
+            if let Some(item_kind) = deck_list
+                .items
+                .iter()
+                .chain(deck_list.final_items.iter())
+                .find(|item_kind| !item_kinds.contains(item_kind))
+            {
+                return Err(ItemSetError::UndefinedItem(item_kind.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, item_kind: &ItemKind) -> Option<&ItemDefinition> {
+        self.items.iter().find(|item| &item.id == item_kind)
+    }
+
+    // 定義がないアイテムは ID をそのまま表示する
+    pub fn get_name(&self, item_kind: &ItemKind) -> String {
+        self.get(item_kind)
+            .map(|item| item.get_name())
+            .unwrap_or_else(|| item_kind.0.clone())
+    }
+
+    pub fn get_description(&self, item_kind: &ItemKind) -> String {
+        self.get(item_kind)
+            .map(|item| item.get_description())
+            .unwrap_or_default()
+    }
+
+    pub fn get_target(&self, item_kind: &ItemKind) -> ItemTarget {
+        self.get(item_kind)
+            .map(|item| item.target.clone())
+            .unwrap_or_default()
+    }
+
+    // deck / final_items から作る標準の山札
+    pub fn get_standard_deck_list(&self) -> DeckList {
+        let mut name = BTreeMap::new();
+        name.insert(DEFAULT_LOCALE.to_string(), "スタンダード".to_string());
+        name.insert("en".to_string(), "Standard".to_string());
+        DeckList {
+            id: STANDARD_DECK_LIST_ID.to_string(),
+            name,
+            items: self.deck.clone(),
+            final_items: self.final_items.clone(),
+        }
+    }
+
+    // 標準の山札を先頭にした、選べる山札の一覧
+    pub fn get_deck_lists(&self) -> Vec<DeckList> {
+        let mut deck_lists = vec![self.get_standard_deck_list()];
+        deck_lists.extend(self.deck_lists.iter().cloned());
+        deck_lists
+    }
+
+    pub fn get_deck_list(&self, id: &str) -> Option<DeckList> {
+        self.get_deck_lists()
+            .into_iter()
+            .find(|deck_list| deck_list.id == id)
+    }
+
+    // シードが同じなら同じ順序の山札になる
+    // 存在しない山札の場合は標準の山札を使う
+    pub fn create_deck(&self, deck_list_id: &str, seed: u64) -> Vec<Item> {
+        self.get_deck_list(deck_list_id)
+            .unwrap_or_else(|| self.get_standard_deck_list())
+            .create_deck(seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_json(item_set: &ItemSet) -> String {
+        serde_json::to_string(item_set).unwrap()
+    }
+
+    #[test]
+    fn default_item_set_round_trips() {
+        let item_set = ItemSet::default_set();
+        assert_eq!(
+            ItemSet::from_json(&to_json(&item_set)).as_ref(),
+            Ok(item_set.as_ref())
+        );
+    }
+
+    #[test]
+    fn from_json_rejects_duplicated_item() {
+        let mut item_set = (*ItemSet::default_set()).clone();
+        item_set.items.push(item_set.items[0].clone());
+        assert_eq!(
+            ItemSet::from_json(&to_json(&item_set)),
+            Err(ItemSetError::DuplicatedItem(item_set.items[0].id.clone()))
+        );
+    }
+
+    #[test]
+    fn from_json_rejects_undefined_item_in_deck() {
+        let mut item_set = (*ItemSet::default_set()).clone();
+        item_set.deck.push(ItemKind("Undefined".to_string()));
+        assert_eq!(
+            ItemSet::from_json(&to_json(&item_set)),
+            Err(ItemSetError::UndefinedItem(ItemKind(
+                "Undefined".to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn from_json_rejects_missing_default_locale() {
+        let mut item_set = (*ItemSet::default_set()).clone();
+        item_set.items[0].name.remove(DEFAULT_LOCALE);
+        assert_eq!(
+            ItemSet::from_json(&to_json(&item_set)),
+            Err(ItemSetError::MissingDefaultLocale(
+                item_set.items[0].id.clone()
+            ))
+        );
+    }
+
+    #[test]
+    fn from_json_rejects_unknown_stat() {
+        let json = r#"{
+            "items": [{
+                "id": "Unknown",
+                "name": {"ja": "不明"},
+                "description": {"ja": "不明"},
+                "effects": [{"type": "UpdateStatus", "status": "LUCK", "amount": 1}]
+            }],
+            "deck": ["Unknown"]
+        }"#;
+        assert!(matches!(
+            ItemSet::from_json(json),
+            Err(ItemSetError::MalformedItemSet(_))
+        ));
+    }
+
+    #[test]
+    fn from_json_rejects_malformed_json() {
+        for json in ["not json", r#"{"items": []}"#] {
+            assert!(matches!(
+                ItemSet::from_json(json),
+                Err(ItemSetError::MalformedItemSet(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use crate::bop_core::mechanism::status_effect::{StatusEffect, StatusEffectKind};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+// Stat の列挙・一覧・名前を1つの表から作る
+// 名前は JSON やログで使い、serde でもこの名前で読み書きする
+macro_rules! define_stats {
+    ($($stat:ident => $name:literal,)*) => {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        pub enum Stat {
+            $(
+                #[serde(rename = $name)]
+                $stat,
+            )*
+        }
+
+        impl Stat {
+            pub const ALL: [Stat; [$($name),*].len()] = [$(Stat::$stat),*];
+
+            // JSON やログで使う名前
+            pub fn get_name(&self) -> &'static str {
+                match self {
+                    $(Stat::$stat => $name,)*
+                }
+            }
+        }
+    };
+}
+
+// PlayerStatus のうち、アイテムの効果で増減できる値
+// ステータスを追加する場合はここに1行足し、PlayerStatus の get_amount / set_amount に対応するフィールドを足す
+define_stats! {
+    Attack => "ATK",
+    Defence => "DEF",
+    Hp => "HP",
+    MaxHp => "MHP",
+    Money => "Money",
+    Gain => "Gain",
+    ExtraItemUse => "ExtraItemUse",
+    ExtraAttack => "ExtraAttack",
+}
+
+impl Stat {
+    pub fn iter() -> impl Iterator<Item = Stat> {
+        Stat::ALL.iter().copied()
+    }
+}
+
+impl fmt::Display for Stat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.get_name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseStatError(pub String);
+
+impl fmt::Display for ParseStatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "存在しないステータスです: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseStatError {}
+
+impl FromStr for Stat {
+    type Err = ParseStatError;
+
+    fn from_str(name: &str) -> Result<Stat, ParseStatError> {
+        Stat::iter()
+            .find(|stat| stat.get_name() == name)
+            .ok_or_else(|| ParseStatError(name.to_string()))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlayerStatus {
+    pub max_hp: u32,
+    pub current_hp: u32,
+    pub attack_point: u32,
+    pub defence_point: u32,
+    pub current_money_amount: u32,
+    pub estimated_money_amount: u32,
+    // このターンに続けて行えるアイテム使用・攻撃の回数。ターンの終わりに 0 に戻る
+    #[serde(default)]
+    pub extra_item_use_count: u32,
+    #[serde(default)]
+    pub extra_attack_count: u32,
+    // 一定のターン続く効果。同じ種類の効果は1つにまとめる
+    #[serde(default)]
+    pub status_effects: Vec<StatusEffect>,
+}
+
+impl PlayerStatus {
+    pub fn init() -> PlayerStatus {
+        PlayerStatus {
+            max_hp: 50,
+            current_hp: 50,
+            attack_point: 10,
+            defence_point: 5,
+            current_money_amount: 5,
+            estimated_money_amount: 3,
+            extra_item_use_count: 0,
+            extra_attack_count: 0,
+            status_effects: vec![],
+        }
+    }
+
+    pub fn update_current_hp(&mut self, amount_of_change: i32) {
+        let mut updated_hp = self.current_hp as i32 + amount_of_change;
+        updated_hp = updated_hp.max(0);
+        updated_hp = updated_hp.min(self.max_hp as i32);
+        self.current_hp = updated_hp as u32;
+    }
+
+    pub fn get_amount(&self, stat: Stat) -> u32 {
+        match stat {
+            Stat::Attack => self.attack_point,
+            Stat::Defence => self.defence_point,
+            Stat::Hp => self.current_hp,
+            Stat::MaxHp => self.max_hp,
+            Stat::Money => self.current_money_amount,
+            Stat::Gain => self.estimated_money_amount,
+            Stat::ExtraItemUse => self.extra_item_use_count,
+            Stat::ExtraAttack => self.extra_attack_count,
+        }
+    }
+
+    pub fn set_amount(&mut self, stat: Stat, amount: u32) {
+        match stat {
+            Stat::Attack => self.attack_point = amount,
+            Stat::Defence => self.defence_point = amount,
+            Stat::Hp => self.current_hp = amount.min(self.max_hp),
+            Stat::MaxHp => self.max_hp = amount,
+            Stat::Money => self.current_money_amount = amount,
+            Stat::Gain => self.estimated_money_amount = amount,
+            Stat::ExtraItemUse => self.extra_item_use_count = amount,
+            Stat::ExtraAttack => self.extra_attack_count = amount,
+        }
+    }
+    pub fn capped_update_status(&mut self, stat: Stat, amount_of_change: i32) {
+        if let Stat::Hp = stat {
+            self.update_current_hp(amount_of_change);
+            return;
+        }
+        let current_amount = self.get_amount(stat);
+        let updated_amount = (current_amount as i32 + amount_of_change).max(0) as u32;
+        self.set_amount(stat, updated_amount);
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current_hp == 0
+    }
+
+    // シールド・弱体の効果を含めたダメージ
+    pub fn get_damage(&self, attack_point: u32) -> u32 {
+        let damage = if self.defence_point >= attack_point {
+            // attack_point が 0 ならダメージは 0
+            // そうでなければ最低保証ダメージは 1
+            attack_point.min(1)
+        } else {
+            attack_point - self.defence_point
+        };
+        (damage + self.get_status_effect_amount(StatusEffectKind::Vulnerable))
+            .saturating_sub(self.get_status_effect_amount(StatusEffectKind::Shield))
+    }
+
+    pub fn get_status_effect_amount(&self, kind: StatusEffectKind) -> u32 {
+        self.status_effects
+            .iter()
+            .filter(|status_effect| status_effect.kind == kind)
+            .map(|status_effect| status_effect.amount)
+            .sum()
+    }
+
+    // かかっている効果を画面表示用に並べる
+    pub fn get_status_effect_labels(&self) -> String {
+        self.status_effects
+            .iter()
+            .map(|status_effect| status_effect.get_label())
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    pub fn add_status_effect(&mut self, status_effect: StatusEffect) {
+        if let Some(current) = self
+            .status_effects
+            .iter_mut()
+            .find(|current| current.kind == status_effect.kind)
+        {
+            current.stack(&status_effect);
+        } else {
+            self.status_effects.push(status_effect);
+        }
+    }
+
+    // ターンの終わりに発動する効果を適用し、(効果の種類, 実際に変化した量) を返す
+    pub fn trigger_turn_end_effects(&mut self) -> Vec<(StatusEffectKind, u32)> {
+        let mut triggered = vec![];
+        for status_effect in self.status_effects.clone().iter() {
+            let amount = match status_effect.kind {
+                StatusEffectKind::Regen => {
+                    let current_hp = self.current_hp;
+                    self.update_current_hp(status_effect.amount as i32);
+                    self.current_hp - current_hp
+                }
+                StatusEffectKind::Poison => {
+                    let current_hp = self.current_hp;
+                    self.update_current_hp(-(status_effect.amount as i32));
+                    current_hp - self.current_hp
+                }
+                StatusEffectKind::MoneyDrain => {
+                    let current_money_amount = self.current_money_amount;
+                    self.current_money_amount =
+                        current_money_amount.saturating_sub(status_effect.amount);
+                    current_money_amount - self.current_money_amount
+                }
+                StatusEffectKind::Shield
+                | StatusEffectKind::Vulnerable
+                | StatusEffectKind::Thorns => continue,
+            };
+            triggered.push((status_effect.kind, amount));
+        }
+        triggered
+    }
+
+    // 残りターンを減らし、切れた効果の種類を返す
+    pub fn expire_status_effects(&mut self) -> Vec<StatusEffectKind> {
+        for status_effect in self.status_effects.iter_mut() {
+            status_effect.remaining_turns = status_effect.remaining_turns.saturating_sub(1);
+        }
+        let expired = self
+            .status_effects
+            .iter()
+            .filter(|status_effect| status_effect.remaining_turns == 0)
+            .map(|status_effect| status_effect.kind)
+            .collect();
+        self.status_effects
+            .retain(|status_effect| status_effect.remaining_turns > 0);
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stat_name_round_trips() {
+        for stat in Stat::iter() {
+            assert_eq!(stat.get_name().parse::<Stat>(), Ok(stat));
+            assert_eq!(stat.to_string(), stat.get_name());
+            let json = serde_json::to_string(&stat).unwrap();
+            assert_eq!(json, format!("\"{}\"", stat.get_name()));
+            assert_eq!(serde_json::from_str::<Stat>(&json).unwrap(), stat);
+        }
+    }
+
+    #[test]
+    fn stat_rejects_unknown_name() {
+        assert_eq!(
+            "LUCK".parse::<Stat>(),
+            Err(ParseStatError("LUCK".to_string()))
+        );
+        // 列挙子の名前ではなく、JSON で使う名前でのみ解釈する
+        assert!("Attack".parse::<Stat>().is_err());
+        assert!(serde_json::from_str::<Stat>("\"Attack\"").is_err());
+    }
+
+    #[test]
+    fn every_stat_can_be_set_and_read() {
+        let mut player_status = PlayerStatus::init();
+        for (amount, stat) in Stat::iter().enumerate() {
+            player_status.set_amount(stat, amount as u32 + 1);
+        }
+        for (amount, stat) in Stat::iter().enumerate() {
+            assert_eq!(player_status.get_amount(stat), amount as u32 + 1);
+        }
+    }
+
+    #[test]
+    fn add_status_effect_stacks_same_kind_only() {
+        let mut player_status = PlayerStatus::init();
+        player_status.add_status_effect(StatusEffect::new(StatusEffectKind::Poison, 2, 2));
+        player_status.add_status_effect(StatusEffect::new(StatusEffectKind::Regen, 1, 1));
+        player_status.add_status_effect(StatusEffect::new(StatusEffectKind::Poison, 1, 3));
+        assert_eq!(
+            player_status.status_effects,
+            vec![
+                StatusEffect::new(StatusEffectKind::Poison, 3, 3),
+                StatusEffect::new(StatusEffectKind::Regen, 1, 1),
+            ]
+        );
+        assert_eq!(
+            player_status.get_status_effect_amount(StatusEffectKind::Poison),
+            3
+        );
+        assert_eq!(
+            player_status.get_status_effect_amount(StatusEffectKind::Shield),
+            0
+        );
+    }
+}
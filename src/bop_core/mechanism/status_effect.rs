@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+
+// 一定のターン続く効果の種類
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StatusEffectKind {
+    // ターンの終わりに HP が amount 回復する
+    Regen,
+    // ターンの終わりに HP が amount 減る
+    Poison,
+    // ターンの終わりに Money が amount 減る
+    MoneyDrain,
+    // 攻撃で受けるダメージが amount 減る
+    Shield,
+    // 攻撃で受けるダメージが amount 増える
+    Vulnerable,
+    // 攻撃を受けると、攻撃したプレイヤーに amount のダメージを与える
+    Thorns,
+}
+
+// 同じ種類の効果を重ねて受けた場合の扱い
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StackRule {
+    // 効果量を足し、残りターンは長い方に合わせる
+    Intensify,
+    // 効果量・残りターンともに大きい方に合わせる
+    Refresh,
+}
+
+impl StatusEffectKind {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            StatusEffectKind::Regen => "リジェネ",
+            StatusEffectKind::Poison => "毒",
+            StatusEffectKind::MoneyDrain => "浪費",
+            StatusEffectKind::Shield => "シールド",
+            StatusEffectKind::Vulnerable => "弱体",
+            StatusEffectKind::Thorns => "トゲ",
+        }
+    }
+
+    pub fn get_stack_rule(&self) -> StackRule {
+        match self {
+            StatusEffectKind::Poison => StackRule::Intensify,
+            _ => StackRule::Refresh,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub kind: StatusEffectKind,
+    pub amount: u32,
+    // ターンの終わりに 1 減り、0 になると取り除かれる
+    pub remaining_turns: u32,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusEffectKind, amount: u32, remaining_turns: u32) -> StatusEffect {
+        StatusEffect {
+            kind,
+            amount,
+            remaining_turns,
+        }
+    }
+
+    // 画面表示用の短い文言（例: 毒5:2T）
+    pub fn get_label(&self) -> String {
+        format!(
+            "{}{}:{}T",
+            self.kind.get_name(),
+            self.amount,
+            self.remaining_turns
+        )
+    }
+
+    // 既にかかっている同じ種類の効果に重ねる
+    pub fn stack(&mut self, other: &StatusEffect) {
+        match self.kind.get_stack_rule() {
+            StackRule::Intensify => self.amount += other.amount,
+            StackRule::Refresh => self.amount = self.amount.max(other.amount),
+        }
+        self.remaining_turns = self.remaining_turns.max(other.remaining_turns);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intensify_adds_amount() {
+        let mut poison = StatusEffect::new(StatusEffectKind::Poison, 2, 3);
+        poison.stack(&StatusEffect::new(StatusEffectKind::Poison, 3, 1));
+        assert_eq!(poison, StatusEffect::new(StatusEffectKind::Poison, 5, 3));
+    }
+
+    #[test]
+    fn refresh_keeps_stronger_amount_and_longer_turns() {
+        let mut shield = StatusEffect::new(StatusEffectKind::Shield, 2, 1);
+        shield.stack(&StatusEffect::new(StatusEffectKind::Shield, 1, 3));
+        assert_eq!(shield, StatusEffect::new(StatusEffectKind::Shield, 2, 3));
+        shield.stack(&StatusEffect::new(StatusEffectKind::Shield, 4, 2));
+        assert_eq!(shield, StatusEffect::new(StatusEffectKind::Shield, 4, 3));
+        // 弱く短い効果を重ねても変わらない
+        shield.stack(&StatusEffect::new(StatusEffectKind::Shield, 1, 1));
+        assert_eq!(shield, StatusEffect::new(StatusEffectKind::Shield, 4, 3));
+    }
+}
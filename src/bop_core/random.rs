@@ -0,0 +1,48 @@
+// ルール上の乱数はすべてシードから導出する
+// 両クライアントが同じシードから同じ山札を作れるように、また1つの数値から対戦を再現できるように
+// rand のバージョンで結果が変わらないよう、SplitMix64 を自前で実装している
+#[derive(Clone, Debug)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn from_seed(seed: u64) -> SeededRng {
+        SeededRng { state: seed }
+    }
+
+    // シード自体を決めるときだけは環境の乱数を使う
+    pub fn random_seed() -> u64 {
+        use rand::Rng;
+        rand::thread_rng().random::<u64>()
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    pub fn next_bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    // 0..len の範囲のインデックスを返す
+    pub fn gen_index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+
+    // probability の確率で true を返す
+    pub fn gen_bool(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+
+    pub fn shuffle<T>(&mut self, values: &mut [T]) {
+        for index in (1..values.len()).rev() {
+            let swap_index = self.gen_index(index + 1);
+            values.swap(index, swap_index);
+        }
+    }
+}
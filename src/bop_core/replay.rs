@@ -0,0 +1,294 @@
+use crate::bop_core::mechanism::deck_list::{standard_deck_list_id, DeckError};
+use crate::bop_core::mechanism::item_set::ItemSet;
+use crate::bop_core::rules::rule_set::RuleSet;
+use crate::bop_core::rules::{apply, Command, GameEvent, RuleError};
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+use crate::bop_core::state::message::GameRuleMessage;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+// 保存形式や、シードから山札を作る方法を変更した場合はインクリメントする
+pub const REPLAY_VERSION: u32 = 3;
+
+// 対戦を再現するためのリプレイ
+// シードから初期状態を作り、記録した操作を順に適用し直す
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Replay {
+    pub version: u32,
+    // CPU対戦の場合は存在しない
+    pub game_rule_message: Option<GameRuleMessage>,
+    pub seed: u64,
+    pub player_names: Vec<String>,
+    pub commands: Vec<Command>,
+    // 対戦で使ったアイテムセット。省略時は同梱のもの
+    #[serde(default = "ItemSet::default_set")]
+    pub item_set: Arc<ItemSet>,
+    // 対戦で使ったルール。省略時は標準のルール
+    #[serde(default)]
+    pub rule_set: RuleSet,
+    // 対戦で使った山札。省略時は標準の山札
+    #[serde(default = "standard_deck_list_id")]
+    pub deck_list_id: String,
+}
+
+#[derive(Deserialize)]
+struct ReplayHeader {
+    version: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayError {
+    Malformed(String),
+    UnsupportedVersion(u32),
+    // 記録された山札を初期状態に再現できない
+    InvalidDeck(DeckError),
+    // 記録された操作がルール上適用できない
+    RuleViolation(RuleError),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Malformed(message) => {
+                write!(f, "リプレイを解釈できません: {}", message)
+            }
+            ReplayError::UnsupportedVersion(version) => {
+                write!(f, "対応していないバージョンのリプレイです: {}", version)
+            }
+            ReplayError::InvalidDeck(error) => {
+                write!(f, "リプレイの山札を再現できません: {}", error)
+            }
+            ReplayError::RuleViolation(error) => {
+                write!(f, "リプレイの操作を適用できません: {}", error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<DeckError> for ReplayError {
+    fn from(error: DeckError) -> ReplayError {
+        ReplayError::InvalidDeck(error)
+    }
+}
+
+impl From<RuleError> for ReplayError {
+    fn from(error: RuleError) -> ReplayError {
+        ReplayError::RuleViolation(error)
+    }
+}
+
+impl Replay {
+    // 同期ずれでスナップショットに上書きされた対戦は、記録した操作を適用しても同じ結果にならない
+    pub fn from_state(bop_shared_state: &BoPSharedState) -> Replay {
+        Replay {
+            version: REPLAY_VERSION,
+            game_rule_message: bop_shared_state.game_rule_message.clone(),
+            seed: bop_shared_state.seed,
+            player_names: bop_shared_state
+                .players
+                .iter()
+                .map(|player| player.player_name.to_string())
+                .collect(),
+            commands: bop_shared_state.command_history.clone(),
+            item_set: bop_shared_state.item_set.clone(),
+            rule_set: bop_shared_state.rule_set.clone(),
+            deck_list_id: bop_shared_state.deck_list_id.clone(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Replay, ReplayError> {
+        let header = serde_json::from_str::<ReplayHeader>(json)
+            .map_err(|error| ReplayError::Malformed(error.to_string()))?;
+        if header.version != REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(header.version));
+        }
+        serde_json::from_str::<Replay>(json)
+            .map_err(|error| ReplayError::Malformed(error.to_string()))
+    }
+
+    // 操作を1つも適用していない状態
+    pub fn initial_state(&self) -> Result<BoPSharedState, ReplayError> {
+        let mut bop_shared_state =
+            BoPSharedState::init_for_players(self.seed, self.player_names.len());
+        for (player, player_name) in bop_shared_state
+            .players
+            .iter_mut()
+            .zip(self.player_names.iter())
+        {
+            player.player_name = player_name.to_string();
+        }
+        bop_shared_state.game_rule_message = self.game_rule_message.clone();
+        self.rule_set.validate()?;
+        bop_shared_state.update_item_set(self.item_set.clone());
+        bop_shared_state.update_rule_set(self.rule_set.clone());
+        bop_shared_state.select_deck_list(&self.deck_list_id)?;
+        // オンライン対戦ではホストから受け取った並びの山札を使う
+        if let Some(item_kind_list) = self
+            .game_rule_message
+            .as_ref()
+            .and_then(|game_rule_message| game_rule_message.item_kind_list.clone())
+        {
+            bop_shared_state.update_item_list(item_kind_list)?;
+        }
+        Ok(bop_shared_state)
+    }
+
+    // すべての操作を適用し終えた状態
+    pub fn final_state(&self) -> Result<BoPSharedState, ReplayError> {
+        let mut replay_player = ReplayPlayer::new(self.clone())?;
+        while !replay_player.is_end() {
+            replay_player.step_forward()?;
+        }
+        Ok(replay_player.bop_shared_state)
+    }
+}
+
+// リプレイを1手ずつ進めたり戻したりする
+// 戻す場合は初期状態から適用し直す
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    // 適用済みの操作の数
+    pub position: usize,
+    pub bop_shared_state: BoPSharedState,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Result<ReplayPlayer, ReplayError> {
+        let bop_shared_state = replay.initial_state()?;
+        Ok(ReplayPlayer {
+            replay,
+            position: 0,
+            bop_shared_state,
+        })
+    }
+
+    pub fn is_end(&self) -> bool {
+        self.position >= self.replay.commands.len()
+    }
+
+    pub fn step_forward(&mut self) -> Result<Vec<GameEvent>, ReplayError> {
+        if self.is_end() {
+            return Ok(vec![]);
+        }
+        let command = self.replay.commands[self.position].clone();
+        self.position += 1;
+        Ok(apply(&mut self.bop_shared_state, command)?)
+    }
+
+    pub fn step_back(&mut self) -> Result<(), ReplayError> {
+        if self.position == 0 {
+            return Ok(());
+        }
+        self.seek(self.position - 1)
+    }
+
+    pub fn seek(&mut self, position: usize) -> Result<(), ReplayError> {
+        self.bop_shared_state = self.replay.initial_state()?;
+        self.position = 0;
+        while self.position < position && !self.is_end() {
+            self.step_forward()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bop_core::mechanism::item::ItemKind;
+    use crate::bop_core::rules::tests::{play_randomly, start_game};
+    use crate::bop_core::state::snapshot::state_hash;
+
+    #[test]
+    fn replay_reproduces_final_state() {
+        let mut state = start_game(1, 2);
+        play_randomly(&mut state, 1, 100);
+        let replay = Replay::from_json(&Replay::from_state(&state).to_json()).unwrap();
+        let final_state = replay.final_state().unwrap();
+        assert_eq!(state_hash(&final_state), state_hash(&state));
+        assert_eq!(final_state.consumed_seq_no, state.consumed_seq_no);
+    }
+
+    #[test]
+    fn replay_seek_returns_to_same_state() {
+        let mut state = start_game(1, 2);
+        play_randomly(&mut state, 1, 30);
+        let mut replay_player = ReplayPlayer::new(Replay::from_state(&state)).unwrap();
+        replay_player.seek(10).unwrap();
+        let hash_at_10 = state_hash(&replay_player.bop_shared_state);
+        replay_player.step_forward().unwrap();
+        replay_player.step_back().unwrap();
+        assert_eq!(replay_player.position, 10);
+        assert_eq!(state_hash(&replay_player.bop_shared_state), hash_at_10);
+    }
+
+    #[test]
+    fn replay_rejects_unknown_deck_list() {
+        let replay = Replay {
+            deck_list_id: "unknown".to_string(),
+            ..Replay::from_state(&start_game(1, 2))
+        };
+        assert_eq!(
+            replay.initial_state().err(),
+            Some(ReplayError::InvalidDeck(DeckError::UnknownDeckList(
+                "unknown".to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn replay_rejects_undefined_item_from_host() {
+        let state = start_game(1, 2);
+        let game_rule_message = GameRuleMessage {
+            item_kind_list: Some(vec![ItemKind("undefined".to_string())]),
+            ..GameRuleMessage::new(&state, "host".to_string(), "guest".to_string())
+        };
+        let replay = Replay {
+            game_rule_message: Some(game_rule_message),
+            ..Replay::from_state(&state)
+        };
+        assert!(matches!(
+            replay.initial_state(),
+            Err(ReplayError::InvalidDeck(DeckError::UndefinedItem(_)))
+        ));
+    }
+
+    #[test]
+    fn replay_rejects_other_version() {
+        let json = format!(r#"{{"version":{}}}"#, REPLAY_VERSION + 1);
+        assert_eq!(
+            Replay::from_json(&json).err(),
+            Some(ReplayError::UnsupportedVersion(REPLAY_VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn replay_rejects_malformed_json() {
+        assert!(matches!(
+            Replay::from_json("not json"),
+            Err(ReplayError::Malformed(_))
+        ));
+        let json = format!(r#"{{"version":{}}}"#, REPLAY_VERSION);
+        assert!(matches!(
+            Replay::from_json(&json),
+            Err(ReplayError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn replay_rejects_invalid_rule_set() {
+        let mut replay = Replay::from_state(&start_game(1, 2));
+        replay.rule_set.bid_increment = 0;
+        assert!(matches!(
+            replay.initial_state(),
+            Err(ReplayError::RuleViolation(RuleError::InvalidRuleSet(_)))
+        ));
+    }
+}
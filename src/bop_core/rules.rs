@@ -0,0 +1,778 @@
+use crate::bop_core::mechanism::item::ItemKind;
+use crate::bop_core::mechanism::status_effect::StatusEffectKind;
+use crate::bop_core::rules::rule_set::AuctionMode;
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+use crate::bop_core::state::message::{
+    AttackTargetMessage, BidMessage, GameStartIsApprovedMessage, UseItemMessage,
+};
+use crate::bop_core::state::phase::PhaseType;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub mod rule_set;
+mod validation;
+
+// プレイヤーの操作
+// WebSocket経由の文字列メッセージも、CPUのシミュレーションもここを通して state を更新する
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Command {
+    ApproveGameStart(GameStartIsApprovedMessage),
+    Bid(BidMessage),
+    UseItem(UseItemMessage),
+    AttackTarget(AttackTargetMessage),
+}
+
+impl Command {
+    pub fn from_message(message: &str) -> Result<Command, RuleError> {
+        if let Ok(message) = serde_json::from_str::<BidMessage>(message) {
+            Ok(Command::Bid(message))
+        } else if let Ok(message) = serde_json::from_str::<UseItemMessage>(message) {
+            Ok(Command::UseItem(message))
+        } else if let Ok(message) = serde_json::from_str::<AttackTargetMessage>(message) {
+            Ok(Command::AttackTarget(message))
+        } else if let Ok(message) = serde_json::from_str::<GameStartIsApprovedMessage>(message) {
+            Ok(Command::ApproveGameStart(message))
+        } else {
+            Err(RuleError::MalformedMessage(message.to_string()))
+        }
+    }
+
+    pub fn player_index(&self) -> usize {
+        match self {
+            Command::ApproveGameStart(message) => message.player_index,
+            Command::Bid(message) => message.player_index,
+            Command::UseItem(message) => message.player_index,
+            Command::AttackTarget(message) => message.player_index,
+        }
+    }
+
+    pub fn seq_no(&self) -> Option<usize> {
+        match self {
+            Command::ApproveGameStart(_) => None,
+            Command::Bid(message) => Some(message.seq_no),
+            Command::UseItem(message) => Some(message.seq_no),
+            Command::AttackTarget(message) => Some(message.seq_no),
+        }
+    }
+
+    pub fn to_message(&self) -> String {
+        match self {
+            Command::ApproveGameStart(message) => serde_json::to_string(message),
+            Command::Bid(message) => serde_json::to_string(message),
+            Command::UseItem(message) => serde_json::to_string(message),
+            Command::AttackTarget(message) => serde_json::to_string(message),
+        }
+        .unwrap()
+    }
+}
+
+// 操作の結果として起きたこと
+// UI、通信、CPU、ログは state の差分を読み直すのではなくこのイベントを参照する
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GameEvent {
+    // 開始を承認して対戦に参加した
+    PlayerJoined {
+        player_index: usize,
+    },
+    BidPlaced {
+        player_index: usize,
+        bid_item_index: usize,
+        bid_amount: u32,
+    },
+    // このフェーズの入札から降りた
+    BidPassed {
+        player_index: usize,
+    },
+    ItemWon {
+        player_index: usize,
+        item_kind: ItemKind,
+        bid_amount: u32,
+    },
+    InitiativeChanged {
+        first_player_index: usize,
+    },
+    ItemUsed {
+        player_index: usize,
+        item_kind: ItemKind,
+    },
+    DamageDealt {
+        attacker_player_index: usize,
+        target_player_index: usize,
+        damage: u32,
+        remaining_hp: u32,
+    },
+    MoneyGained {
+        player_index: usize,
+        amount: u32,
+        source: MoneySource,
+    },
+    PhaseChanged {
+        from: PhaseType,
+        to: PhaseType,
+        turn: usize,
+    },
+    // 追加の行動回数を消費して、同じプレイヤーが続けて行動する
+    ActionChained {
+        player_index: usize,
+        phase_type: PhaseType,
+    },
+    // 一定のターン続く効果が発動した（player_index は効果を受けたプレイヤー）
+    // ターンの終わりのほか、トゲは攻撃を受けた時に発動する
+    StatusEffectTriggered {
+        player_index: usize,
+        kind: StatusEffectKind,
+        amount: u32,
+    },
+    StatusEffectExpired {
+        player_index: usize,
+        kind: StatusEffectKind,
+    },
+    // 封印入札のラウンドが終わり、全員の入札を公開した
+    // (プレイヤー, 入札したアイテム, 入札額)
+    SealedBidsRevealed {
+        bids: Vec<(usize, ItemKind, u32)>,
+    },
+    // ダッチ方式で、全員が見送るか落札したのでラウンドが終わり価格が下がった
+    DutchPriceDropped {
+        price: u32,
+    },
+    // HPが0になり、行動順から取り除かれた
+    PlayerEliminated {
+        player_index: usize,
+    },
+    GameEnded {
+        winner_player_indexes: Vec<usize>,
+    },
+    // 間のシーケンス番号のメッセージが届いていないか、届いたメッセージが不正で、再同期が必要になった
+    Desynced {
+        expected_seq_no: usize,
+        received_seq_no: usize,
+    },
+    // 保持していたメッセージをすべて適用し終えた
+    Resynced,
+    // 同期確認のためのハッシュを記録した（相手に送る）
+    StateHashed {
+        seq_no: usize,
+        state_hash: u64,
+    },
+    // 相手とハッシュが一致しなかった
+    StateHashMismatched {
+        seq_no: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MoneySource {
+    // 落札時またはパス時の獲得Money
+    Gain,
+    // 攻撃しなかった場合の獲得Money
+    AttackSkipped,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleError {
+    MalformedMessage(String),
+    // 存在しないプレイヤーの操作
+    NoSuchPlayer,
+    // 入力すべきプレイヤーではない
+    NotYourTurn,
+    // ゲームの開始後に開始の承認が届いた
+    GameAlreadyStarted,
+    InsufficientMoney,
+    // 最低入札価格（現在価格+RuleSet の bid_increment）を下回っている
+    BidTooLow,
+    NoSuchItem,
+    // 封印入札・ダッチ方式で、既に落札が決まったアイテムが指定された
+    ItemAlreadyWon,
+    // ダッチ方式で、現在の価格と異なる金額が指定された
+    WrongPrice,
+    // ダッチ方式で、これ以上価格が下がらないのに見送ろうとした
+    CannotWait,
+    // 現在のフェーズでは受け付けない操作
+    WrongPhase,
+    // 過去のターンに向けた操作
+    StaleTurn,
+    // 対象として選べないプレイヤーが指定された
+    InvalidTarget,
+    // RuleSet の値が不正
+    InvalidRuleSet(String),
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleError::MalformedMessage(message) => {
+                write!(f, "解釈できないメッセージです: {}", message)
+            }
+            RuleError::NoSuchPlayer => write!(f, "存在しないプレイヤーです"),
+            RuleError::NotYourTurn => write!(f, "あなたの手番ではありません"),
+            RuleError::GameAlreadyStarted => write!(f, "ゲームは既に開始しています"),
+            RuleError::InsufficientMoney => write!(f, "Moneyが足りません"),
+            RuleError::BidTooLow => write!(f, "入札金額が最低入札価格を下回っています"),
+            RuleError::NoSuchItem => write!(f, "指定されたアイテムが存在しません"),
+            RuleError::ItemAlreadyWon => write!(f, "そのアイテムは落札済みです"),
+            RuleError::WrongPrice => write!(f, "現在の価格と一致しません"),
+            RuleError::CannotWait => write!(f, "これ以上価格が下がらないため見送れません"),
+            RuleError::WrongPhase => write!(f, "現在のフェーズでは実行できません"),
+            RuleError::StaleTurn => write!(f, "過去のターンに対する操作です"),
+            RuleError::InvalidTarget => write!(f, "そのプレイヤーは対象にできません"),
+            RuleError::InvalidRuleSet(message) => write!(f, "ルールの設定が不正です: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+// 適用はせずに、操作がルール上許されるかだけを調べる（CPUが選べる入力を調べる）
+pub fn validate(state: &mut BoPSharedState, command: &Command) -> Result<(), RuleError> {
+    validation::validate(state, command)
+}
+
+// 操作を検査してから state に適用し、起きたことをイベントとして返す
+// フェーズが完了した場合は次のフェーズへの移行まで行う
+// シーケンス番号が飛んでいる操作は、間の操作が届くまで保持してから順に適用する
+pub fn apply(state: &mut BoPSharedState, command: Command) -> Result<Vec<GameEvent>, RuleError> {
+    if let Some(seq_no) = command.seq_no() {
+        if seq_no <= state.consumed_seq_no {
+            // 再送信などにより適用済みのメッセージなので無視
+            return Ok(vec![]);
+        }
+        if !state.is_valid_new_message(seq_no) {
+            return Ok(state.buffer_command(command));
+        }
+    }
+    let mut events = apply_in_order(state, command)?;
+    events.extend(apply_buffered_commands(state));
+    Ok(events)
+}
+
+fn apply_in_order(
+    state: &mut BoPSharedState,
+    command: Command,
+) -> Result<Vec<GameEvent>, RuleError> {
+    validation::validate(state, &command)?;
+    let has_seq_no = command.seq_no().is_some();
+    if let Some(seq_no) = command.seq_no() {
+        state.update_consumed_seq_no(seq_no);
+    }
+    state.command_history.push(command.clone());
+    let turn = state.turn;
+    let mut events = match command {
+        Command::ApproveGameStart(message) => apply_approve_game_start(state, message),
+        Command::Bid(message) => apply_bid(state, message),
+        Command::UseItem(message) => apply_use_item(state, message),
+        Command::AttackTarget(message) => apply_attack_target(state, message),
+    };
+    events.extend(state.advance_phase());
+    state.push_game_logs(turn, &events);
+    if has_seq_no {
+        events.extend(state.record_state_hash());
+    }
+    Ok(events)
+}
+
+fn apply_buffered_commands(state: &mut BoPSharedState) -> Vec<GameEvent> {
+    let mut events = vec![];
+    if !state.is_desynced() {
+        return events;
+    }
+    while let Some(position) = state
+        .buffered_commands
+        .iter()
+        .position(|buffered| buffered.seq_no() == Some(state.consumed_seq_no + 1))
+    {
+        let command = state.buffered_commands.remove(position);
+        let seq_no = command.seq_no().unwrap();
+        match apply_in_order(state, command) {
+            Ok(applied_events) => events.extend(applied_events),
+            // ルール上許されない操作はシーケンス番号を消費しないので、後続の操作も適用できない
+            // 後続の操作は保持したまま、このシーケンス番号からの再送信を依頼する
+            Err(_) => {
+                events.push(GameEvent::Desynced {
+                    expected_seq_no: state.consumed_seq_no + 1,
+                    received_seq_no: seq_no,
+                });
+                return events;
+            }
+        }
+    }
+    let consumed_seq_no = state.consumed_seq_no;
+    state
+        .buffered_commands
+        .retain(|buffered| buffered.seq_no().unwrap() > consumed_seq_no);
+    if !state.is_desynced() {
+        events.push(GameEvent::Resynced);
+    }
+    events
+}
+
+// 相手が last_consumed_seq_no まで適用済みのときに、再送信すべき操作を返す
+// 負けた入札のように途中の履歴から消える操作もあるので、適用した操作の記録から取り出す
+pub fn get_commands_to_resend(state: &BoPSharedState, last_consumed_seq_no: usize) -> Vec<Command> {
+    state
+        .command_history
+        .iter()
+        .filter(|command| {
+            command.seq_no().is_some_and(|seq_no| {
+                seq_no > last_consumed_seq_no && seq_no <= state.consumed_seq_no
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+fn apply_approve_game_start(
+    state: &mut BoPSharedState,
+    message: GameStartIsApprovedMessage,
+) -> Vec<GameEvent> {
+    let was_approved = state.players[message.player_index].game_start_is_approved;
+    state.players[message.player_index].game_start_is_approved = message.game_start_is_approved;
+    if !was_approved && message.game_start_is_approved {
+        vec![GameEvent::PlayerJoined {
+            player_index: message.player_index,
+        }]
+    } else {
+        vec![]
+    }
+}
+
+fn apply_bid(state: &mut BoPSharedState, message: BidMessage) -> Vec<GameEvent> {
+    let auction_mode = state.rule_set.auction_mode;
+    let mut events = if !message.is_skipped {
+        vec![GameEvent::BidPlaced {
+            player_index: message.player_index,
+            bid_item_index: message.bid_item_index,
+            bid_amount: message.bid_amount,
+        }]
+    } else if !auction_mode.is_sealed() {
+        vec![GameEvent::BidPassed {
+            player_index: message.player_index,
+        }]
+    } else {
+        // 封印入札ではパスしたことも公開まで伏せる
+        vec![]
+    };
+    let progress_before = (auction_mode != AuctionMode::Open).then(|| state.get_auction_progress());
+    state.temporary_bid_history.push(message.clone());
+    state.ready_bid_input();
+    let Some(progress_before) = progress_before else {
+        return events;
+    };
+    let progress = state.get_auction_progress();
+    if progress.round == progress_before.round {
+        return events;
+    }
+    // ラウンドが終わったので、封印していた入札を公開する
+    if auction_mode.is_sealed() {
+        let round_bids = progress_before
+            .round_bids
+            .iter()
+            .chain(std::iter::once(&message))
+            .collect::<Vec<&BidMessage>>();
+        let bids = round_bids
+            .iter()
+            .filter(|bid| !bid.is_skipped)
+            .map(|bid| {
+                (
+                    bid.player_index,
+                    state.items_bid_on[bid.bid_item_index].item_kind.clone(),
+                    bid.bid_amount,
+                )
+            })
+            .collect::<Vec<(usize, ItemKind, u32)>>();
+        if !bids.is_empty() {
+            events.push(GameEvent::SealedBidsRevealed { bids });
+        }
+        events.extend(round_bids.iter().filter(|bid| bid.is_skipped).map(|bid| {
+            GameEvent::BidPassed {
+                player_index: bid.player_index,
+            }
+        }));
+    }
+    if auction_mode == AuctionMode::Dutch && !progress.is_complete() {
+        events.push(GameEvent::DutchPriceDropped {
+            price: progress.get_dutch_price(&state.rule_set),
+        });
+    }
+    events
+}
+
+fn apply_use_item(state: &mut BoPSharedState, message: UseItemMessage) -> Vec<GameEvent> {
+    let mut events = vec![];
+    let mut message = message;
+    // 続けて使用できるかはルール側で決める（クライアントの指定は使わない）
+    message.check_is_blocked = false;
+    if !message.is_skipped {
+        let item = state.players[message.player_index]
+            .own_item_list
+            .remove(message.use_item_index);
+        // 対象は検査済み
+        let item_set = state.item_set.clone();
+        let item_definition = item_set.get(&item.item_kind).unwrap();
+        let target_player_indexes = state
+            .resolve_item_target_player_indexes(
+                message.player_index,
+                &item_definition.target,
+                message.args_usize.first().copied(),
+            )
+            .unwrap();
+        let mut item_use_functions =
+            item_definition.get_use_func(message.player_index, &target_player_indexes);
+        item_use_functions(state);
+        events.push(GameEvent::ItemUsed {
+            player_index: message.player_index,
+            item_kind: item.item_kind,
+        });
+        if state.consume_extra_item_use(message.player_index) {
+            message.check_is_blocked = true;
+            events.push(GameEvent::ActionChained {
+                player_index: message.player_index,
+                phase_type: PhaseType::UseItem,
+            });
+        }
+    }
+    state.use_item_history.push(message);
+    events
+}
+
+fn apply_attack_target(state: &mut BoPSharedState, message: AttackTargetMessage) -> Vec<GameEvent> {
+    let mut events = vec![];
+    let mut message = message;
+    // 続けて攻撃できるかはルール側で決める（クライアントの指定は使わない）
+    message.check_is_blocked = false;
+    if message.is_skipped {
+        state.players[message.player_index]
+            .player_status
+            .current_money_amount += state.rule_set.attack_skip_money;
+        events.push(GameEvent::MoneyGained {
+            player_index: message.player_index,
+            amount: state.rule_set.attack_skip_money,
+            source: MoneySource::AttackSkipped,
+        });
+    } else {
+        let opponent_player_index = message.attack_target_player_index;
+        let player_attack_point = state.players[message.player_index]
+            .player_status
+            .attack_point;
+        let damage = state.players[opponent_player_index]
+            .player_status
+            .get_damage(player_attack_point);
+        state.players[opponent_player_index]
+            .player_status
+            .update_current_hp(-(damage as i32));
+        events.push(GameEvent::DamageDealt {
+            attacker_player_index: message.player_index,
+            target_player_index: opponent_player_index,
+            damage,
+            remaining_hp: state.players[opponent_player_index]
+                .player_status
+                .current_hp,
+        });
+        // 攻撃を受けたプレイヤーのトゲで、攻撃したプレイヤーもダメージを受ける
+        let thorns_damage = state.players[opponent_player_index]
+            .player_status
+            .get_status_effect_amount(StatusEffectKind::Thorns);
+        if thorns_damage > 0 {
+            state.players[message.player_index]
+                .player_status
+                .update_current_hp(-(thorns_damage as i32));
+            events.push(GameEvent::StatusEffectTriggered {
+                player_index: message.player_index,
+                kind: StatusEffectKind::Thorns,
+                amount: thorns_damage,
+            });
+        }
+        if state.consume_extra_attack(message.player_index) {
+            message.check_is_blocked = true;
+            events.push(GameEvent::ActionChained {
+                player_index: message.player_index,
+                phase_type: PhaseType::AttackTarget,
+            });
+        }
+    }
+    state.attack_target_history.push(message);
+    events
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::bop_core::cpu_player::playout;
+    use crate::bop_core::random::SeededRng;
+    use crate::bop_core::state::bop_shared_state::LogType;
+    use crate::bop_core::state::snapshot::state_hash;
+
+    // 全員が開始を承認し、入札フェーズに入った state
+    pub(crate) fn start_game(seed: u64, players_len: usize) -> BoPSharedState {
+        let mut state = BoPSharedState::init_for_players(seed, players_len);
+        for player_index in 0..players_len {
+            let command = Command::ApproveGameStart(GameStartIsApprovedMessage {
+                player_index,
+                game_start_is_approved: true,
+            });
+            apply(&mut state, command).unwrap();
+        }
+        state
+    }
+
+    // CPUのプレイアウトと同じ乱択で、入力を max_steps 回まで進める
+    pub(crate) fn play_randomly(state: &mut BoPSharedState, seed: u64, max_steps: usize) {
+        state.has_cpu = false;
+        playout(state, &mut SeededRng::from_seed(seed), max_steps);
+    }
+
+    // 指定したフェーズになるまで入力を乱択で進め、入力すべきプレイヤーを返す
+    pub(crate) fn advance_to_phase(state: &mut BoPSharedState, phase_type: PhaseType) -> usize {
+        for step in 0..1000 {
+            if state.phases[state.phase_index].phase_type == phase_type {
+                return get_required_player_index(state);
+            }
+            play_randomly(state, step, 1);
+        }
+        panic!("{:?} になりませんでした", phase_type);
+    }
+
+    pub(crate) fn get_required_player_index(state: &mut BoPSharedState) -> usize {
+        (0..state.players.len())
+            .find(|player_index| state.is_required_input_of(*player_index))
+            .unwrap()
+    }
+
+    pub(crate) fn create_bid(
+        state: &BoPSharedState,
+        player_index: usize,
+        bid_item_index: usize,
+        bid_amount: u32,
+    ) -> Command {
+        Command::Bid(BidMessage {
+            seq_no: state.get_seq_no_to_send(),
+            player_index,
+            bid_item_index,
+            bid_amount,
+            is_skipped: false,
+        })
+    }
+
+    pub(crate) fn create_attack(
+        state: &BoPSharedState,
+        player_index: usize,
+        attack_target_player_index: usize,
+    ) -> Command {
+        Command::AttackTarget(AttackTargetMessage {
+            seq_no: state.get_seq_no_to_send(),
+            turn: state.turn,
+            player_index,
+            check_is_blocked: false,
+            attack_target_player_index,
+            is_skipped: false,
+        })
+    }
+
+    // 入札できる最低の金額で、最初のアイテムに入札する
+    pub(crate) fn create_lowest_bid(state: &mut BoPSharedState) -> Command {
+        let player_index = get_required_player_index(state);
+        let bid_amount = state.lowest_bid_amount(0);
+        create_bid(state, player_index, 0, bid_amount)
+    }
+
+    #[test]
+    fn random_game_reaches_end() {
+        for players_len in 2..=4 {
+            let mut state = start_game(1, players_len);
+            play_randomly(&mut state, 1, usize::MAX);
+            assert!(state.game_is_end());
+        }
+    }
+
+    #[test]
+    fn apply_consumes_seq_no() {
+        let mut state = start_game(1, 2);
+        let command = create_lowest_bid(&mut state);
+        let events = apply(&mut state, command).unwrap();
+        assert!(!events.is_empty());
+        assert_eq!(state.consumed_seq_no, 1);
+        assert_eq!(state.command_history.len(), 3);
+    }
+
+    #[test]
+    fn apply_rejects_command_of_wrong_phase() {
+        let mut state = start_game(1, 2);
+        let player_index = get_required_player_index(&mut state);
+        let command = create_attack(
+            &state,
+            player_index,
+            state.opponent_player_index(player_index),
+        );
+        assert_eq!(apply(&mut state, command), Err(RuleError::WrongPhase));
+        assert_eq!(state.consumed_seq_no, 0);
+        assert_eq!(state.command_history.len(), 2);
+    }
+
+    #[test]
+    fn command_round_trips_through_message() {
+        let mut state = start_game(1, 2);
+        let command = create_lowest_bid(&mut state);
+        let parsed = Command::from_message(&command.to_message()).unwrap();
+        assert_eq!(parsed.seq_no(), command.seq_no());
+        assert_eq!(parsed.player_index(), command.player_index());
+        assert_eq!(parsed.to_message(), command.to_message());
+    }
+
+    #[test]
+    fn command_from_message_rejects_malformed_message() {
+        assert!(matches!(
+            Command::from_message("not json"),
+            Err(RuleError::MalformedMessage(_))
+        ));
+    }
+
+    #[test]
+    fn apply_ignores_applied_seq_no() {
+        let mut state = start_game(1, 2);
+        let command = create_lowest_bid(&mut state);
+        apply(&mut state, command.clone()).unwrap();
+        assert_eq!(apply(&mut state, command), Ok(vec![]));
+        assert_eq!(state.consumed_seq_no, 1);
+    }
+
+    #[test]
+    fn apply_buffers_command_until_gap_is_filled() {
+        let mut state = start_game(1, 2);
+        let first = create_lowest_bid(&mut state);
+        let mut next_state = state.clone();
+        apply(&mut next_state, first.clone()).unwrap();
+        let second = create_lowest_bid(&mut next_state);
+
+        let events = apply(&mut state, second).unwrap();
+        assert_eq!(
+            events,
+            vec![GameEvent::Desynced {
+                expected_seq_no: 1,
+                received_seq_no: 2,
+            }]
+        );
+        assert!(state.is_desynced());
+
+        let events = apply(&mut state, first).unwrap();
+        assert_eq!(events.last(), Some(&GameEvent::Resynced));
+        assert_eq!(state.consumed_seq_no, 2);
+        assert!(!state.is_desynced());
+    }
+
+    #[test]
+    fn apply_requests_resend_when_buffered_command_is_invalid() {
+        let mut state = start_game(1, 2);
+        let first = create_lowest_bid(&mut state);
+        let mut next_state = state.clone();
+        apply(&mut next_state, first.clone()).unwrap();
+        let player_index = get_required_player_index(&mut next_state);
+        let invalid = create_bid(&next_state, player_index, 99, 1);
+
+        apply(&mut state, invalid).unwrap();
+        let events = apply(&mut state, first).unwrap();
+        assert_eq!(
+            events.last(),
+            Some(&GameEvent::Desynced {
+                expected_seq_no: 2,
+                received_seq_no: 2,
+            })
+        );
+        assert!(!events.contains(&GameEvent::Resynced));
+        assert_eq!(state.consumed_seq_no, 1);
+    }
+
+    #[test]
+    fn resend_recovers_lost_losing_bid() {
+        let mut sender_state = start_game(1, 2);
+        let mut receiver_state = sender_state.clone();
+        // 最初の入札は次の入札に上書きされて負ける
+        let losing_bid = create_lowest_bid(&mut sender_state);
+        apply(&mut sender_state, losing_bid).unwrap();
+        let winning_bid = create_lowest_bid(&mut sender_state);
+        apply(&mut sender_state, winning_bid).unwrap();
+        for step in 0..100 {
+            if sender_state.phases[sender_state.phase_index].phase_type != PhaseType::Bid {
+                break;
+            }
+            play_randomly(&mut sender_state, step, 1);
+        }
+        assert_ne!(
+            sender_state.phases[sender_state.phase_index].phase_type,
+            PhaseType::Bid
+        );
+        // 入札の履歴からは消えている
+        assert!(!sender_state
+            .bid_history
+            .iter()
+            .chain(sender_state.temporary_bid_history.iter())
+            .any(|bid| bid.seq_no == 1));
+
+        // 負けた入札だけが届かなかった
+        for command in get_commands_to_resend(&sender_state, 1) {
+            apply(&mut receiver_state, command).unwrap();
+        }
+        assert!(receiver_state.is_desynced());
+        assert_eq!(receiver_state.consumed_seq_no, 0);
+
+        for command in get_commands_to_resend(&sender_state, receiver_state.consumed_seq_no) {
+            apply(&mut receiver_state, command).unwrap();
+        }
+        assert!(!receiver_state.is_desynced());
+        assert_eq!(receiver_state.consumed_seq_no, sender_state.consumed_seq_no);
+        receiver_state.own_player_index = sender_state.own_player_index;
+        assert_eq!(state_hash(&receiver_state), state_hash(&sender_state));
+    }
+
+    #[test]
+    fn eliminated_player_is_skipped_for_every_input() {
+        let mut state = start_game(1, 3);
+        let player_index = advance_to_phase(&mut state, PhaseType::AttackTarget);
+        let target_player_index = state.opponent_player_index(player_index);
+        state.players[target_player_index].player_status.current_hp = 1;
+        let command = create_attack(&state, player_index, target_player_index);
+        let events = apply(&mut state, command).unwrap();
+        assert!(events.contains(&GameEvent::PlayerEliminated {
+            player_index: target_player_index,
+        }));
+        assert!(!state.game_is_end());
+
+        let commands_len = state.command_history.len();
+        let mut phase_types = vec![];
+        for step in 0..1000 {
+            if state.game_is_end() {
+                break;
+            }
+            assert!(!state.is_required_input_of(target_player_index));
+            phase_types.push(state.phases[state.phase_index].phase_type.clone());
+            play_randomly(&mut state, step, 1);
+        }
+        for phase_type in [PhaseType::Bid, PhaseType::UseItem, PhaseType::AttackTarget] {
+            assert!(phase_types.contains(&phase_type));
+        }
+        assert!(state.command_history[commands_len..]
+            .iter()
+            .all(|command| command.player_index() != target_player_index));
+    }
+
+    #[test]
+    fn game_ends_when_one_player_is_left() {
+        for players_len in 3..=4 {
+            let mut state = start_game(2, players_len);
+            play_randomly(&mut state, 2, usize::MAX);
+            assert_eq!(
+                state.phases[state.phase_index].phase_type,
+                PhaseType::GameEnd
+            );
+            let survivor_player_indexes = (0..players_len)
+                .filter(|player_index| !state.players[*player_index].is_lose())
+                .collect::<Vec<usize>>();
+            assert_eq!(survivor_player_indexes.len(), 1);
+            assert_eq!(state.initiatives_to_player_index, survivor_player_indexes);
+            assert!(matches!(
+                &state.game_logs.last().unwrap().log_type,
+                LogType::GameEnd(winner_player_indexes)
+                    if *winner_player_indexes == survivor_player_indexes
+            ));
+        }
+    }
+}
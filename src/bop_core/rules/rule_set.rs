@@ -0,0 +1,280 @@
+use crate::bop_core::mechanism::player_status::PlayerStatus;
+use crate::bop_core::rules::RuleError;
+use serde::{Deserialize, Serialize};
+
+// 対戦ごとに変えられるルールの数値
+// オンライン対戦ではマッチング時に GameRuleMessage で共有する
+// 項目を追加しても以前のセーブデータを読めるよう、ない項目は標準のルールの値を使う
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuleSet {
+    pub preset: RuleSetPreset,
+    // 開始時のステータス
+    pub initial_max_hp: u32,
+    pub initial_attack_point: u32,
+    pub initial_defence_point: u32,
+    pub initial_money_amount: u32,
+    pub initial_gain: u32,
+    // 入札対象のアイテムは生存しているプレイヤー数よりこの数だけ多い
+    pub extra_items_on_offer: usize,
+    // 現在価格が1以上の場合、最低入札価格は現在価格 + bid_increment
+    pub bid_increment: u32,
+    // 全員がこの数だけ落札するまで入札を続けてから戦闘に移る
+    pub items_drafted_before_combat: usize,
+    // 攻撃しなかった場合に得る Money
+    pub attack_skip_money: u32,
+    pub auction_mode: AuctionMode,
+    // 最低落札価格。誰も入札していないアイテムの最低入札価格でもある
+    pub reserve_price: u32,
+    // ダッチ方式の開始価格。ラウンドごとに bid_increment ずつ reserve_price まで下がる
+    pub dutch_start_price: u32,
+}
+
+// 入札の方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum AuctionMode {
+    // 行動順に競り上げ、同じアイテムに入札したプレイヤーがいなくなるまで続ける
+    #[default]
+    Open,
+    // 全員が1回ずつ入札してから公開し、最も高い入札者が入札額を支払う
+    // 落札できなかったプレイヤーは残りのアイテムに入札し直す
+    SealedFirstPrice,
+    // 封印入札と同じ進め方で、落札者は2番目に高い入札額（なければ最低落札価格）を支払う
+    SecondPrice,
+    // 価格がラウンドごとに下がり、行動順に今の価格で落札するか見送るかを選ぶ
+    Dutch,
+}
+
+impl AuctionMode {
+    pub const ALL: [AuctionMode; 4] = [
+        AuctionMode::Open,
+        AuctionMode::SealedFirstPrice,
+        AuctionMode::SecondPrice,
+        AuctionMode::Dutch,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            AuctionMode::Open => "競り上げ",
+            AuctionMode::SealedFirstPrice => "封印入札",
+            AuctionMode::SecondPrice => "セカンドプライス",
+            AuctionMode::Dutch => "ダッチ",
+        }
+    }
+
+    // コマンドライン引数などで使う名前
+    pub fn get_id(&self) -> &'static str {
+        match self {
+            AuctionMode::Open => "open",
+            AuctionMode::SealedFirstPrice => "sealed",
+            AuctionMode::SecondPrice => "second-price",
+            AuctionMode::Dutch => "dutch",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<AuctionMode> {
+        AuctionMode::ALL
+            .iter()
+            .find(|auction_mode| auction_mode.get_id() == id)
+            .copied()
+    }
+
+    // タイトル画面で選び直す際の次の方式
+    pub fn next(&self) -> AuctionMode {
+        let index = AuctionMode::ALL
+            .iter()
+            .position(|auction_mode| auction_mode == self)
+            .unwrap();
+        AuctionMode::ALL[(index + 1) % AuctionMode::ALL.len()]
+    }
+
+    // 他のプレイヤーの入札がラウンドの終わりまで公開されない
+    pub fn is_sealed(&self) -> bool {
+        matches!(
+            self,
+            AuctionMode::SealedFirstPrice | AuctionMode::SecondPrice
+        )
+    }
+}
+
+// 名前付きのルール
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RuleSetPreset {
+    // HPが少なく、1つ落札したら戦闘に移る短期戦
+    Quick,
+    Standard,
+    // Money が多く、入札が激しくなる
+    HighEconomy,
+}
+
+impl RuleSetPreset {
+    pub const ALL: [RuleSetPreset; 3] = [
+        RuleSetPreset::Quick,
+        RuleSetPreset::Standard,
+        RuleSetPreset::HighEconomy,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            RuleSetPreset::Quick => "クイック",
+            RuleSetPreset::Standard => "スタンダード",
+            RuleSetPreset::HighEconomy => "ハイエコノミー",
+        }
+    }
+
+    // コマンドライン引数などで使う名前
+    pub fn get_id(&self) -> &'static str {
+        match self {
+            RuleSetPreset::Quick => "quick",
+            RuleSetPreset::Standard => "standard",
+            RuleSetPreset::HighEconomy => "high-economy",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<RuleSetPreset> {
+        RuleSetPreset::ALL
+            .iter()
+            .find(|preset| preset.get_id() == id)
+            .copied()
+    }
+
+    // タイトル画面で選び直す際の次のルール
+    pub fn next(&self) -> RuleSetPreset {
+        let index = RuleSetPreset::ALL
+            .iter()
+            .position(|preset| preset == self)
+            .unwrap();
+        RuleSetPreset::ALL[(index + 1) % RuleSetPreset::ALL.len()]
+    }
+
+    pub fn get_rule_set(&self) -> RuleSet {
+        let standard = RuleSet {
+            preset: *self,
+            initial_max_hp: 50,
+            initial_attack_point: 10,
+            initial_defence_point: 5,
+            initial_money_amount: 5,
+            initial_gain: 3,
+            extra_items_on_offer: 1,
+            bid_increment: 2,
+            items_drafted_before_combat: 2,
+            attack_skip_money: 1,
+            auction_mode: AuctionMode::Open,
+            reserve_price: 1,
+            dutch_start_price: 10,
+        };
+        match self {
+            RuleSetPreset::Quick => RuleSet {
+                initial_max_hp: 30,
+                initial_attack_point: 12,
+                initial_defence_point: 3,
+                items_drafted_before_combat: 1,
+                ..standard
+            },
+            RuleSetPreset::Standard => standard,
+            RuleSetPreset::HighEconomy => RuleSet {
+                initial_money_amount: 10,
+                initial_gain: 5,
+                extra_items_on_offer: 2,
+                bid_increment: 3,
+                attack_skip_money: 3,
+                dutch_start_price: 15,
+                ..standard
+            },
+        }
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> RuleSet {
+        RuleSetPreset::Standard.get_rule_set()
+    }
+}
+
+impl RuleSet {
+    pub fn create_player_status(&self) -> PlayerStatus {
+        let mut player_status = PlayerStatus::init();
+        player_status.max_hp = self.initial_max_hp;
+        player_status.current_hp = self.initial_max_hp;
+        player_status.attack_point = self.initial_attack_point;
+        player_status.defence_point = self.initial_defence_point;
+        player_status.current_money_amount = self.initial_money_amount;
+        player_status.estimated_money_amount = self.initial_gain;
+        player_status
+    }
+
+    // 入札が進まなくなったり、ダッチ方式の価格が決まらなくなったりする値を弾く
+    // セーブデータ・リプレイ・マッチングで受け取ったルールは、使う前にこれで確かめる
+    pub fn validate(&self) -> Result<(), RuleError> {
+        if self.bid_increment == 0 {
+            return Err(RuleError::InvalidRuleSet(
+                "bid_increment は 1 以上にしてください".to_string(),
+            ));
+        }
+        // ダッチ方式では入札額 0 を見送りとして扱うので、0 では落札できない
+        if self.reserve_price == 0 {
+            return Err(RuleError::InvalidRuleSet(
+                "reserve_price は 1 以上にしてください".to_string(),
+            ));
+        }
+        if self.dutch_start_price < self.reserve_price {
+            return Err(RuleError::InvalidRuleSet(
+                "dutch_start_price は reserve_price 以上にしてください".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    // 生存しているプレイヤー数に対する入札対象のアイテム数
+    pub fn get_items_on_offer_len(&self, active_players_len: usize) -> usize {
+        active_players_len + self.extra_items_on_offer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_are_valid() {
+        for preset in RuleSetPreset::ALL {
+            for auction_mode in AuctionMode::ALL {
+                let rule_set = RuleSet {
+                    auction_mode,
+                    ..preset.get_rule_set()
+                };
+                assert_eq!(rule_set.validate(), Ok(()));
+            }
+            assert_eq!(RuleSetPreset::from_id(preset.get_id()), Some(preset));
+        }
+    }
+
+    #[test]
+    fn validate_rejects_values_that_stall_auction() {
+        let standard = RuleSet::default();
+        for rule_set in [
+            RuleSet {
+                bid_increment: 0,
+                ..standard.clone()
+            },
+            RuleSet {
+                reserve_price: 0,
+                ..standard.clone()
+            },
+            RuleSet {
+                dutch_start_price: standard.reserve_price - 1,
+                ..standard.clone()
+            },
+        ] {
+            assert!(matches!(
+                rule_set.validate(),
+                Err(RuleError::InvalidRuleSet(_))
+            ));
+        }
+        let rule_set = RuleSet {
+            dutch_start_price: standard.reserve_price,
+            ..standard
+        };
+        assert_eq!(rule_set.validate(), Ok(()));
+    }
+}
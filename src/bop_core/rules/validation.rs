@@ -0,0 +1,310 @@
+use crate::bop_core::rules::rule_set::AuctionMode;
+use crate::bop_core::rules::{Command, RuleError};
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+use crate::bop_core::state::phase::PhaseType;
+
+// 受信した操作がルール上許されるかを、適用前に検査する
+// 改造されたクライアントからのメッセージで不正な状態になったり、パニックしたりしないようにする
+pub fn validate(state: &mut BoPSharedState, command: &Command) -> Result<(), RuleError> {
+    let expected_phase_type = match command {
+        // 開始前であれば何度でも受け付ける
+        // 誰か一人の承認で開始するので、開始後も他のプレイヤーの最初の承認だけは受け付ける
+        Command::ApproveGameStart(message) => {
+            if message.player_index >= state.players.len() {
+                return Err(RuleError::NoSuchPlayer);
+            }
+            let is_first_approval = message.game_start_is_approved
+                && !state.players[message.player_index].game_start_is_approved;
+            return if state.phases[state.phase_index].phase_type == PhaseType::GameStart
+                || is_first_approval
+            {
+                Ok(())
+            } else {
+                Err(RuleError::GameAlreadyStarted)
+            };
+        }
+        Command::Bid(_) => PhaseType::Bid,
+        Command::UseItem(_) => PhaseType::UseItem,
+        Command::AttackTarget(_) => PhaseType::AttackTarget,
+    };
+    if state.phases[state.phase_index].phase_type != expected_phase_type {
+        return Err(RuleError::WrongPhase);
+    }
+    let player_index = command.player_index();
+    if player_index >= state.players.len() {
+        return Err(RuleError::NoSuchPlayer);
+    }
+    if !state.is_required_input_of(player_index) {
+        return Err(RuleError::NotYourTurn);
+    }
+    match command {
+        Command::Bid(message) => {
+            // パスはどの方式でもいつでもできる
+            if message.is_skipped {
+                return Ok(());
+            }
+            if message.bid_item_index >= state.items_bid_on.len() {
+                return Err(RuleError::NoSuchItem);
+            }
+            if state.rule_set.auction_mode != AuctionMode::Open {
+                // ダッチ方式では 0 で見送る
+                if state.rule_set.auction_mode == AuctionMode::Dutch && message.bid_amount == 0 {
+                    return if state.can_wait_for_lower_price() {
+                        Ok(())
+                    } else {
+                        Err(RuleError::CannotWait)
+                    };
+                }
+                if state.get_auction_progress().is_won(message.bid_item_index) {
+                    return Err(RuleError::ItemAlreadyWon);
+                }
+            }
+            if message.bid_amount
+                > state.players[player_index]
+                    .player_status
+                    .current_money_amount
+            {
+                return Err(RuleError::InsufficientMoney);
+            }
+            let lowest_bid_amount = state.lowest_bid_amount(message.bid_item_index);
+            if state.rule_set.auction_mode == AuctionMode::Dutch
+                && message.bid_amount != lowest_bid_amount
+            {
+                return Err(RuleError::WrongPrice);
+            }
+            if message.bid_amount < lowest_bid_amount {
+                return Err(RuleError::BidTooLow);
+            }
+        }
+        Command::UseItem(message) => {
+            if message.turn != state.turn {
+                return Err(RuleError::StaleTurn);
+            }
+            if message.is_skipped {
+                return Ok(());
+            }
+            let Some(item) = state.players[player_index]
+                .own_item_list
+                .get(message.use_item_index)
+            else {
+                return Err(RuleError::NoSuchItem);
+            };
+            // アイテムセットに定義がないアイテムは使えない
+            let Some(item_definition) = state.item_set.get(&item.item_kind) else {
+                return Err(RuleError::NoSuchItem);
+            };
+            // 対象は args_usize の先頭で指定する
+            if state
+                .resolve_item_target_player_indexes(
+                    player_index,
+                    &item_definition.target,
+                    message.args_usize.first().copied(),
+                )
+                .is_none()
+            {
+                return Err(RuleError::InvalidTarget);
+            }
+        }
+        Command::AttackTarget(message) => {
+            if message.turn != state.turn {
+                return Err(RuleError::StaleTurn);
+            }
+            if !message.is_skipped
+                && !state
+                    .get_opponent_player_indexes(player_index)
+                    .contains(&message.attack_target_player_index)
+            {
+                return Err(RuleError::InvalidTarget);
+            }
+        }
+        Command::ApproveGameStart(_) => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bop_core::mechanism::item::{Item, ItemKind};
+    use crate::bop_core::rules::tests::{
+        advance_to_phase, create_attack, create_bid, get_required_player_index, start_game,
+    };
+    use crate::bop_core::state::message::{GameStartIsApprovedMessage, UseItemMessage};
+
+    fn create_use_item(
+        state: &BoPSharedState,
+        player_index: usize,
+        use_item_index: usize,
+        args_usize: Vec<usize>,
+    ) -> Command {
+        Command::UseItem(UseItemMessage {
+            seq_no: state.get_seq_no_to_send(),
+            turn: state.turn,
+            check_is_blocked: false,
+            player_index,
+            use_item_index,
+            is_skipped: false,
+            args_i32: vec![],
+            args_usize,
+        })
+    }
+
+    fn give_item(state: &mut BoPSharedState, player_index: usize, item_kind: &str) -> usize {
+        let own_item_list = &mut state.players[player_index].own_item_list;
+        own_item_list.push(Item::from(ItemKind(item_kind.to_string())));
+        own_item_list.len() - 1
+    }
+
+    #[test]
+    fn validate_accepts_lowest_bid() {
+        let mut state = start_game(1, 2);
+        let player_index = get_required_player_index(&mut state);
+        let command = create_bid(&state, player_index, 0, state.lowest_bid_amount(0));
+        assert_eq!(validate(&mut state, &command), Ok(()));
+    }
+
+    #[test]
+    fn validate_rejects_invalid_bids() {
+        let mut state = start_game(1, 2);
+        let player_index = get_required_player_index(&mut state);
+        let lowest_bid_amount = state.lowest_bid_amount(0);
+        let current_money_amount = state.players[player_index]
+            .player_status
+            .current_money_amount;
+        let cases = [
+            (
+                create_bid(&state, player_index, 0, lowest_bid_amount - 1),
+                RuleError::BidTooLow,
+            ),
+            (
+                create_bid(&state, player_index, 0, current_money_amount + 1),
+                RuleError::InsufficientMoney,
+            ),
+            (
+                create_bid(&state, player_index, 99, lowest_bid_amount),
+                RuleError::NoSuchItem,
+            ),
+            (
+                create_bid(&state, 99, 0, lowest_bid_amount),
+                RuleError::NoSuchPlayer,
+            ),
+        ];
+        for (command, error) in cases {
+            assert_eq!(validate(&mut state, &command), Err(error));
+        }
+    }
+
+    #[test]
+    fn validate_rejects_command_of_wrong_phase() {
+        let mut state = start_game(1, 2);
+        let player_index = get_required_player_index(&mut state);
+        let command = create_use_item(&state, player_index, 0, vec![]);
+        assert_eq!(validate(&mut state, &command), Err(RuleError::WrongPhase));
+    }
+
+    #[test]
+    fn validate_rejects_approval_after_start() {
+        let mut state = BoPSharedState::init_for_players(1, 2);
+        let approve = |player_index| {
+            Command::ApproveGameStart(GameStartIsApprovedMessage {
+                player_index,
+                game_start_is_approved: true,
+            })
+        };
+        assert_eq!(validate(&mut state, &approve(0)), Ok(()));
+        assert_eq!(
+            validate(&mut state, &approve(2)),
+            Err(RuleError::NoSuchPlayer)
+        );
+        let mut state = start_game(1, 2);
+        assert_eq!(
+            validate(&mut state, &approve(0)),
+            Err(RuleError::GameAlreadyStarted)
+        );
+        let withdraw = Command::ApproveGameStart(GameStartIsApprovedMessage {
+            player_index: 0,
+            game_start_is_approved: false,
+        });
+        assert_eq!(
+            validate(&mut state, &withdraw),
+            Err(RuleError::GameAlreadyStarted)
+        );
+    }
+
+    #[test]
+    fn validate_use_item() {
+        let mut state = start_game(1, 2);
+        let player_index = advance_to_phase(&mut state, PhaseType::UseItem);
+        let opponent_player_index = state.opponent_player_index(player_index);
+        let shrink_index = give_item(&mut state, player_index, "Shrink");
+        let undefined_index = give_item(&mut state, player_index, "Undefined");
+        let items_len = state.players[player_index].own_item_list.len();
+
+        let command = create_use_item(&state, player_index, shrink_index, vec![]);
+        assert_eq!(validate(&mut state, &command), Ok(()));
+        let command = create_use_item(
+            &state,
+            player_index,
+            shrink_index,
+            vec![opponent_player_index],
+        );
+        assert_eq!(validate(&mut state, &command), Ok(()));
+
+        let cases = [
+            (
+                create_use_item(&state, player_index, items_len, vec![]),
+                RuleError::NoSuchItem,
+            ),
+            (
+                create_use_item(&state, player_index, undefined_index, vec![]),
+                RuleError::NoSuchItem,
+            ),
+            (
+                create_use_item(&state, player_index, shrink_index, vec![player_index]),
+                RuleError::InvalidTarget,
+            ),
+            (
+                create_use_item(&state, player_index, shrink_index, vec![99]),
+                RuleError::InvalidTarget,
+            ),
+        ];
+        for (command, error) in cases {
+            assert_eq!(validate(&mut state, &command), Err(error));
+        }
+        let mut command = create_use_item(&state, player_index, shrink_index, vec![]);
+        if let Command::UseItem(message) = &mut command {
+            message.turn = state.turn + 1;
+        }
+        assert_eq!(validate(&mut state, &command), Err(RuleError::StaleTurn));
+    }
+
+    #[test]
+    fn validate_attack_target() {
+        let mut state = start_game(1, 3);
+        let player_index = advance_to_phase(&mut state, PhaseType::AttackTarget);
+        let opponent_player_indexes = state.get_opponent_player_indexes(player_index);
+        assert_eq!(opponent_player_indexes.len(), 2);
+
+        let command = create_attack(&state, player_index, opponent_player_indexes[0]);
+        assert_eq!(validate(&mut state, &command), Ok(()));
+        let command = create_attack(&state, player_index, player_index);
+        assert_eq!(
+            validate(&mut state, &command),
+            Err(RuleError::InvalidTarget)
+        );
+
+        // 脱落したプレイヤーは攻撃できない
+        let eliminated_player_index = opponent_player_indexes[1];
+        state.players[eliminated_player_index]
+            .player_status
+            .current_hp = 0;
+        state
+            .initiatives_to_player_index
+            .retain(|player_index| *player_index != eliminated_player_index);
+        let command = create_attack(&state, player_index, eliminated_player_index);
+        assert_eq!(
+            validate(&mut state, &command),
+            Err(RuleError::InvalidTarget)
+        );
+    }
+}
@@ -1,4 +1,6 @@
-pub mod bind;
+pub mod auction;
 pub mod bop_shared_state;
 pub mod message;
 pub mod phase;
+pub mod save_data;
+pub mod snapshot;
@@ -0,0 +1,322 @@
+use crate::bop_core::rules::rule_set::{AuctionMode, RuleSet};
+use crate::bop_core::state::message::BidMessage;
+
+// 封印入札・セカンドプライス・ダッチ方式の入札の進み具合
+// 入札履歴から毎回導出するので、セーブデータやリプレイに別に記録する必要はない
+// どの方式でも、通信でシーケンス番号がぶつからないよう行動順に1人ずつ入札する
+#[derive(Debug, Clone)]
+pub struct AuctionProgress {
+    pub auction_mode: AuctionMode,
+    // 落札が決まった入札（決まった順）
+    pub winning_bids: Vec<WinningBid>,
+    // 現在のラウンドで入札するプレイヤー（行動順）
+    pub bidder_player_indexes: Vec<usize>,
+    // 現在のラウンドで入札済みの入札
+    pub round_bids: Vec<BidMessage>,
+    // 終わったラウンドの数（ダッチ方式では価格が下がった回数）
+    pub round: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WinningBid {
+    pub player_index: usize,
+    pub bid_item_index: usize,
+    pub bid_amount: u32,
+    // 実際に支払う金額。セカンドプライスでは入札額より低くなることがある
+    pub price: u32,
+}
+
+impl AuctionProgress {
+    // 行動順と、このフェーズの入札履歴から進み具合を導く
+    // 検査済みの入札のみが履歴に入るので、手番でない入札は考えない
+    pub fn from_history(
+        active_player_indexes: &[usize],
+        temporary_bid_history: &[BidMessage],
+        rule_set: &RuleSet,
+    ) -> AuctionProgress {
+        // 最初のラウンドは全員がフェーズ開始時の行動順に入札するので、履歴に現れた順がその行動順になる
+        // 落札の確定は行動順の並べ替えの後に行われるため、現在の行動順は使わない
+        let mut bidder_player_indexes = vec![];
+        for player_index in temporary_bid_history
+            .iter()
+            .map(|bid| bid.player_index)
+            .chain(active_player_indexes.iter().copied())
+        {
+            if active_player_indexes.contains(&player_index)
+                && !bidder_player_indexes.contains(&player_index)
+            {
+                bidder_player_indexes.push(player_index);
+            }
+        }
+        let mut progress = AuctionProgress {
+            auction_mode: rule_set.auction_mode,
+            winning_bids: vec![],
+            bidder_player_indexes,
+            round_bids: vec![],
+            round: 0,
+        };
+        for bid in temporary_bid_history.iter() {
+            if progress.get_next_bidder() != Some(bid.player_index) {
+                continue;
+            }
+            progress.round_bids.push(bid.clone());
+            // ダッチ方式では、落札した時点で他のプレイヤーはそのアイテムを選べなくなる
+            if progress.auction_mode == AuctionMode::Dutch && !bid.is_skipped && bid.bid_amount > 0
+            {
+                progress.winning_bids.push(WinningBid {
+                    player_index: bid.player_index,
+                    bid_item_index: bid.bid_item_index,
+                    bid_amount: bid.bid_amount,
+                    price: bid.bid_amount,
+                });
+            }
+            if progress.round_bids.len() == progress.bidder_player_indexes.len() {
+                progress.close_round(rule_set);
+            }
+        }
+        progress
+    }
+
+    // 全員の入札が済んだラウンドを締める
+    fn close_round(&mut self, rule_set: &RuleSet) {
+        let round_bids = std::mem::take(&mut self.round_bids);
+        let mut next_bidder_player_indexes = vec![];
+        for player_index in self.bidder_player_indexes.iter().copied() {
+            let bid = round_bids
+                .iter()
+                .find(|bid| bid.player_index == player_index)
+                .unwrap();
+            // パスしたプレイヤーは落札せず、次のラウンドにも参加しない
+            if bid.is_skipped {
+                continue;
+            }
+            match self.auction_mode {
+                // 見送ったプレイヤーは次のラウンドで下がった価格から選び直す
+                AuctionMode::Dutch => {
+                    if bid.bid_amount == 0 {
+                        next_bidder_player_indexes.push(player_index);
+                    }
+                }
+                _ => {
+                    // 同じアイテムへの入札のうち最も高いものが落札する
+                    // 金額が同じ場合は行動順が先のプレイヤーを優先する
+                    let competing_bids = self
+                        .bidder_player_indexes
+                        .iter()
+                        .filter_map(|bidder_player_index| {
+                            round_bids.iter().find(|competing_bid| {
+                                competing_bid.player_index == *bidder_player_index
+                                    && !competing_bid.is_skipped
+                                    && competing_bid.bid_item_index == bid.bid_item_index
+                            })
+                        })
+                        .collect::<Vec<&BidMessage>>();
+                    let highest_amount = competing_bids
+                        .iter()
+                        .map(|competing_bid| competing_bid.bid_amount)
+                        .max()
+                        .unwrap();
+                    let winner_player_index = competing_bids
+                        .iter()
+                        .find(|competing_bid| competing_bid.bid_amount == highest_amount)
+                        .unwrap()
+                        .player_index;
+                    if winner_player_index != player_index {
+                        next_bidder_player_indexes.push(player_index);
+                        continue;
+                    }
+                    let price = if self.auction_mode == AuctionMode::SecondPrice {
+                        competing_bids
+                            .iter()
+                            .filter(|competing_bid| competing_bid.player_index != player_index)
+                            .map(|competing_bid| competing_bid.bid_amount)
+                            .max()
+                            .unwrap_or(rule_set.reserve_price)
+                            .max(rule_set.reserve_price)
+                    } else {
+                        bid.bid_amount
+                    };
+                    self.winning_bids.push(WinningBid {
+                        player_index,
+                        bid_item_index: bid.bid_item_index,
+                        bid_amount: bid.bid_amount,
+                        price,
+                    });
+                }
+            }
+        }
+        self.bidder_player_indexes = next_bidder_player_indexes;
+        self.round += 1;
+    }
+
+    // 全員の落札（またはパス）が決まった
+    pub fn is_complete(&self) -> bool {
+        self.bidder_player_indexes.is_empty()
+    }
+
+    // 現在のラウンドで、行動順で最初のまだ入札していないプレイヤー
+    pub fn get_next_bidder(&self) -> Option<usize> {
+        self.bidder_player_indexes
+            .iter()
+            .find(|player_index| {
+                !self
+                    .round_bids
+                    .iter()
+                    .any(|bid| bid.player_index == **player_index)
+            })
+            .copied()
+    }
+
+    pub fn is_won(&self, item_index: usize) -> bool {
+        self.winning_bids
+            .iter()
+            .any(|winning_bid| winning_bid.bid_item_index == item_index)
+    }
+
+    pub fn get_winning_bid(&self, item_index: usize) -> Option<&WinningBid> {
+        self.winning_bids
+            .iter()
+            .find(|winning_bid| winning_bid.bid_item_index == item_index)
+    }
+
+    // ダッチ方式の現在の価格
+    pub fn get_dutch_price(&self, rule_set: &RuleSet) -> u32 {
+        rule_set
+            .dutch_start_price
+            .saturating_sub(rule_set.bid_increment * self.round as u32)
+            .max(rule_set.reserve_price)
+    }
+
+    // ダッチ方式で、まだ価格が下がるので見送ることができる
+    pub fn can_wait(&self, rule_set: &RuleSet) -> bool {
+        self.get_dutch_price(rule_set) > rule_set.reserve_price
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_rule_set(auction_mode: AuctionMode) -> RuleSet {
+        RuleSet {
+            auction_mode,
+            reserve_price: 2,
+            dutch_start_price: 10,
+            bid_increment: 3,
+            ..RuleSet::default()
+        }
+    }
+
+    fn create_bid(player_index: usize, bid_item_index: usize, bid_amount: u32) -> BidMessage {
+        BidMessage {
+            seq_no: 0,
+            player_index,
+            bid_item_index,
+            bid_amount,
+            is_skipped: false,
+        }
+    }
+
+    #[test]
+    fn second_price_pays_runner_up_or_reserve() {
+        let rule_set = create_rule_set(AuctionMode::SecondPrice);
+        let bids = [
+            create_bid(0, 0, 8),
+            create_bid(1, 0, 5),
+            create_bid(2, 1, 4),
+        ];
+        let progress = AuctionProgress::from_history(&[0, 1, 2], &bids, &rule_set);
+        assert_eq!(progress.round, 1);
+        assert_eq!(
+            progress.winning_bids,
+            vec![
+                WinningBid {
+                    player_index: 0,
+                    bid_item_index: 0,
+                    bid_amount: 8,
+                    price: 5,
+                },
+                // 競合がいない場合は最低落札価格を支払う
+                WinningBid {
+                    player_index: 2,
+                    bid_item_index: 1,
+                    bid_amount: 4,
+                    price: 2,
+                },
+            ]
+        );
+        // 負けたプレイヤーは次のラウンドで入札し直す
+        assert_eq!(progress.bidder_player_indexes, vec![1]);
+        assert_eq!(progress.get_next_bidder(), Some(1));
+        assert!(!progress.is_complete());
+    }
+
+    #[test]
+    fn sealed_tie_goes_to_earlier_initiative() {
+        let rule_set = create_rule_set(AuctionMode::SealedFirstPrice);
+        let bids = [create_bid(1, 0, 5), create_bid(0, 0, 5)];
+        let progress = AuctionProgress::from_history(&[0, 1], &bids, &rule_set);
+        // 履歴に現れた順がこのフェーズの行動順になる
+        assert_eq!(
+            progress.winning_bids,
+            vec![WinningBid {
+                player_index: 1,
+                bid_item_index: 0,
+                bid_amount: 5,
+                price: 5,
+            }]
+        );
+        assert_eq!(progress.bidder_player_indexes, vec![0]);
+
+        let bids = [bids[0].clone(), bids[1].clone(), create_bid(0, 1, 3)];
+        let progress = AuctionProgress::from_history(&[0, 1], &bids, &rule_set);
+        assert_eq!(progress.round, 2);
+        assert!(progress.is_won(1));
+        assert!(progress.is_complete());
+    }
+
+    #[test]
+    fn passed_player_leaves_auction() {
+        let rule_set = create_rule_set(AuctionMode::SealedFirstPrice);
+        let mut pass = create_bid(1, 0, 0);
+        pass.is_skipped = true;
+        let bids = [create_bid(0, 0, 3), pass];
+        let progress = AuctionProgress::from_history(&[0, 1], &bids, &rule_set);
+        assert_eq!(progress.winning_bids.len(), 1);
+        assert!(progress.is_complete());
+    }
+
+    #[test]
+    fn dutch_price_steps_down_to_reserve() {
+        let rule_set = create_rule_set(AuctionMode::Dutch);
+        let mut progress = AuctionProgress::from_history(&[0, 1], &[], &rule_set);
+        let prices = (0..5)
+            .map(|round| {
+                progress.round = round;
+                progress.get_dutch_price(&rule_set)
+            })
+            .collect::<Vec<u32>>();
+        assert_eq!(prices, vec![10, 7, 4, 2, 2]);
+        progress.round = 2;
+        assert!(progress.can_wait(&rule_set));
+        progress.round = 3;
+        assert!(!progress.can_wait(&rule_set));
+    }
+
+    #[test]
+    fn dutch_waiting_player_bids_again_at_lower_price() {
+        let rule_set = create_rule_set(AuctionMode::Dutch);
+        let bids = [create_bid(0, 0, 0), create_bid(1, 1, 10)];
+        let progress = AuctionProgress::from_history(&[0, 1], &bids, &rule_set);
+        assert_eq!(progress.round, 1);
+        assert_eq!(progress.get_dutch_price(&rule_set), 7);
+        assert_eq!(progress.bidder_player_indexes, vec![0]);
+        assert!(progress.is_won(1));
+        assert!(!progress.is_won(0));
+
+        let bids = [bids[0].clone(), bids[1].clone(), create_bid(0, 0, 7)];
+        let progress = AuctionProgress::from_history(&[0, 1], &bids, &rule_set);
+        assert!(progress.is_complete());
+        assert_eq!(progress.get_winning_bid(0).unwrap().price, 7);
+    }
+}
@@ -0,0 +1,1346 @@
+use crate::bop_core::cpu_player::profile::CPUProfile;
+use crate::bop_core::mechanism::deck_list::{
+    standard_deck_list_id, DeckError, DeckRules, STANDARD_DECK_LIST_ID,
+};
+use crate::bop_core::mechanism::item::{Item, ItemKind, ItemTarget};
+use crate::bop_core::mechanism::item_set::ItemSet;
+use crate::bop_core::mechanism::player_status::PlayerStatus;
+use crate::bop_core::mechanism::status_effect::StatusEffectKind;
+use crate::bop_core::random::SeededRng;
+use crate::bop_core::rules::rule_set::{AuctionMode, RuleSet};
+use crate::bop_core::rules::{Command, GameEvent, MoneySource};
+use crate::bop_core::state::auction::AuctionProgress;
+use crate::bop_core::state::message::{
+    AttackTargetMessage, BidMessage, GameRuleMessage, MatchError, UseItemMessage,
+};
+use crate::bop_core::state::phase::PhaseType::*;
+use crate::bop_core::state::phase::{CheckPhaseCompleteResult, Phase};
+use crate::bop_core::state::snapshot::state_hash;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BoPPlayer {
+    pub player_name: String,
+    pub game_start_is_approved: bool,
+    pub battle_is_viewed: bool,
+    pub own_item_list: Vec<Item>,
+    pub player_status: PlayerStatus,
+}
+
+impl BoPPlayer {
+    pub fn is_lose(&self) -> bool {
+        self.player_status.is_dead()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameLog {
+    pub turn: usize,
+    pub log_type: LogType,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum LogType {
+    Joined(usize),
+    // (プレイヤー, 落札したアイテム, 落札価格)
+    BidSuccessful(usize, ItemKind, u32),
+    // 封印入札で公開した (プレイヤー, 入札したアイテム, 入札額) の一覧
+    SealedBidsRevealed(Vec<(usize, ItemKind, u32)>),
+    // ダッチ方式で下がった後の価格
+    DutchPriceDropped(u32),
+    // 入札をパスしたプレイヤー
+    BidPassed(usize),
+    // 先攻になったプレイヤー
+    InitiativeChanged(usize),
+    UseItem(usize, ItemKind),
+    // (攻撃したプレイヤー, 攻撃されたプレイヤー, ダメージ)
+    AttackTarget(usize, usize, u32),
+    // (攻撃しなかったプレイヤー, 獲得したMoney)
+    AttackSkipped(usize, u32),
+    // 続けてアイテムを使用できるプレイヤー
+    ItemUseChained(usize),
+    // 続けて攻撃できるプレイヤー
+    AttackChained(usize),
+    // (効果を受けたプレイヤー, 効果の種類, 変化した量)
+    StatusEffectTriggered(usize, StatusEffectKind, u32),
+    // (効果が切れたプレイヤー, 効果の種類)
+    StatusEffectExpired(usize, StatusEffectKind),
+    // HPが0になり脱落したプレイヤー
+    Eliminated(usize),
+    // 勝利したプレイヤー
+    GameEnd(Vec<usize>),
+}
+
+impl GameLog {
+    // ログとして残すイベントのみ変換する
+    pub fn from_event(turn: usize, event: &GameEvent) -> Option<GameLog> {
+        let log_type = match event {
+            GameEvent::PlayerJoined { player_index } => LogType::Joined(*player_index),
+            GameEvent::ItemWon {
+                player_index,
+                item_kind,
+                bid_amount,
+            } => LogType::BidSuccessful(*player_index, item_kind.clone(), *bid_amount),
+            GameEvent::SealedBidsRevealed { bids } => LogType::SealedBidsRevealed(bids.clone()),
+            GameEvent::DutchPriceDropped { price } => LogType::DutchPriceDropped(*price),
+            GameEvent::BidPassed { player_index } => LogType::BidPassed(*player_index),
+            GameEvent::InitiativeChanged { first_player_index } => {
+                LogType::InitiativeChanged(*first_player_index)
+            }
+            GameEvent::ItemUsed {
+                player_index,
+                item_kind,
+            } => LogType::UseItem(*player_index, item_kind.clone()),
+            GameEvent::DamageDealt {
+                attacker_player_index,
+                target_player_index,
+                damage,
+                ..
+            } => LogType::AttackTarget(*attacker_player_index, *target_player_index, *damage),
+            GameEvent::MoneyGained {
+                player_index,
+                amount,
+                source: MoneySource::AttackSkipped,
+            } => LogType::AttackSkipped(*player_index, *amount),
+            GameEvent::ActionChained {
+                player_index,
+                phase_type: UseItem,
+            } => LogType::ItemUseChained(*player_index),
+            GameEvent::ActionChained {
+                player_index,
+                phase_type: AttackTarget,
+            } => LogType::AttackChained(*player_index),
+            GameEvent::StatusEffectTriggered {
+                player_index,
+                kind,
+                amount,
+            } => LogType::StatusEffectTriggered(*player_index, *kind, *amount),
+            GameEvent::StatusEffectExpired { player_index, kind } => {
+                LogType::StatusEffectExpired(*player_index, *kind)
+            }
+            GameEvent::PlayerEliminated { player_index } => LogType::Eliminated(*player_index),
+            GameEvent::GameEnded {
+                winner_player_indexes,
+            } => LogType::GameEnd(winner_player_indexes.clone()),
+            _ => return None,
+        };
+        Some(GameLog { turn, log_type })
+    }
+
+    // ログの表示用の文言
+    pub fn get_message(&self, bop_shared_state: &BoPSharedState) -> String {
+        let player_name =
+            |player_index: &usize| &bop_shared_state.players[*player_index].player_name;
+        match &self.log_type {
+            LogType::Joined(player_index) => {
+                format!("{}さんが参加しました", player_name(player_index))
+            }
+            LogType::BidSuccessful(player_index, item_kind, bid_amount) => format!(
+                "{}さんが{}を{}で落札しました",
+                player_name(player_index),
+                bop_shared_state.item_set.get_name(item_kind),
+                bid_amount
+            ),
+            LogType::SealedBidsRevealed(bids) => format!(
+                "入札を公開しました: {}",
+                bids.iter()
+                    .map(|(player_index, item_kind, bid_amount)| format!(
+                        "{}さん {} {}",
+                        player_name(player_index),
+                        bop_shared_state.item_set.get_name(item_kind),
+                        bid_amount
+                    ))
+                    .collect::<Vec<String>>()
+                    .join("、")
+            ),
+            LogType::DutchPriceDropped(price) => {
+                format!("価格が{}に下がりました", price)
+            }
+            LogType::BidPassed(player_index) => {
+                format!("{}さんは入札をパスしました", player_name(player_index))
+            }
+            LogType::InitiativeChanged(player_index) => {
+                format!("{}さんが先攻になりました", player_name(player_index))
+            }
+            LogType::UseItem(player_index, item_kind) => format!(
+                "{}さんが{}を使用しました",
+                player_name(player_index),
+                bop_shared_state.item_set.get_name(item_kind)
+            ),
+            LogType::AttackTarget(player_index, target_player_index, damage) => format!(
+                "{}さんが{}さんに{}のダメージを与えました",
+                player_name(player_index),
+                player_name(target_player_index),
+                damage
+            ),
+            LogType::AttackSkipped(player_index, amount) => format!(
+                "{}さんは攻撃せずに{} Moneyを得ました",
+                player_name(player_index),
+                amount
+            ),
+            LogType::ItemUseChained(player_index) => {
+                format!(
+                    "{}さんは続けてアイテムを使用できます",
+                    player_name(player_index)
+                )
+            }
+            LogType::AttackChained(player_index) => {
+                format!("{}さんは続けて攻撃できます", player_name(player_index))
+            }
+            LogType::StatusEffectTriggered(player_index, kind, amount) => match kind {
+                StatusEffectKind::Regen => format!(
+                    "{}さんはリジェネでHPが{}回復しました",
+                    player_name(player_index),
+                    amount
+                ),
+                StatusEffectKind::MoneyDrain => format!(
+                    "{}さんは浪費でMoneyを{}失いました",
+                    player_name(player_index),
+                    amount
+                ),
+                _ => format!(
+                    "{}さんは{}で{}のダメージを受けました",
+                    player_name(player_index),
+                    kind.get_name(),
+                    amount
+                ),
+            },
+            LogType::StatusEffectExpired(player_index, kind) => format!(
+                "{}さんの{}の効果が切れました",
+                player_name(player_index),
+                kind.get_name()
+            ),
+            LogType::Eliminated(player_index) => {
+                format!("{}さんが脱落しました", player_name(player_index))
+            }
+            LogType::GameEnd(winner_player_indexes) => format!(
+                "{}さんの勝利です",
+                winner_player_indexes
+                    .iter()
+                    .map(player_name)
+                    .cloned()
+                    .collect::<Vec<String>>()
+                    .join("さん、")
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BoPSharedState {
+    pub players: Vec<BoPPlayer>,
+    pub players_len: usize,
+    pub own_player_index: usize,
+    pub items_bid_on: Vec<Item>,
+    // 入札確定前の入力を管理する
+    pub bid_input: Vec<BidMessage>,
+    pub bid_scheduled_items: Vec<Item>,
+    // 山札などルール上の乱数はすべてこのシードから決まる
+    #[serde(default)]
+    pub seed: u64,
+    pub temporary_bid_history: Vec<BidMessage>,
+    pub bid_history: Vec<BidMessage>,
+    // カード使用確定前の入力を管理する
+    pub use_item_input: UseItemMessage,
+    pub use_item_history: Vec<UseItemMessage>,
+    pub attack_target_input: AttackTargetMessage,
+    pub attack_target_history: Vec<AttackTargetMessage>,
+    // 適用したすべての操作を順に記録する（リプレイ用）
+    #[serde(default)]
+    pub command_history: Vec<Command>,
+    // オンライン対戦の場合、マッチング時に決めたルール
+    #[serde(default)]
+    pub game_rule_message: Option<GameRuleMessage>,
+    // このVectorだけ少し特殊で、プレイヤーのインデックス自体が追加される
+    // 前に出現したプレイヤーほど優先される
+    // 脱落したプレイヤーは取り除かれるので、生存しているプレイヤーの一覧も兼ねる
+    pub initiatives_to_player_index: Vec<usize>,
+    pub game_logs: Vec<GameLog>,
+    pub turn: usize,
+    // enum PhaseType と同じ順序でセットされていないとエラーになる
+    // TODO
+    // 初期化時にチェックを追加
+    pub phase_index: usize,
+    pub phases: Vec<Phase>,
+    pub input_is_guard: bool,
+    pub consumed_seq_no: usize,
+    // consumed_seq_no + 1 より先のシーケンス番号で届いたメッセージ
+    pub buffered_commands: Vec<Command>,
+    // 0 の場合はハッシュによる同期確認を行わない
+    pub state_hash_interval: usize,
+    // (シーケンス番号, ハッシュ) の組を直近の分だけ保持する
+    pub state_hashes: Vec<(usize, u64)>,
+    pub peer_state_hashes: Vec<(usize, u64)>,
+    // 同期ずれ時に正とするプレイヤー
+    pub host_player_index: usize,
+    pub has_cpu: bool,
+    // 対戦で使うアイテムの定義。以前のセーブデータには存在しないので同梱のものを使う
+    #[serde(default = "ItemSet::default_set")]
+    pub item_set: Arc<ItemSet>,
+    // 開始時のステータスや入札のルール。以前のセーブデータでは標準のルール
+    #[serde(default)]
+    pub rule_set: RuleSet,
+    // item_set の中から選んだ山札
+    #[serde(default = "standard_deck_list_id")]
+    pub deck_list_id: String,
+    // CPUの強さと性格。ゲームの進行には関わらないので、ハッシュや同期の対象外
+    #[serde(default)]
+    pub cpu_profile: CPUProfile,
+}
+
+impl BoPSharedState {
+    pub fn init() -> BoPSharedState {
+        BoPSharedState::init_with_seed(SeededRng::random_seed())
+    }
+
+    pub fn init_with_seed(seed: u64) -> BoPSharedState {
+        BoPSharedState::init_for_players(seed, 2)
+    }
+
+    // 3〜4人のバトルロイヤルにも対応する
+    pub fn init_for_players(seed: u64, players_len: usize) -> BoPSharedState {
+        let item_set = ItemSet::default_set();
+        let rule_set = RuleSet::default();
+        BoPSharedState {
+            players: (0..players_len)
+                .map(|player_index| BoPPlayer {
+                    player_name: format!("プレイヤー{}", player_index + 1),
+                    game_start_is_approved: false,
+                    battle_is_viewed: false,
+                    own_item_list: vec![],
+                    player_status: rule_set.create_player_status(),
+                })
+                .collect(),
+            players_len,
+            own_player_index: 0,
+            items_bid_on: vec![],
+            bid_input: (0..rule_set.get_items_on_offer_len(players_len))
+                .map(BidMessage::init)
+                .collect(),
+            bid_scheduled_items: item_set.create_deck(STANDARD_DECK_LIST_ID, seed),
+            seed,
+            temporary_bid_history: vec![],
+            bid_history: vec![],
+            use_item_input: UseItemMessage::empty(),
+            use_item_history: vec![],
+            attack_target_input: AttackTargetMessage::empty(),
+            attack_target_history: vec![],
+            command_history: vec![],
+            game_rule_message: None,
+            initiatives_to_player_index: (0..players_len).collect(),
+            game_logs: vec![],
+            turn: 0,
+            phase_index: 0,
+            phases: Phase::get_phases(),
+            input_is_guard: false,
+            consumed_seq_no: 0,
+            buffered_commands: vec![],
+            state_hash_interval: 0,
+            state_hashes: vec![],
+            peer_state_hashes: vec![],
+            host_player_index: 0,
+            has_cpu: false,
+            item_set,
+            rule_set,
+            deck_list_id: standard_deck_list_id(),
+            cpu_profile: CPUProfile::default(),
+        }
+    }
+
+    pub fn get_seq_no_to_send(&self) -> usize {
+        self.consumed_seq_no + 1
+    }
+
+    pub fn is_valid_new_message(&self, message_seq_no: usize) -> bool {
+        message_seq_no == self.consumed_seq_no + 1
+    }
+    pub fn update_consumed_seq_no(&mut self, message_seq_no: usize) {
+        self.consumed_seq_no = message_seq_no;
+    }
+
+    // 順序が前後して届いたメッセージがあり、間のメッセージを待っている状態
+    pub fn is_desynced(&self) -> bool {
+        !self.buffered_commands.is_empty()
+    }
+
+    // 先のシーケンス番号のメッセージを、間が埋まるまで保持しておく
+    pub fn buffer_command(&mut self, command: Command) -> Vec<GameEvent> {
+        let seq_no = command.seq_no().unwrap();
+        if self
+            .buffered_commands
+            .iter()
+            .any(|buffered| buffered.seq_no() == Some(seq_no))
+        {
+            return vec![];
+        }
+        let was_desynced = self.is_desynced();
+        self.buffered_commands.push(command);
+        self.buffered_commands
+            .sort_by_key(|buffered| buffered.seq_no());
+        if was_desynced {
+            return vec![];
+        }
+        vec![GameEvent::Desynced {
+            expected_seq_no: self.consumed_seq_no + 1,
+            received_seq_no: seq_no,
+        }]
+    }
+
+    // 同期確認を行うシーケンス番号に達していれば、ハッシュを記録する
+    pub fn record_state_hash(&mut self) -> Vec<GameEvent> {
+        if self.state_hash_interval == 0
+            || !self
+                .consumed_seq_no
+                .is_multiple_of(self.state_hash_interval)
+        {
+            return vec![];
+        }
+        let seq_no = self.consumed_seq_no;
+        let state_hash = state_hash(self);
+        BoPSharedState::push_state_hash(&mut self.state_hashes, seq_no, state_hash);
+        let mut events = vec![GameEvent::StateHashed { seq_no, state_hash }];
+        events.extend(self.compare_state_hash(seq_no));
+        events
+    }
+
+    pub fn receive_peer_state_hash(&mut self, seq_no: usize, state_hash: u64) -> Vec<GameEvent> {
+        BoPSharedState::push_state_hash(&mut self.peer_state_hashes, seq_no, state_hash);
+        self.compare_state_hash(seq_no)
+    }
+
+    fn push_state_hash(state_hashes: &mut Vec<(usize, u64)>, seq_no: usize, state_hash: u64) {
+        state_hashes.retain(|(recorded_seq_no, _)| *recorded_seq_no != seq_no);
+        state_hashes.push((seq_no, state_hash));
+        if state_hashes.len() > 10 {
+            state_hashes.remove(0);
+        }
+    }
+
+    // 自分と相手の両方のハッシュが揃っていれば比較する
+    fn compare_state_hash(&self, seq_no: usize) -> Vec<GameEvent> {
+        let find = |state_hashes: &Vec<(usize, u64)>| {
+            state_hashes
+                .iter()
+                .find(|(recorded_seq_no, _)| *recorded_seq_no == seq_no)
+                .map(|(_, state_hash)| *state_hash)
+        };
+        match (find(&self.state_hashes), find(&self.peer_state_hashes)) {
+            (Some(own_state_hash), Some(peer_state_hash)) if own_state_hash != peer_state_hash => {
+                vec![GameEvent::StateHashMismatched { seq_no }]
+            }
+            _ => vec![],
+        }
+    }
+
+    // 誰の入力待ちかを判定する
+    // フェーズの移行は rules::apply の中で advance_phase によって行われるので、ここでは state を進めない
+    pub fn check_phase_complete(&mut self, is_matched: bool) -> CheckPhaseCompleteResult {
+        if self.phase_index == GameEnd as i32 as usize {
+            return CheckPhaseCompleteResult {
+                is_phase_complete: true,
+                next_phase_index: Some(GameEnd as i32 as usize),
+                is_required_own_input_for_complete: Some(false),
+            };
+        }
+        let mut check_result = CheckPhaseCompleteResult::empty();
+        for player_index in 0..self.players.len() {
+            if self.has_cpu && self.own_player_index != player_index {
+                continue;
+            }
+            if is_matched && self.own_player_index != player_index {
+                continue;
+            }
+            self.own_player_index = player_index;
+            let check_func = self.phases[self.phase_index].check_phase_complete_func;
+            check_result = check_func(self);
+            if check_result
+                .is_required_own_input_for_complete
+                .unwrap_or(false)
+            {
+                break;
+            }
+        }
+        check_result
+    }
+
+    // 指定したプレイヤーが次の入力者であるか
+    pub fn is_required_input_of(&mut self, player_index: usize) -> bool {
+        let own_player_index = self.own_player_index;
+        self.own_player_index = player_index;
+        let check_func = self.phases[self.phase_index].check_phase_complete_func;
+        let check_result = check_func(self);
+        self.own_player_index = own_player_index;
+        !check_result.is_phase_complete
+            && check_result
+                .is_required_own_input_for_complete
+                .unwrap_or(false)
+    }
+
+    // 完了しているフェーズがなくなるまで次のフェーズへ移行する
+    pub fn advance_phase(&mut self) -> Vec<GameEvent> {
+        let mut events = self.eliminate_dead_players();
+        while self.phase_index != GameEnd as i32 as usize {
+            let check_func = self.phases[self.phase_index].check_phase_complete_func;
+            let check_result = check_func(self);
+            if !check_result.is_phase_complete {
+                break;
+            }
+            events.extend(self.phase_shift_to(check_result.next_phase_index.unwrap()));
+        }
+        events
+    }
+
+    pub fn phase_shift_to(&mut self, next_phase_index: usize) -> Vec<GameEvent> {
+        let mut events = vec![];
+        let now_phase_index = self.phase_index;
+        let mut next_phase_index = next_phase_index;
+        if now_phase_index == Bid as i32 as usize {
+            events.extend(self.update_initiatives_by_bid_amount());
+        }
+        // ターンの終わりに一定のターン続く効果を発動する
+        // 効果で脱落したプレイヤーは次のフェーズの前に取り除き、決着した場合は終了する
+        if now_phase_index == AttackTarget as i32 as usize
+            && next_phase_index != GameEnd as i32 as usize
+        {
+            events.extend(self.tick_status_effects());
+            events.extend(self.eliminate_dead_players());
+            if self.game_is_end() {
+                next_phase_index = GameEnd as i32 as usize;
+            }
+        }
+        match self.phases[next_phase_index].phase_type {
+            Bid => match now_phase_index {
+                0 => {
+                    self.ready_to_bid();
+                }
+                1 => {
+                    events.extend(self.move_to_own_item_list());
+                    self.ready_to_bid();
+                    self.ready_bid_input();
+                    self.turn += 1;
+                }
+                3 => {
+                    // TODO
+                    // 根本的な解決になっていないが空になっていないことがあるので…
+                    self.temporary_bid_history.clear();
+                    self.turn += 1;
+                }
+                _ => {}
+            },
+            UseItem => match now_phase_index {
+                1 => {
+                    events.extend(self.move_to_own_item_list());
+                    self.ready_to_bid();
+                    self.ready_bid_input();
+                }
+                3 => {
+                    self.turn += 1;
+                }
+                _ => {}
+            },
+            AttackTarget if now_phase_index == 3 => {
+                self.turn += 1;
+            }
+            _ => {}
+        }
+        // 追加の行動回数はそのターン限り
+        if now_phase_index == AttackTarget as i32 as usize {
+            for player in self.players.iter_mut() {
+                player.player_status.extra_item_use_count = 0;
+                player.player_status.extra_attack_count = 0;
+            }
+        }
+        self.phase_index = self.phases[next_phase_index].phase_type.to_owned() as i32 as usize;
+        events.push(GameEvent::PhaseChanged {
+            from: self.phases[now_phase_index].phase_type.clone(),
+            to: self.phases[next_phase_index].phase_type.clone(),
+            turn: self.turn,
+        });
+        if self.phase_index == GameEnd as i32 as usize {
+            events.push(GameEvent::GameEnded {
+                winner_player_indexes: (0..self.players.len())
+                    .filter(|player_index| !self.players[*player_index].is_lose())
+                    .collect(),
+            });
+        }
+        events
+    }
+
+    // 入札が完了した時点で、最後の入札金額が高い順に行動順を並べ替える
+    // 金額が同じ場合は、それまでの行動順が後のプレイヤーを優先する
+    fn update_initiatives_by_bid_amount(&mut self) -> Vec<GameEvent> {
+        let mut initiatives_to_player_index = self.initiatives_to_player_index.clone();
+        initiatives_to_player_index.reverse();
+        initiatives_to_player_index.sort_by_key(|player_index| {
+            std::cmp::Reverse(BidMessage::last_bid_amount_of_player(
+                *player_index,
+                &self.temporary_bid_history,
+            ))
+        });
+        if initiatives_to_player_index == self.initiatives_to_player_index {
+            return vec![];
+        }
+        self.initiatives_to_player_index = initiatives_to_player_index;
+        vec![GameEvent::InitiativeChanged {
+            first_player_index: self.initiatives_to_player_index[0],
+        }]
+    }
+
+    // 生存しているプレイヤーの効果を行動順に発動し、残りターンを減らす
+    fn tick_status_effects(&mut self) -> Vec<GameEvent> {
+        let mut events = vec![];
+        for player_index in self.initiatives_to_player_index.clone() {
+            let player_status = &mut self.players[player_index].player_status;
+            for (kind, amount) in player_status.trigger_turn_end_effects() {
+                events.push(GameEvent::StatusEffectTriggered {
+                    player_index,
+                    kind,
+                    amount,
+                });
+            }
+            for kind in player_status.expire_status_effects() {
+                events.push(GameEvent::StatusEffectExpired { player_index, kind });
+            }
+        }
+        events
+    }
+
+    // HPが0になったプレイヤーを行動順から取り除く
+    fn eliminate_dead_players(&mut self) -> Vec<GameEvent> {
+        let eliminated_player_indexes = self
+            .initiatives_to_player_index
+            .iter()
+            .filter(|player_index| self.players[**player_index].is_lose())
+            .copied()
+            .collect::<Vec<usize>>();
+        self.initiatives_to_player_index
+            .retain(|player_index| !eliminated_player_indexes.contains(player_index));
+        eliminated_player_indexes
+            .into_iter()
+            .map(|player_index| GameEvent::PlayerEliminated { player_index })
+            .collect()
+    }
+
+    pub fn is_active_player(&self, player_index: usize) -> bool {
+        self.initiatives_to_player_index.contains(&player_index)
+    }
+
+    // 2人対戦では先攻・後攻、3人以上では何番目に行動するかを表す
+    pub fn get_initiative_label(&self, player_index: usize) -> String {
+        let Some(order) = self
+            .initiatives_to_player_index
+            .iter()
+            .position(|initiative_player_index| *initiative_player_index == player_index)
+        else {
+            return "脱落".to_string();
+        };
+        if self.players.len() == 2 {
+            if order == 0 { "先攻" } else { "後攻" }.to_string()
+        } else {
+            format!("{}番手", order + 1)
+        }
+    }
+
+    // イベントを発生した順にログに残す
+    // フェーズの移行でターンが進むので、イベントが起きた時点のターンを追いかけながら記録する
+    pub fn push_game_logs(&mut self, turn: usize, events: &[GameEvent]) {
+        let mut turn = turn;
+        for event in events.iter() {
+            if let Some(game_log) = GameLog::from_event(turn, event) {
+                self.game_logs.push(game_log);
+            }
+            if let GameEvent::PhaseChanged {
+                turn: next_turn, ..
+            } = event
+            {
+                turn = *next_turn;
+            }
+        }
+    }
+
+    // 対戦相手と共有したシードで山札を作り直す
+    pub fn update_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.bid_scheduled_items = self.item_set.create_deck(&self.deck_list_id, seed);
+    }
+
+    // アイテムセットを差し替え、同じシードで山札を作り直す
+    // 選んでいた山札が新しいアイテムセットにない場合は標準の山札に戻す
+    // ゲームの開始前にのみ呼ぶ
+    pub fn update_item_set(&mut self, item_set: Arc<ItemSet>) {
+        self.item_set = item_set;
+        if self.item_set.get_deck_list(&self.deck_list_id).is_none() {
+            self.deck_list_id = standard_deck_list_id();
+        }
+        self.bid_scheduled_items = self.item_set.create_deck(&self.deck_list_id, self.seed);
+    }
+
+    // オンライン対戦では両者で同じ定義になるよう、同梱のアイテムセットに戻す
+    // 読み込んだアイテムセットを使っていた場合は true を返す（山札も同梱のものに戻る）
+    // マッチングを始める前に呼ぶ
+    pub fn reset_item_set_for_online(&mut self) -> bool {
+        let default_set = ItemSet::default_set();
+        if self.item_set == default_set {
+            return false;
+        }
+        self.update_item_set(default_set);
+        true
+    }
+
+    // アイテムセットの中から山札を選び、同じシードで山札を作り直す
+    // ゲームの開始前にのみ呼ぶ
+    pub fn select_deck_list(&mut self, deck_list_id: &str) -> Result<(), DeckError> {
+        let deck_list = self
+            .item_set
+            .get_deck_list(deck_list_id)
+            .ok_or_else(|| DeckError::UnknownDeckList(deck_list_id.to_string()))?;
+        deck_list.validate(&self.item_set, &DeckRules::default())?;
+        self.bid_scheduled_items = deck_list.create_deck(self.seed);
+        self.deck_list_id = deck_list.id;
+        Ok(())
+    }
+
+    pub fn get_deck_list_name(&self) -> String {
+        self.item_set
+            .get_deck_list(&self.deck_list_id)
+            .map(|deck_list| deck_list.get_name())
+            .unwrap_or_else(|| self.deck_list_id.clone())
+    }
+
+    // 並び順の決まった山札に差し替える
+    // 定義されていないアイテムを含む場合は差し替えずにエラーを返す
+    pub fn update_item_list(&mut self, item_kind_list: Vec<ItemKind>) -> Result<(), DeckError> {
+        if let Some(item_kind) = item_kind_list
+            .iter()
+            .find(|item_kind| self.item_set.get(item_kind).is_none())
+        {
+            return Err(DeckError::UndefinedItem(item_kind.clone()));
+        }
+        let new_items = item_kind_list
+            .into_iter()
+            .map(Item::from)
+            .collect::<Vec<Item>>();
+        self.bid_scheduled_items = new_items;
+        Ok(())
+    }
+
+    // マッチングで受け取ったホストのルールと山札に合わせる
+    // オンライン対戦では両者で同じ定義になるよう、同梱のアイテムセットを使う
+    // ルールが不正な場合や山札を同じにできない場合は state を変更せずにエラーを返す
+    // ゲームの開始前にのみ呼ぶ
+    pub fn apply_game_rule_message(&mut self, message: &GameRuleMessage) -> Result<(), MatchError> {
+        message.rule_set.validate()?;
+        let mut bop_shared_state = self.clone();
+        bop_shared_state.item_set = ItemSet::default_set();
+        bop_shared_state.seed = message.seed;
+        bop_shared_state.select_deck_list(&message.deck_list_id)?;
+        bop_shared_state.update_rule_set(message.rule_set.clone());
+        if let Some(item_kind_list) = &message.item_kind_list {
+            bop_shared_state.update_item_list(item_kind_list.clone())?;
+        }
+        bop_shared_state.game_rule_message = Some(message.clone());
+        bop_shared_state.host_player_index = message.host_player_index;
+        *self = bop_shared_state;
+        Ok(())
+    }
+
+    // ルールを差し替え、開始時のステータスと入札対象の数を合わせる
+    // ゲームの開始前にのみ呼ぶ
+    pub fn update_rule_set(&mut self, rule_set: RuleSet) {
+        for player in self.players.iter_mut() {
+            player.player_status = rule_set.create_player_status();
+        }
+        self.bid_input = (0..rule_set.get_items_on_offer_len(self.players.len()))
+            .map(BidMessage::init)
+            .collect();
+        self.rule_set = rule_set;
+        self.ready_bid_input();
+    }
+
+    // 生存しているプレイヤーが1人以下になったら終了
+    pub fn game_is_end(&self) -> bool {
+        self.players
+            .iter()
+            .filter(|player| !player.is_lose())
+            .count()
+            <= 1
+    }
+
+    // 席順で次の、生存しているプレイヤー
+    pub fn opponent_player_index(&self, player_index: usize) -> usize {
+        let players_len = self.players.len();
+        (1..players_len)
+            .map(|offset| (player_index + offset) % players_len)
+            .find(|opponent_player_index| !self.players[*opponent_player_index].is_lose())
+            .unwrap_or((player_index + 1) % players_len)
+    }
+
+    // 追加のアイテム使用回数が残っていれば1つ消費し、続けてアイテムを使えるようにする
+    // 使えるアイテムが残っていない場合は消費しない
+    pub fn consume_extra_item_use(&mut self, player_index: usize) -> bool {
+        let player = &mut self.players[player_index];
+        if player.player_status.extra_item_use_count == 0
+            || player.own_item_list.is_empty()
+            || player.is_lose()
+        {
+            return false;
+        }
+        player.player_status.extra_item_use_count -= 1;
+        true
+    }
+
+    // 追加の攻撃回数が残っていれば1つ消費し、続けて攻撃できるようにする
+    // 攻撃できる相手が残っていない場合は消費しない
+    pub fn consume_extra_attack(&mut self, player_index: usize) -> bool {
+        // トゲで自分が脱落した場合も続けて攻撃できない
+        if self.players[player_index].player_status.extra_attack_count == 0
+            || self.players[player_index].is_lose()
+            || self.game_is_end()
+        {
+            return false;
+        }
+        self.players[player_index].player_status.extra_attack_count -= 1;
+        true
+    }
+
+    // このターンの直前の行動が同じプレイヤーによる連続行動の途中であるか
+    pub fn is_chaining(&self, player_index: usize) -> bool {
+        let last_history = match self.phases[self.phase_index].phase_type {
+            UseItem => self
+                .use_item_history
+                .last()
+                .filter(|history| history.turn == self.turn)
+                .map(|history| (history.player_index, history.check_is_blocked)),
+            AttackTarget => self
+                .attack_target_history
+                .last()
+                .filter(|history| history.turn == self.turn)
+                .map(|history| (history.player_index, history.check_is_blocked)),
+            _ => None,
+        };
+        last_history == Some((player_index, true))
+    }
+
+    // 生存している自分以外のプレイヤー。攻撃対象として選べる
+    pub fn get_opponent_player_indexes(&self, player_index: usize) -> Vec<usize> {
+        let players_len = self.players.len();
+        (1..players_len)
+            .map(|offset| (player_index + offset) % players_len)
+            .filter(|opponent_player_index| self.is_active_player(*opponent_player_index))
+            .collect()
+    }
+
+    // アイテムの対象として選べるプレイヤー。選ぶ必要がないアイテムでは空
+    pub fn get_item_target_candidates(
+        &self,
+        use_player_index: usize,
+        item_target: &ItemTarget,
+    ) -> Vec<usize> {
+        match item_target {
+            ItemTarget::Opponent => self.get_opponent_player_indexes(use_player_index),
+            ItemTarget::Choice => self.initiatives_to_player_index.clone(),
+            ItemTarget::Own | ItemTarget::AllOpponents => vec![],
+        }
+    }
+
+    // 選ばれたプレイヤーから、実際に効果を受けるプレイヤーを決める
+    // 選べないプレイヤーが指定された場合は None
+    pub fn resolve_item_target_player_indexes(
+        &self,
+        use_player_index: usize,
+        item_target: &ItemTarget,
+        chosen_player_index: Option<usize>,
+    ) -> Option<Vec<usize>> {
+        match (item_target, chosen_player_index) {
+            (ItemTarget::Own, _) => Some(vec![use_player_index]),
+            (ItemTarget::AllOpponents, _) => {
+                Some(self.get_opponent_player_indexes(use_player_index))
+            }
+            // 対象を指定しないクライアントや、以前のリプレイとの互換のため
+            (ItemTarget::Opponent, None) => {
+                Some(vec![self.opponent_player_index(use_player_index)])
+            }
+            (ItemTarget::Choice, None) => Some(vec![use_player_index]),
+            (ItemTarget::Opponent | ItemTarget::Choice, Some(chosen_player_index)) => self
+                .get_item_target_candidates(use_player_index, item_target)
+                .contains(&chosen_player_index)
+                .then(|| vec![chosen_player_index]),
+        }
+    }
+
+    // 競り上げ方式以外の入札の進み具合
+    pub fn get_auction_progress(&self) -> AuctionProgress {
+        AuctionProgress::from_history(
+            &self.initiatives_to_player_index,
+            &self.temporary_bid_history,
+            &self.rule_set,
+        )
+    }
+
+    // 入札の方式ごとの最低入札価格
+    // ダッチ方式では現在の価格でのみ落札できる
+    pub fn lowest_bid_amount(&self, item_index: usize) -> u32 {
+        match self.rule_set.auction_mode {
+            AuctionMode::Open => BidMessage::lowest_bid_amount(
+                item_index,
+                &self.temporary_bid_history,
+                &self.rule_set,
+            ),
+            AuctionMode::SealedFirstPrice | AuctionMode::SecondPrice => self.rule_set.reserve_price,
+            AuctionMode::Dutch => self.get_auction_progress().get_dutch_price(&self.rule_set),
+        }
+    }
+
+    // 入札の入力欄を最低入札価格にする
+    pub fn ready_bid_input(&mut self) {
+        for input_index in 0..self.bid_input.len() {
+            self.bid_input[input_index] = BidMessage::init(input_index);
+            self.bid_input[input_index].bid_amount = self.lowest_bid_amount(input_index);
+        }
+    }
+
+    // まだ落札されておらず、最低入札価格を支払えるアイテム
+    pub fn get_biddable_item_indexes(&self, player_index: usize) -> Vec<usize> {
+        let progress =
+            (self.rule_set.auction_mode != AuctionMode::Open).then(|| self.get_auction_progress());
+        let current_money_amount = self.players[player_index]
+            .player_status
+            .current_money_amount;
+        (0..self.items_bid_on.len())
+            .filter(|item_index| {
+                !progress
+                    .as_ref()
+                    .is_some_and(|progress| progress.is_won(*item_index))
+                    && self.lowest_bid_amount(*item_index) <= current_money_amount
+            })
+            .collect()
+    }
+
+    // ダッチ方式で、今の価格で落札せずに見送ることができる
+    pub fn can_wait_for_lower_price(&self) -> bool {
+        self.rule_set.auction_mode == AuctionMode::Dutch
+            && self.get_auction_progress().can_wait(&self.rule_set)
+    }
+
+    pub fn ready_to_bid(&mut self) {
+        let items_bid_on_len = self
+            .rule_set
+            .get_items_on_offer_len(self.initiatives_to_player_index.len());
+        while self.items_bid_on.len() < items_bid_on_len && !self.bid_scheduled_items.is_empty() {
+            let item = self.bid_scheduled_items.remove(0);
+            self.items_bid_on.push(item);
+        }
+    }
+
+    pub fn move_to_own_item_list(&mut self) -> Vec<GameEvent> {
+        let mut events = self.pay_gain_to_passed_players();
+        if self.rule_set.auction_mode != AuctionMode::Open {
+            events.extend(self.move_won_items_to_own_item_list());
+            return events;
+        }
+        // 入札中リストの後ろから対象の履歴を探す
+        // 途中で items_bid_on に対して remove するのでインデックスがズレないように
+        let bid_on_len = self.items_bid_on.len();
+        for bid_on_index_reverse in 0..bid_on_len {
+            if let Some((history_index, history)) = self
+                .temporary_bid_history
+                .iter_mut()
+                .enumerate()
+                .filter(|(_, history)| {
+                    !history.is_skipped
+                        && bid_on_len - bid_on_index_reverse - 1 == history.bid_item_index
+                })
+                .last()
+            {
+                let player_index = history.player_index;
+                let history = self.temporary_bid_history.remove(history_index);
+                self.players[player_index]
+                    .player_status
+                    .current_money_amount = self.players[player_index]
+                    .player_status
+                    .current_money_amount
+                    .saturating_sub(history.bid_amount)
+                    + self.players[player_index]
+                        .player_status
+                        .estimated_money_amount;
+                events.push(GameEvent::MoneyGained {
+                    player_index,
+                    amount: self.players[player_index]
+                        .player_status
+                        .estimated_money_amount,
+                    source: MoneySource::Gain,
+                });
+                let item = self
+                    .items_bid_on
+                    .remove(bid_on_len - bid_on_index_reverse - 1);
+                events.push(GameEvent::ItemWon {
+                    player_index,
+                    item_kind: item.item_kind.clone(),
+                    bid_amount: history.bid_amount,
+                });
+                self.bid_history.push(history);
+                self.players[player_index].own_item_list.push(item);
+            } else {
+                // items_bid_on の中には落札されていないアイテムも当然存在する
+            };
+        }
+        self.temporary_bid_history.clear();
+        events
+    }
+
+    // パスしたプレイヤーは何も落札しないが、獲得Moneyは得られる
+    fn pay_gain_to_passed_players(&mut self) -> Vec<GameEvent> {
+        let mut events = vec![];
+        for player_index in self.initiatives_to_player_index.clone() {
+            if !BidMessage::is_passed(player_index, &self.temporary_bid_history) {
+                continue;
+            }
+            let player_status = &mut self.players[player_index].player_status;
+            player_status.current_money_amount += player_status.estimated_money_amount;
+            events.push(GameEvent::MoneyGained {
+                player_index,
+                amount: player_status.estimated_money_amount,
+                source: MoneySource::Gain,
+            });
+        }
+        events
+    }
+
+    // 競り上げ方式以外では、落札が決まった入札ごとに支払う金額が決まっている
+    fn move_won_items_to_own_item_list(&mut self) -> Vec<GameEvent> {
+        let mut events = vec![];
+        let mut winning_bids = self.get_auction_progress().winning_bids;
+        // 途中で items_bid_on に対して remove するので、後ろのアイテムから処理する
+        winning_bids.sort_by_key(|winning_bid| std::cmp::Reverse(winning_bid.bid_item_index));
+        for winning_bid in winning_bids {
+            let player_index = winning_bid.player_index;
+            let player_status = &mut self.players[player_index].player_status;
+            // 落札額は入札額以下だが、決済の方式によらず Money が負にならないようにする
+            player_status.current_money_amount = player_status
+                .current_money_amount
+                .saturating_sub(winning_bid.price)
+                + player_status.estimated_money_amount;
+            events.push(GameEvent::MoneyGained {
+                player_index,
+                amount: player_status.estimated_money_amount,
+                source: MoneySource::Gain,
+            });
+            let item = self.items_bid_on.remove(winning_bid.bid_item_index);
+            events.push(GameEvent::ItemWon {
+                player_index,
+                item_kind: item.item_kind.clone(),
+                bid_amount: winning_bid.price,
+            });
+            // 再送信に使うので、落札した入札のメッセージをそのまま残す
+            if let Some(history) = self
+                .temporary_bid_history
+                .iter()
+                .rev()
+                .find(|history| history.player_index == player_index)
+            {
+                self.bid_history.push(history.clone());
+            }
+            self.players[player_index].own_item_list.push(item);
+        }
+        self.temporary_bid_history.clear();
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bop_core::mechanism::status_effect::StatusEffect;
+    use crate::bop_core::rules::tests::start_game;
+
+    #[test]
+    fn game_log_from_event_keeps_only_logged_events() {
+        let game_log =
+            GameLog::from_event(2, &GameEvent::PlayerEliminated { player_index: 1 }).unwrap();
+        assert_eq!(game_log.turn, 2);
+        assert!(matches!(game_log.log_type, LogType::Eliminated(1)));
+        let game_log = GameLog::from_event(
+            0,
+            &GameEvent::MoneyGained {
+                player_index: 0,
+                amount: 3,
+                source: MoneySource::AttackSkipped,
+            },
+        );
+        assert!(matches!(
+            game_log.map(|game_log| game_log.log_type),
+            Some(LogType::AttackSkipped(0, 3))
+        ));
+        let ignored_events = [
+            GameEvent::BidPlaced {
+                player_index: 0,
+                bid_item_index: 0,
+                bid_amount: 1,
+            },
+            GameEvent::MoneyGained {
+                player_index: 0,
+                amount: 3,
+                source: MoneySource::Gain,
+            },
+            GameEvent::Resynced,
+        ];
+        for event in ignored_events.iter() {
+            assert!(GameLog::from_event(0, event).is_none());
+        }
+    }
+
+    #[test]
+    fn push_game_logs_follows_turn_of_phase_change() {
+        let mut state = BoPSharedState::init_for_players(1, 2);
+        let events = [
+            GameEvent::BidPassed { player_index: 0 },
+            GameEvent::PhaseChanged {
+                from: Bid,
+                to: UseItem,
+                turn: 3,
+            },
+            GameEvent::BidPlaced {
+                player_index: 1,
+                bid_item_index: 0,
+                bid_amount: 1,
+            },
+            GameEvent::InitiativeChanged {
+                first_player_index: 1,
+            },
+        ];
+        state.push_game_logs(2, &events);
+        assert_eq!(state.game_logs.len(), 2);
+        assert_eq!(state.game_logs[0].turn, 2);
+        assert!(matches!(state.game_logs[0].log_type, LogType::BidPassed(0)));
+        assert_eq!(state.game_logs[1].turn, 3);
+        assert!(matches!(
+            state.game_logs[1].log_type,
+            LogType::InitiativeChanged(1)
+        ));
+    }
+
+    #[test]
+    fn approval_is_logged_once_per_player() {
+        let state = start_game(1, 2);
+        let joined_player_indexes = state
+            .game_logs
+            .iter()
+            .filter_map(|game_log| match game_log.log_type {
+                LogType::Joined(player_index) => Some(player_index),
+                _ => None,
+            })
+            .collect::<Vec<usize>>();
+        assert_eq!(joined_player_indexes, vec![0, 1]);
+    }
+
+    #[test]
+    fn resolve_item_target_player_indexes_by_target() {
+        let mut state = BoPSharedState::init_for_players(1, 3);
+        let resolve = |state: &BoPSharedState, item_target, chosen_player_index| {
+            state.resolve_item_target_player_indexes(0, &item_target, chosen_player_index)
+        };
+        assert_eq!(resolve(&state, ItemTarget::Own, None), Some(vec![0]));
+        assert_eq!(resolve(&state, ItemTarget::Own, Some(1)), Some(vec![0]));
+        assert_eq!(resolve(&state, ItemTarget::Opponent, None), Some(vec![1]));
+        assert_eq!(
+            resolve(&state, ItemTarget::Opponent, Some(2)),
+            Some(vec![2])
+        );
+        assert_eq!(
+            resolve(&state, ItemTarget::AllOpponents, None),
+            Some(vec![1, 2])
+        );
+        assert_eq!(resolve(&state, ItemTarget::Choice, None), Some(vec![0]));
+        assert_eq!(resolve(&state, ItemTarget::Choice, Some(0)), Some(vec![0]));
+        assert_eq!(resolve(&state, ItemTarget::Choice, Some(2)), Some(vec![2]));
+
+        // 脱落したプレイヤーは対象から外れる
+        state.players[1].player_status.current_hp = 0;
+        state.eliminate_dead_players();
+        assert_eq!(resolve(&state, ItemTarget::Opponent, None), Some(vec![2]));
+        assert_eq!(
+            resolve(&state, ItemTarget::AllOpponents, None),
+            Some(vec![2])
+        );
+    }
+
+    #[test]
+    fn resolve_item_target_player_indexes_rejects_invalid_choice() {
+        let mut state = BoPSharedState::init_for_players(1, 3);
+        state.players[2].player_status.current_hp = 0;
+        state.eliminate_dead_players();
+        for (item_target, chosen_player_index) in [
+            (ItemTarget::Opponent, 0),
+            (ItemTarget::Opponent, 2),
+            (ItemTarget::Opponent, 99),
+            (ItemTarget::Choice, 2),
+            (ItemTarget::Choice, 99),
+        ] {
+            assert_eq!(
+                state.resolve_item_target_player_indexes(
+                    0,
+                    &item_target,
+                    Some(chosen_player_index)
+                ),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn consume_extra_item_use_needs_count_and_items() {
+        let mut state = BoPSharedState::init_for_players(1, 2);
+        assert!(!state.consume_extra_item_use(0));
+
+        state.players[0].player_status.extra_item_use_count = 1;
+        assert!(!state.consume_extra_item_use(0));
+        assert_eq!(state.players[0].player_status.extra_item_use_count, 1);
+
+        state.players[0]
+            .own_item_list
+            .push(Item::from(ItemKind("LongSword".to_string())));
+        assert!(state.consume_extra_item_use(0));
+        assert_eq!(state.players[0].player_status.extra_item_use_count, 0);
+        assert!(!state.consume_extra_item_use(0));
+
+        state.players[0].player_status.extra_item_use_count = 1;
+        state.players[0].player_status.current_hp = 0;
+        assert!(!state.consume_extra_item_use(0));
+        assert_eq!(state.players[0].player_status.extra_item_use_count, 1);
+    }
+
+    #[test]
+    fn consume_extra_attack_needs_count_and_opponent() {
+        let mut state = BoPSharedState::init_for_players(1, 3);
+        assert!(!state.consume_extra_attack(0));
+
+        state.players[0].player_status.extra_attack_count = 2;
+        assert!(state.consume_extra_attack(0));
+        assert_eq!(state.players[0].player_status.extra_attack_count, 1);
+
+        // 相手が1人でも残っていれば続けて攻撃できる
+        state.players[1].player_status.current_hp = 0;
+        assert!(state.consume_extra_attack(0));
+        assert_eq!(state.players[0].player_status.extra_attack_count, 0);
+
+        state.players[0].player_status.extra_attack_count = 1;
+        state.players[2].player_status.current_hp = 0;
+        assert!(!state.consume_extra_attack(0));
+
+        let mut state = BoPSharedState::init_for_players(1, 2);
+        state.players[0].player_status.extra_attack_count = 1;
+        state.players[0].player_status.current_hp = 0;
+        assert!(!state.consume_extra_attack(0));
+        assert_eq!(state.players[0].player_status.extra_attack_count, 1);
+    }
+
+    #[test]
+    fn tick_status_effects_triggers_and_expires() {
+        let mut state = BoPSharedState::init_for_players(1, 3);
+        let max_hp = state.players[1].player_status.max_hp;
+        state.players[0]
+            .player_status
+            .add_status_effect(StatusEffect::new(StatusEffectKind::Poison, 2, 1));
+        state.players[1].player_status.current_hp = max_hp - 5;
+        state.players[1]
+            .player_status
+            .add_status_effect(StatusEffect::new(StatusEffectKind::Regen, 3, 2));
+        // 脱落したプレイヤーの効果は発動しない
+        state.players[2]
+            .player_status
+            .add_status_effect(StatusEffect::new(StatusEffectKind::Regen, 3, 1));
+        state.players[2].player_status.current_hp = 0;
+        state.eliminate_dead_players();
+
+        assert_eq!(
+            state.tick_status_effects(),
+            vec![
+                GameEvent::StatusEffectTriggered {
+                    player_index: 0,
+                    kind: StatusEffectKind::Poison,
+                    amount: 2,
+                },
+                GameEvent::StatusEffectExpired {
+                    player_index: 0,
+                    kind: StatusEffectKind::Poison,
+                },
+                GameEvent::StatusEffectTriggered {
+                    player_index: 1,
+                    kind: StatusEffectKind::Regen,
+                    amount: 3,
+                },
+            ]
+        );
+        // 最大HPを超えて回復した分は数えない
+        assert_eq!(
+            state.tick_status_effects(),
+            vec![
+                GameEvent::StatusEffectTriggered {
+                    player_index: 1,
+                    kind: StatusEffectKind::Regen,
+                    amount: 2,
+                },
+                GameEvent::StatusEffectExpired {
+                    player_index: 1,
+                    kind: StatusEffectKind::Regen,
+                },
+            ]
+        );
+        assert!(state.tick_status_effects().is_empty());
+        assert_eq!(state.players[1].player_status.current_hp, max_hp);
+        assert_eq!(state.players[2].player_status.status_effects.len(), 1);
+    }
+
+    #[test]
+    fn pay_gain_to_passed_players_pays_only_passed_players() {
+        let mut state = BoPSharedState::init_for_players(1, 3);
+        for (player_index, estimated_money_amount) in [(0, 3), (1, 4), (2, 5)] {
+            state.players[player_index]
+                .player_status
+                .estimated_money_amount = estimated_money_amount;
+        }
+        let mut bids = [0, 1, 2].map(|player_index| {
+            let mut bid = BidMessage::init(0);
+            bid.player_index = player_index;
+            bid.bid_amount = 2;
+            bid
+        });
+        bids[0].is_skipped = true;
+        bids[2].is_skipped = true;
+        state.temporary_bid_history = bids.to_vec();
+        let money_amounts = state
+            .players
+            .iter()
+            .map(|player| player.player_status.current_money_amount)
+            .collect::<Vec<u32>>();
+
+        assert_eq!(
+            state.pay_gain_to_passed_players(),
+            vec![
+                GameEvent::MoneyGained {
+                    player_index: 0,
+                    amount: 3,
+                    source: MoneySource::Gain,
+                },
+                GameEvent::MoneyGained {
+                    player_index: 2,
+                    amount: 5,
+                    source: MoneySource::Gain,
+                },
+            ]
+        );
+        assert_eq!(
+            state.players[0].player_status.current_money_amount,
+            money_amounts[0] + 3
+        );
+        assert_eq!(
+            state.players[1].player_status.current_money_amount,
+            money_amounts[1]
+        );
+        assert_eq!(
+            state.players[2].player_status.current_money_amount,
+            money_amounts[2] + 5
+        );
+    }
+}
@@ -0,0 +1,379 @@
+use crate::bop_core::mechanism::deck_list::{standard_deck_list_id, DeckError};
+use crate::bop_core::mechanism::item::ItemKind;
+use crate::bop_core::mechanism::item_set::ItemSet;
+use crate::bop_core::random::SeededRng;
+use crate::bop_core::rules::rule_set::RuleSet;
+use crate::bop_core::rules::RuleError;
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+use crate::bop_core::state::snapshot::GameplaySnapshot;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GameStartIsApprovedMessage {
+    pub player_index: usize,
+    pub game_start_is_approved: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct BidMessage {
+    pub seq_no: usize,
+    pub player_index: usize,
+    pub bid_item_index: usize,
+    pub bid_amount: u32,
+    // パスした場合はこのフェーズでは落札せず、獲得Moneyのみ得る
+    // bid_item_index と bid_amount は使わない
+    #[serde(default)]
+    pub is_skipped: bool,
+}
+
+impl BidMessage {
+    pub fn init(index: usize) -> BidMessage {
+        BidMessage {
+            seq_no: 0,
+            player_index: 0,
+            bid_item_index: index,
+            bid_amount: 1,
+            is_skipped: false,
+        }
+    }
+    // 競り上げ方式の最低入札価格
+    // 現在価格が0なら最低落札価格、1以上なら現在価格+bid_increment（RuleSet で決める）
+    pub fn lowest_bid_amount(
+        item_index: usize,
+        temporary_bid_history: &[BidMessage],
+        rule_set: &RuleSet,
+    ) -> u32 {
+        let current_bid_amount = BidMessage::current_bid_amount(item_index, temporary_bid_history);
+        if current_bid_amount == 0 {
+            rule_set.reserve_price
+        } else {
+            current_bid_amount + rule_set.bid_increment
+        }
+    }
+
+    pub fn last_bid_amount_of_player(
+        player_index: usize,
+        temporary_bid_history: &[BidMessage],
+    ) -> u32 {
+        temporary_bid_history
+            .iter()
+            .rev()
+            .find(|history| history.player_index == player_index)
+            .map(|history| history.bid_amount)
+            .unwrap_or(0)
+    }
+
+    // 最後の入札がパスであったプレイヤー
+    pub fn is_passed(player_index: usize, temporary_bid_history: &[BidMessage]) -> bool {
+        matches!(
+            temporary_bid_history
+                .iter()
+                .rev()
+                .find(|history| history.player_index == player_index),
+            Some(history) if history.is_skipped
+        )
+    }
+
+    pub fn current_bid_amount(item_index: usize, temporary_bid_history: &[BidMessage]) -> u32 {
+        if temporary_bid_history.is_empty() {
+            0
+        } else if let Some(last_bid) = temporary_bid_history
+            .iter()
+            .rfind(|history| !history.is_skipped && history.bid_item_index == item_index)
+        {
+            last_bid.bid_amount
+        } else {
+            0
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct UseItemMessage {
+    pub seq_no: usize,
+    pub turn: usize,
+    // 1度のターンで複数のカードを使うことができるように用意したフラグ
+    // 当然ブロックしているユーザーが次のカード使用者である
+    // 追加の使用回数が残っているかを見てルール側で設定するので、送信時は false でよい
+    pub check_is_blocked: bool,
+    pub player_index: usize,
+    pub use_item_index: usize,
+    pub is_skipped: bool,
+    pub args_i32: Vec<i32>,
+    pub args_usize: Vec<usize>,
+}
+
+impl UseItemMessage {
+    pub fn new_with_turn(turn: usize) -> UseItemMessage {
+        UseItemMessage {
+            seq_no: 0,
+            turn,
+            check_is_blocked: false,
+            player_index: 0,
+            use_item_index: 0,
+            is_skipped: false,
+            args_i32: vec![],
+            args_usize: vec![],
+        }
+    }
+    pub fn empty() -> UseItemMessage {
+        UseItemMessage::new_with_turn(0)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct AttackTargetMessage {
+    pub seq_no: usize,
+    pub turn: usize,
+    pub player_index: usize,
+    // 1度のターンで複数回攻撃決定ができるように用意したフラグ
+    // 当然ブロックしているユーザーが次の攻撃対象決定者である
+    // 追加の攻撃回数が残っているかを見てルール側で設定するので、送信時は false でよい
+    pub check_is_blocked: bool,
+    pub attack_target_player_index: usize,
+    pub is_skipped: bool,
+}
+
+impl AttackTargetMessage {
+    pub fn new_with_turn(turn: usize) -> AttackTargetMessage {
+        AttackTargetMessage {
+            seq_no: 0,
+            turn,
+            player_index: 0,
+            check_is_blocked: false,
+            attack_target_player_index: 0,
+            is_skipped: false,
+        }
+    }
+    pub fn empty() -> AttackTargetMessage {
+        AttackTargetMessage::new_with_turn(0)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct GameStateMessage {
+    pub player_index: usize,
+    pub last_consumed_seq_no: usize,
+}
+
+// 一定間隔のシーケンス番号ごとに、state のハッシュを相手に送る
+#[derive(Deserialize, Serialize, Debug)]
+pub struct StateHashMessage {
+    pub player_index: usize,
+    pub seq_no: usize,
+    pub state_hash: u64,
+}
+
+// ハッシュが一致しなかった場合に、ホストが自分の state を送る
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SnapshotMessage {
+    pub player_index: usize,
+    pub snapshot: GameplaySnapshot,
+}
+
+// 先攻の決定には山札のシャッフルとは別の乱数列を使う
+// 同じ乱数列の最初の値を使うと、先攻と山札の並びに相関ができてしまう
+const TURN_ORDER_SEED_SALT: u64 = 0x5bd1e995;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GameRuleMessage {
+    pub host_player_name: String,
+    pub host_player_index: usize,
+    pub guest_player_name: String,
+    pub guest_player_index: usize,
+    // 山札と先攻はこのシードから両クライアントがそれぞれ導出する
+    pub seed: u64,
+    // マッチングを受けた側（ホスト）のルールに合わせる
+    #[serde(default)]
+    pub rule_set: RuleSet,
+    // ホストの山札の並びが、シードから作った山札と異なる場合のみ送る
+    // ない場合は deck_list_id の山札をシードから作る
+    #[serde(default)]
+    pub item_kind_list: Option<Vec<ItemKind>>,
+    // ホストが選んだ山札。ゲストの山札にない場合はマッチングを中止する
+    #[serde(default = "standard_deck_list_id")]
+    pub deck_list_id: String,
+}
+
+// マッチングで受け取ったルールと山札を適用できない理由
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatchError {
+    // ホストと同じ山札を作れない
+    InvalidDeck(DeckError),
+    // ホストのルールの値が不正
+    InvalidRuleSet(RuleError),
+}
+
+impl fmt::Display for MatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchError::InvalidDeck(error) => write!(f, "山札を合わせられません: {}", error),
+            MatchError::InvalidRuleSet(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for MatchError {}
+
+impl From<DeckError> for MatchError {
+    fn from(error: DeckError) -> MatchError {
+        MatchError::InvalidDeck(error)
+    }
+}
+
+impl From<RuleError> for MatchError {
+    fn from(error: RuleError) -> MatchError {
+        MatchError::InvalidRuleSet(error)
+    }
+}
+
+impl GameRuleMessage {
+    pub fn new(
+        bop_shared_state: &BoPSharedState,
+        host_player_name: String,
+        guest_player_name: String,
+    ) -> GameRuleMessage {
+        let seed = bop_shared_state.seed;
+        let host_is_first = SeededRng::from_seed(seed ^ TURN_ORDER_SEED_SALT).next_bool();
+        let item_kind_list = bop_shared_state
+            .bid_scheduled_items
+            .iter()
+            .map(|item| item.item_kind.clone())
+            .collect::<Vec<ItemKind>>();
+        // ゲストは同梱のアイテムセットとシードから山札を作るので、同じ並びになる場合は送らない
+        let seed_derived_item_kind_list = ItemSet::default_set()
+            .create_deck(&bop_shared_state.deck_list_id, seed)
+            .into_iter()
+            .map(|item| item.item_kind)
+            .collect::<Vec<ItemKind>>();
+        GameRuleMessage {
+            host_player_name,
+            host_player_index: if host_is_first { 0 } else { 1 },
+            guest_player_name,
+            guest_player_index: if host_is_first { 1 } else { 0 },
+            seed,
+            rule_set: bop_shared_state.rule_set.clone(),
+            item_kind_list: (item_kind_list != seed_derived_item_kind_list)
+                .then_some(item_kind_list),
+            deck_list_id: bop_shared_state.deck_list_id.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn get_item_kind_list(bop_shared_state: &BoPSharedState) -> Vec<ItemKind> {
+        bop_shared_state
+            .bid_scheduled_items
+            .iter()
+            .map(|item| item.item_kind.clone())
+            .collect()
+    }
+
+    fn create_message(bop_shared_state: &BoPSharedState) -> GameRuleMessage {
+        GameRuleMessage::new(bop_shared_state, "host".to_string(), "guest".to_string())
+    }
+
+    #[test]
+    fn same_seed_derives_same_deck() {
+        let host_state = BoPSharedState::init_for_players(7, 2);
+        let other_host_state = BoPSharedState::init_for_players(7, 2);
+        assert_eq!(
+            get_item_kind_list(&host_state),
+            get_item_kind_list(&other_host_state)
+        );
+        assert_eq!(
+            create_message(&host_state).host_player_index,
+            create_message(&other_host_state).host_player_index
+        );
+    }
+
+    #[test]
+    fn different_seed_derives_different_deck() {
+        let host_state = BoPSharedState::init_for_players(1, 2);
+        let other_host_state = BoPSharedState::init_for_players(2, 2);
+        assert_ne!(
+            get_item_kind_list(&host_state),
+            get_item_kind_list(&other_host_state)
+        );
+    }
+
+    #[test]
+    fn both_players_can_go_first() {
+        let host_player_indexes = (0..32)
+            .map(|seed| {
+                create_message(&BoPSharedState::init_for_players(seed, 2)).host_player_index
+            })
+            .collect::<Vec<usize>>();
+        assert!(host_player_indexes.contains(&0));
+        assert!(host_player_indexes.contains(&1));
+    }
+
+    #[test]
+    fn guest_derives_deck_from_seed() {
+        let host_state = BoPSharedState::init_for_players(3, 2);
+        let message = create_message(&host_state);
+        assert!(message.item_kind_list.is_none());
+        let mut guest_state = BoPSharedState::init_for_players(4, 2);
+        guest_state.apply_game_rule_message(&message).unwrap();
+        assert_eq!(
+            get_item_kind_list(&guest_state),
+            get_item_kind_list(&host_state)
+        );
+    }
+
+    #[test]
+    fn guest_uses_sent_deck_when_not_derived_from_seed() {
+        let mut host_state = BoPSharedState::init_for_players(3, 2);
+        host_state.bid_scheduled_items.reverse();
+        let message = create_message(&host_state);
+        assert!(message.item_kind_list.is_some());
+        let mut guest_state = BoPSharedState::init_for_players(4, 2);
+        guest_state.apply_game_rule_message(&message).unwrap();
+        assert_eq!(
+            get_item_kind_list(&guest_state),
+            get_item_kind_list(&host_state)
+        );
+    }
+
+    #[test]
+    fn guest_rejects_invalid_rule_set() {
+        let mut host_state = BoPSharedState::init_for_players(3, 2);
+        host_state.rule_set.bid_increment = 0;
+        let message = create_message(&host_state);
+        let mut guest_state = BoPSharedState::init_for_players(4, 2);
+        assert!(matches!(
+            guest_state.apply_game_rule_message(&message),
+            Err(MatchError::InvalidRuleSet(RuleError::InvalidRuleSet(_)))
+        ));
+        assert_eq!(guest_state.seed, 4);
+    }
+
+    #[test]
+    fn online_match_resets_custom_item_set() {
+        let mut item_set = (*ItemSet::default_set()).clone();
+        let mut deck_list = item_set.get_standard_deck_list();
+        deck_list.id = "custom".to_string();
+        deck_list.items.reverse();
+        item_set.deck_lists.push(deck_list);
+        let mut host_state = BoPSharedState::init_for_players(3, 2);
+        host_state.update_item_set(Arc::new(item_set));
+        host_state.select_deck_list("custom").unwrap();
+
+        assert!(host_state.reset_item_set_for_online());
+        assert!(!host_state.reset_item_set_for_online());
+        assert_eq!(host_state.deck_list_id, standard_deck_list_id());
+        let message = create_message(&host_state);
+        let mut guest_state = BoPSharedState::init_for_players(4, 2);
+        assert_eq!(guest_state.apply_game_rule_message(&message), Ok(()));
+        assert_eq!(host_state.apply_game_rule_message(&message), Ok(()));
+        assert_eq!(
+            get_item_kind_list(&guest_state),
+            get_item_kind_list(&host_state)
+        );
+    }
+}
@@ -1,9 +1,14 @@
-use crate::bop::state::bop_shared_state::BoPSharedState;
-use crate::bop::state::message::{AttackTargetMessage, UseItemMessage};
-use crate::bop::state::phase::PhaseType::{AttackTarget, Bid, Empty, GameEnd, GameStart, UseItem};
-use wasm_bindgen_test::console_log;
+use crate::bop_core::rules::rule_set::AuctionMode;
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+use crate::bop_core::state::message::{AttackTargetMessage, BidMessage, UseItemMessage};
+use crate::bop_core::state::phase::PhaseType::{
+    AttackTarget, Bid, Empty, GameEnd, GameStart, UseItem,
+};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+// 判定関数は保存できないので、フェーズの種類として保存し、復元時に関数を割り当て直す
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "PhaseType", into = "PhaseType")]
 pub struct Phase {
     pub phase_type: PhaseType,
     pub check_phase_complete_func: fn(&mut BoPSharedState) -> CheckPhaseCompleteResult,
@@ -26,12 +31,12 @@ impl Phase {
         }
     }
     pub fn get_phases() -> Vec<Phase> {
-        // PhaseType::GameEnd の Phase は現状必ずしもいらない…
         vec![
             Phase::get_game_start_phase(),
             Phase::get_bid_phase(),
             Phase::get_use_item_phase(),
             Phase::get_attack_target_phase(),
+            Phase::get_game_end_phase(),
         ]
     }
     pub fn get_game_start_phase() -> Phase {
@@ -64,17 +69,30 @@ impl Phase {
         }
     }
     pub fn get_bid_phase() -> Phase {
-        // TODO
-        // この関数は、3人以上のプレイヤーを意識して書かれていますが、動作確認は不十分です
+        // 入札の方式は RuleSet で決まり、方式ごとに完了の判定が異なる
+        // 保存時にはフェーズの種類しか残らないので、判定関数の中で振り分ける
         fn check_bid_phase_complete_func(
             game_state: &mut BoPSharedState,
+        ) -> CheckPhaseCompleteResult {
+            match game_state.rule_set.auction_mode {
+                AuctionMode::Open => check_open_bid_phase_complete_func(game_state),
+                AuctionMode::SealedFirstPrice | AuctionMode::SecondPrice => {
+                    check_sealed_bid_phase_complete_func(game_state)
+                }
+                AuctionMode::Dutch => check_dutch_bid_phase_complete_func(game_state),
+            }
+        }
+        // 脱落したプレイヤーは行動順から取り除かれているので、行動順にいるプレイヤーのみで判定する
+        fn check_open_bid_phase_complete_func(
+            game_state: &mut BoPSharedState,
         ) -> CheckPhaseCompleteResult {
             let mut result = CheckPhaseCompleteResult::empty();
             let temporary_history_len = game_state.temporary_bid_history.len();
             let player_len = game_state.players.len();
+            let active_player_indexes = game_state.initiatives_to_player_index.clone();
             let own_player_index = game_state.own_player_index;
             // 入札が一巡していない場合のロジック
-            if temporary_history_len < player_len {
+            if temporary_history_len < active_player_indexes.len() {
                 // 優先順位順に入札しているので、次に入札すべきプレイヤーは temporary_history の長さで決まる（一巡しない間）
                 let next_player_index =
                     game_state.initiatives_to_player_index[temporary_history_len];
@@ -84,34 +102,47 @@ impl Phase {
             }
             // 以降は、入札が一巡している
             // 各プレイヤーについて、"最終の"入札済みカードのインデックスを集める
-            let mut player_index_to_target_item_index = vec![0; player_len];
+            // パスしたプレイヤーは対象のカードを持たない
+            let mut player_index_to_target_item_index = vec![None; player_len];
             // 各プレイヤーにについて、最後の入札のインデックスを集める（あとで使う）
             let mut player_index_to_last_bid_index = vec![0; player_len];
-            for player_index in 0..player_len {
+            for player_index in active_player_indexes.iter().copied() {
                 let found = game_state
                     .temporary_bid_history
                     .iter()
                     .enumerate()
-                    .filter(|(_, bid)| bid.player_index == player_index)
-                    .last();
-                if found.is_none() {
-                    let input_player_index = game_state
-                        .temporary_bid_history
+                    .rfind(|(_, bid)| bid.player_index == player_index);
+                // 入札が一巡しているのにまだ入札していないプレイヤーがいる場合（同期ずれなど）は、
+                // 優先順位順で最初の未入札プレイヤーの入力を待つ
+                let Some((last_bid_index, last_bid)) = found else {
+                    let next_player_index = game_state
+                        .initiatives_to_player_index
                         .iter()
-                        .map(|bid| bid.player_index)
-                        .collect::<Vec<usize>>();
-                    console_log!("temporary history len >= player len, but index={} player target item not found. input player index: {:?} {:?}", player_index, input_player_index, game_state.temporary_bid_history);
-                    panic!()
-                }
-                player_index_to_target_item_index[player_index] = found.unwrap().1.bid_item_index;
-                player_index_to_last_bid_index[player_index] = found.unwrap().0;
+                        .find(|initiative_player_index| {
+                            !game_state
+                                .temporary_bid_history
+                                .iter()
+                                .any(|bid| bid.player_index == **initiative_player_index)
+                        })
+                        .copied()
+                        .unwrap_or(player_index);
+                    result.is_required_own_input_for_complete =
+                        Some(next_player_index == own_player_index);
+                    return result;
+                };
+                player_index_to_target_item_index[player_index] =
+                    (!last_bid.is_skipped).then_some(last_bid.bid_item_index);
+                player_index_to_last_bid_index[player_index] = last_bid_index;
             }
             // 各プレイヤーについて、競合を持つかをフラグで集める
             let mut player_index_to_has_competitor_flag = vec![false; player_len];
-            for player_a_index in 1..player_len {
-                for player_b_index in 0..player_a_index {
-                    if player_index_to_target_item_index[player_a_index]
-                        == player_index_to_target_item_index[player_b_index]
+            for (active_a_index, player_a_index) in
+                active_player_indexes.iter().copied().enumerate()
+            {
+                for player_b_index in active_player_indexes.iter().take(active_a_index).copied() {
+                    if player_index_to_target_item_index[player_a_index].is_some()
+                        && player_index_to_target_item_index[player_a_index]
+                            == player_index_to_target_item_index[player_b_index]
                     {
                         // 重複発見時ロジック
                         player_index_to_has_competitor_flag[player_a_index] = true;
@@ -122,43 +153,9 @@ impl Phase {
             // 競合がなければ（次が何のフェースでも）完了
             result.is_phase_complete = player_index_to_has_competitor_flag
                 .iter()
-                .all(|flag| *flag == false);
+                .all(|flag| !*flag);
             if result.is_phase_complete {
-                // イニシアチブの入れ替え
-                // TODO
-                // もっといい位置に移動する
-                // もっとコードをシンプルにする
-                let first_player_last_bid_index =
-                    player_index_to_last_bid_index[game_state.initiatives_to_player_index[0]];
-                let first_player_bid_amount =
-                    game_state.temporary_bid_history[first_player_last_bid_index].bid_amount;
-                let second_player_last_bid_index =
-                    player_index_to_last_bid_index[game_state.initiatives_to_player_index[1]];
-                let second_player_bid_amount =
-                    game_state.temporary_bid_history[second_player_last_bid_index].bid_amount;
-                if second_player_bid_amount >= first_player_bid_amount {
-                    let new_first_player = game_state.initiatives_to_player_index[1];
-                    let new_second_player = game_state.initiatives_to_player_index[0];
-                    game_state.initiatives_to_player_index[0] = new_first_player;
-                    game_state.initiatives_to_player_index[1] = new_second_player;
-                    // interrupt_animations.push(vec![Animation::create_message(format!(
-                    //     "落札金額により行動順が変更されました。あなたは {} です",
-                    //     if new_first_player == game_state.own_player_index {
-                    //         "先攻"
-                    //     } else {
-                    //         "後攻"
-                    //     }
-                    // ))])
-                }
-
-                // 引き続き Bid フェーズを行うかの判定
-                let is_continuous_bid = game_state.players[0].own_item_list.len() < 2;
-                // まだカード使用フェーズが来ないなら引き続き Bid、そうでないなら UseItem
-                if is_continuous_bid {
-                    result.next_phase_index = Some(Bid as i32 as usize);
-                } else {
-                    result.next_phase_index = Some(UseItem as i32 as usize);
-                }
+                result.next_phase_index = Some(get_next_phase_index_after_bid(game_state));
                 return result;
             }
             // 競合が見つかっている場合のロジック
@@ -173,7 +170,7 @@ impl Phase {
             let mut is_required_own_input = true;
 
             // 自分より前に入札している他のプレイヤー（競合を持つ）を探す
-            for player_index in 0..player_len {
+            for player_index in active_player_indexes.iter().copied() {
                 // 自分自身は除外
                 if player_index == own_player_index {
                     continue;
@@ -192,6 +189,72 @@ impl Phase {
             result.is_required_own_input_for_complete = Some(is_required_own_input);
             result
         }
+        // 封印入札・セカンドプライス
+        // ラウンドごとに全員の入札が揃ったら公開し、落札できなかったプレイヤーだけで次のラウンドを行う
+        // 全員の落札が決まったら完了
+        fn check_sealed_bid_phase_complete_func(
+            game_state: &mut BoPSharedState,
+        ) -> CheckPhaseCompleteResult {
+            let mut result = CheckPhaseCompleteResult::empty();
+            let progress = game_state.get_auction_progress();
+            if progress.is_complete() {
+                result.is_phase_complete = true;
+                result.next_phase_index = Some(get_next_phase_index_after_bid(game_state));
+                return result;
+            }
+            // 入札は公開されないが、通信の順序を保つため行動順に1人ずつ受け付ける
+            result.is_required_own_input_for_complete =
+                Some(progress.get_next_bidder() == Some(game_state.own_player_index));
+            result
+        }
+        // ダッチ方式
+        // 行動順に今の価格で落札するか見送るかを選び、見送ったプレイヤーだけで次のラウンドを行う
+        // 最低落札価格まで下がったら見送れないので、いずれ全員の落札かパスが決まって完了する
+        fn check_dutch_bid_phase_complete_func(
+            game_state: &mut BoPSharedState,
+        ) -> CheckPhaseCompleteResult {
+            let mut result = CheckPhaseCompleteResult::empty();
+            let progress = game_state.get_auction_progress();
+            if progress.is_complete() {
+                result.is_phase_complete = true;
+                result.next_phase_index = Some(get_next_phase_index_after_bid(game_state));
+                return result;
+            }
+            result.is_required_own_input_for_complete =
+                Some(progress.get_next_bidder() == Some(game_state.own_player_index));
+            result
+        }
+        // イニシアチブの入れ替えはフェーズ移行時に BoPSharedState::phase_shift_to で行う
+        // 引き続き Bid フェーズを行うかの判定
+        // パスしたプレイヤー以外は1つずつ落札するので、誰か1人でも RuleSet で決めた数未満であれば引き続き Bid
+        // ただし山札が尽きて次の入札に必要な数のアイテムが残らない場合は UseItem に進む
+        fn get_next_phase_index_after_bid(game_state: &BoPSharedState) -> usize {
+            let active_player_indexes = &game_state.initiatives_to_player_index;
+            let winning_players_len = active_player_indexes
+                .iter()
+                .filter(|player_index| {
+                    !BidMessage::is_passed(**player_index, &game_state.temporary_bid_history)
+                })
+                .count();
+            let remaining_items_len = game_state
+                .items_bid_on
+                .len()
+                .saturating_sub(winning_players_len)
+                + game_state.bid_scheduled_items.len();
+            // このフェーズでパスしたプレイヤーは、アイテムが足りなくても入札を続ける理由にならない
+            let is_continuous_bid = remaining_items_len >= active_player_indexes.len()
+                && active_player_indexes.iter().any(|player_index| {
+                    game_state.players[*player_index].own_item_list.len()
+                        < game_state.rule_set.items_drafted_before_combat
+                        && !BidMessage::is_passed(*player_index, &game_state.temporary_bid_history)
+                });
+            // まだカード使用フェーズが来ないなら引き続き Bid、そうでないなら UseItem
+            if is_continuous_bid {
+                Bid as i32 as usize
+            } else {
+                UseItem as i32 as usize
+            }
+        }
         Phase {
             phase_type: Bid,
             check_phase_complete_func: check_bid_phase_complete_func,
@@ -200,9 +263,6 @@ impl Phase {
     }
 
     pub fn get_use_item_phase() -> Phase {
-        // TODO
-        // この関数は、3人以上のプレイヤーを意識して書かれていますが、動作確認は不十分です
-
         fn check_use_item_complete_func(
             game_state: &mut BoPSharedState,
         ) -> CheckPhaseCompleteResult {
@@ -239,7 +299,10 @@ impl Phase {
                 return result;
             }
             // 使用フラグを収集
-            let mut player_index_to_item_used_flag = vec![false; player_len];
+            // 脱落したプレイヤーは使用済みとして扱う
+            let mut player_index_to_item_used_flag = (0..player_len)
+                .map(|player_index| !game_state.is_active_player(player_index))
+                .collect::<Vec<bool>>();
 
             for player_index in 0..player_len {
                 // カードを使用していなければ当然履歴も見つからない点に注意
@@ -279,8 +342,6 @@ impl Phase {
     }
 
     pub fn get_attack_target_phase() -> Phase {
-        // TODO
-        // この関数は、3人以上のプレイヤーを意識して書かれていますが、動作確認は不十分です
         fn check_attack_target_complete_func(
             game_state: &mut BoPSharedState,
         ) -> CheckPhaseCompleteResult {
@@ -316,7 +377,10 @@ impl Phase {
                 return result;
             }
             // 使用フラグを収集
-            let mut player_index_to_chose_attack_target_flag = vec![false; player_len];
+            // 脱落したプレイヤーは決定済みとして扱う
+            let mut player_index_to_chose_attack_target_flag = (0..player_len)
+                .map(|player_index| !game_state.is_active_player(player_index))
+                .collect::<Vec<bool>>();
 
             for player_index in 0..player_len {
                 // 攻撃対象を決定していなければ当然履歴も見つからない点に注意
@@ -337,13 +401,14 @@ impl Phase {
                 result.is_required_own_input_for_complete =
                     Some(game_state.initiatives_to_player_index[0] == own_player_index);
                 // TODO
-                // 2以上ないと入札ができない
+                // 生存しているプレイヤーの数以上ないと入札ができない
                 // アイテム数が偶数なら1になることはないのだが、なぜか1になるケースがある
                 // 根本的解決が必要
-                if game_state.items_bid_on.len() < 2 {
-                    if game_state.players[0].own_item_list.is_empty()
-                        && game_state.players[1].own_item_list.is_empty()
-                    {
+                let active_player_indexes = &game_state.initiatives_to_player_index;
+                if game_state.items_bid_on.len() < active_player_indexes.len() {
+                    if active_player_indexes.iter().all(|player_index| {
+                        game_state.players[*player_index].own_item_list.is_empty()
+                    }) {
                         result.next_phase_index = Some(AttackTarget as i32 as usize);
                     } else {
                         result.next_phase_index = Some(UseItem as i32 as usize);
@@ -388,6 +453,25 @@ impl Phase {
     }
 }
 
+impl From<PhaseType> for Phase {
+    fn from(phase_type: PhaseType) -> Phase {
+        match phase_type {
+            GameStart => Phase::get_game_start_phase(),
+            Bid => Phase::get_bid_phase(),
+            UseItem => Phase::get_use_item_phase(),
+            AttackTarget => Phase::get_attack_target_phase(),
+            GameEnd => Phase::get_game_end_phase(),
+            Empty => Phase::empty(),
+        }
+    }
+}
+
+impl From<Phase> for PhaseType {
+    fn from(phase: Phase) -> PhaseType {
+        phase.phase_type
+    }
+}
+
 #[derive(Debug)]
 pub struct CheckPhaseCompleteResult {
     pub is_phase_complete: bool,
@@ -405,7 +489,7 @@ impl CheckPhaseCompleteResult {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum PhaseType {
     GameStart,
     Bid,
@@ -0,0 +1,121 @@
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// 保存形式を変更した場合はインクリメントする
+// 異なるバージョンのセーブデータは読み込まずに破棄する
+pub const SAVE_DATA_VERSION: u32 = 1;
+
+// 対戦を中断・再開するためのセーブデータ
+// ブラウザの Storage やファイルに JSON として書き出す
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SaveData {
+    pub version: u32,
+    pub bop_shared_state: BoPSharedState,
+}
+
+// 本体を解釈する前にバージョンだけを読むためのもの
+#[derive(Deserialize)]
+struct SaveDataHeader {
+    version: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SaveDataError {
+    MalformedSaveData(String),
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for SaveDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveDataError::MalformedSaveData(message) => {
+                write!(f, "セーブデータを解釈できません: {}", message)
+            }
+            SaveDataError::UnsupportedVersion(version) => {
+                write!(f, "対応していないバージョンのセーブデータです: {}", version)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SaveDataError {}
+
+impl SaveData {
+    pub fn new(bop_shared_state: &BoPSharedState) -> SaveData {
+        SaveData {
+            version: SAVE_DATA_VERSION,
+            bop_shared_state: bop_shared_state.clone(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<SaveData, SaveDataError> {
+        let header = serde_json::from_str::<SaveDataHeader>(json)
+            .map_err(|error| SaveDataError::MalformedSaveData(error.to_string()))?;
+        if header.version != SAVE_DATA_VERSION {
+            return Err(SaveDataError::UnsupportedVersion(header.version));
+        }
+        let save_data = serde_json::from_str::<SaveData>(json)
+            .map_err(|error| SaveDataError::MalformedSaveData(error.to_string()))?;
+        save_data
+            .bop_shared_state
+            .rule_set
+            .validate()
+            .map_err(|error| SaveDataError::MalformedSaveData(error.to_string()))?;
+        Ok(save_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bop_core::rules::tests::{play_randomly, start_game};
+    use crate::bop_core::state::snapshot::state_hash;
+
+    #[test]
+    fn save_data_round_trip() {
+        let mut state = start_game(1, 2);
+        play_randomly(&mut state, 1, 30);
+        let save_data = SaveData::from_json(&SaveData::new(&state).to_json()).unwrap();
+        assert_eq!(state_hash(&save_data.bop_shared_state), state_hash(&state));
+        assert_eq!(
+            save_data.bop_shared_state.consumed_seq_no,
+            state.consumed_seq_no
+        );
+        assert_eq!(
+            save_data.bop_shared_state.command_history.len(),
+            state.command_history.len()
+        );
+    }
+
+    #[test]
+    fn save_data_rejects_other_version() {
+        let json = format!(r#"{{"version":{}}}"#, SAVE_DATA_VERSION + 1);
+        assert_eq!(
+            SaveData::from_json(&json).err(),
+            Some(SaveDataError::UnsupportedVersion(SAVE_DATA_VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn save_data_rejects_malformed_json() {
+        assert!(matches!(
+            SaveData::from_json("not json"),
+            Err(SaveDataError::MalformedSaveData(_))
+        ));
+    }
+
+    #[test]
+    fn save_data_rejects_invalid_rule_set() {
+        let mut state = start_game(1, 2);
+        state.rule_set.reserve_price = 0;
+        assert!(matches!(
+            SaveData::from_json(&SaveData::new(&state).to_json()),
+            Err(SaveDataError::MalformedSaveData(_))
+        ));
+    }
+}
@@ -0,0 +1,327 @@
+use crate::bop_core::mechanism::item::Item;
+use crate::bop_core::mechanism::player_status::PlayerStatus;
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+use crate::bop_core::state::message::{AttackTargetMessage, BidMessage, UseItemMessage};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// 同期確認のためにハッシュを交換する間隔（シーケンス番号）
+pub const STATE_HASH_INTERVAL: usize = 10;
+
+// ゲーム進行に関わる state のみを抜き出したもの
+// ハッシュの計算と、同期ずれ時にホストの state で上書きするために使う
+// 上書きは操作の記録に残らないので、上書きした後の state はリプレイで再現できない
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GameplaySnapshot {
+    pub player_statuses: Vec<PlayerStatus>,
+    pub own_item_lists: Vec<Vec<Item>>,
+    pub items_bid_on: Vec<Item>,
+    pub bid_scheduled_items: Vec<Item>,
+    pub initiatives_to_player_index: Vec<usize>,
+    pub turn: usize,
+    pub phase_index: usize,
+    // 以下はハッシュの対象外で、上書き時にのみ使う
+    #[serde(default)]
+    pub temporary_bid_history: Vec<BidMessage>,
+    #[serde(default)]
+    pub consumed_seq_no: usize,
+    // 再送信の依頼に応えるためと、連続行動の判定のために履歴も合わせる
+    #[serde(default)]
+    pub bid_history: Vec<BidMessage>,
+    #[serde(default)]
+    pub use_item_history: Vec<UseItemMessage>,
+    #[serde(default)]
+    pub attack_target_history: Vec<AttackTargetMessage>,
+    // 作成時の state のハッシュ。通信中に壊れたり改変されたりしていないかを確かめる
+    #[serde(default)]
+    pub state_hash: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotError {
+    // プレイヤーごとの値の数がプレイヤーの人数と一致しない
+    PlayersLenMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    // 存在しないプレイヤー・フェーズ・アイテムを指している
+    IndexOutOfRange(String),
+    // 内容から計算したハッシュが、添えられたハッシュと一致しない
+    HashMismatch,
+    // 既に適用した操作より前の state で、上書きすると操作が巻き戻る
+    StaleSeqNo {
+        consumed_seq_no: usize,
+        snapshot_seq_no: usize,
+    },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::PlayersLenMismatch { expected, actual } => write!(
+                f,
+                "スナップショットのプレイヤー数が一致しません: {} 人のはずが {} 人分です",
+                expected, actual
+            ),
+            SnapshotError::IndexOutOfRange(name) => {
+                write!(f, "スナップショットの {} が範囲外です", name)
+            }
+            SnapshotError::HashMismatch => {
+                write!(f, "スナップショットのハッシュが内容と一致しません")
+            }
+            SnapshotError::StaleSeqNo {
+                consumed_seq_no,
+                snapshot_seq_no,
+            } => write!(
+                f,
+                "スナップショットが古すぎます: {} まで適用済みですが {} 時点のものです",
+                consumed_seq_no, snapshot_seq_no
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+// ハッシュの対象となる値
+// 両クライアントで同じ値になるよう、フィールドの順序を固定したまま JSON にしてからハッシュを取る
+#[derive(Serialize)]
+struct HashedState<'a> {
+    player_statuses: Vec<&'a PlayerStatus>,
+    own_item_lists: Vec<&'a Vec<Item>>,
+    items_bid_on: &'a Vec<Item>,
+    bid_scheduled_items: &'a Vec<Item>,
+    initiatives_to_player_index: &'a Vec<usize>,
+    turn: usize,
+    phase_index: usize,
+}
+
+impl GameplaySnapshot {
+    pub fn from_state(bop_shared_state: &BoPSharedState) -> GameplaySnapshot {
+        GameplaySnapshot {
+            state_hash: state_hash(bop_shared_state),
+            player_statuses: bop_shared_state
+                .players
+                .iter()
+                .map(|player| player.player_status.clone())
+                .collect(),
+            own_item_lists: bop_shared_state
+                .players
+                .iter()
+                .map(|player| player.own_item_list.clone())
+                .collect(),
+            items_bid_on: bop_shared_state.items_bid_on.clone(),
+            bid_scheduled_items: bop_shared_state.bid_scheduled_items.clone(),
+            initiatives_to_player_index: bop_shared_state.initiatives_to_player_index.clone(),
+            turn: bop_shared_state.turn,
+            phase_index: bop_shared_state.phase_index,
+            temporary_bid_history: bop_shared_state.temporary_bid_history.clone(),
+            consumed_seq_no: bop_shared_state.consumed_seq_no,
+            bid_history: bop_shared_state.bid_history.clone(),
+            use_item_history: bop_shared_state.use_item_history.clone(),
+            attack_target_history: bop_shared_state.attack_target_history.clone(),
+        }
+    }
+
+    // 相手から届いたスナップショットが、この state に上書きできるものか調べる
+    fn validate(&self, bop_shared_state: &BoPSharedState) -> Result<(), SnapshotError> {
+        if self.consumed_seq_no < bop_shared_state.consumed_seq_no {
+            return Err(SnapshotError::StaleSeqNo {
+                consumed_seq_no: bop_shared_state.consumed_seq_no,
+                snapshot_seq_no: self.consumed_seq_no,
+            });
+        }
+        if self.hash() != self.state_hash {
+            return Err(SnapshotError::HashMismatch);
+        }
+        let players_len = bop_shared_state.players.len();
+        for actual in [self.player_statuses.len(), self.own_item_lists.len()] {
+            if actual != players_len {
+                return Err(SnapshotError::PlayersLenMismatch {
+                    expected: players_len,
+                    actual,
+                });
+            }
+        }
+        if self.phase_index >= bop_shared_state.phases.len() {
+            return Err(SnapshotError::IndexOutOfRange("phase_index".to_string()));
+        }
+        if self
+            .initiatives_to_player_index
+            .iter()
+            .any(|player_index| *player_index >= players_len)
+        {
+            return Err(SnapshotError::IndexOutOfRange(
+                "initiatives_to_player_index".to_string(),
+            ));
+        }
+        if self.temporary_bid_history.iter().any(|history| {
+            history.player_index >= players_len || history.bid_item_index >= self.items_bid_on.len()
+        }) {
+            return Err(SnapshotError::IndexOutOfRange(
+                "temporary_bid_history".to_string(),
+            ));
+        }
+        let mut history_player_indexes = self
+            .bid_history
+            .iter()
+            .map(|history| history.player_index)
+            .chain(
+                self.use_item_history
+                    .iter()
+                    .map(|history| history.player_index),
+            )
+            .chain(
+                self.attack_target_history
+                    .iter()
+                    .map(|history| history.player_index),
+            );
+        if history_player_indexes.any(|player_index| player_index >= players_len) {
+            return Err(SnapshotError::IndexOutOfRange("history".to_string()));
+        }
+        Ok(())
+    }
+
+    fn hash(&self) -> u64 {
+        hash_of(&HashedState {
+            player_statuses: self.player_statuses.iter().collect(),
+            own_item_lists: self.own_item_lists.iter().collect(),
+            items_bid_on: &self.items_bid_on,
+            bid_scheduled_items: &self.bid_scheduled_items,
+            initiatives_to_player_index: &self.initiatives_to_player_index,
+            turn: self.turn,
+            phase_index: self.phase_index,
+        })
+    }
+
+    // 不正なスナップショットの場合は state を変更せずにエラーを返す
+    pub fn restore_to(&self, bop_shared_state: &mut BoPSharedState) -> Result<(), SnapshotError> {
+        self.validate(bop_shared_state)?;
+        for (player_index, player) in bop_shared_state.players.iter_mut().enumerate() {
+            player.player_status = self.player_statuses[player_index].clone();
+            player.own_item_list = self.own_item_lists[player_index].clone();
+        }
+        bop_shared_state.items_bid_on = self.items_bid_on.clone();
+        bop_shared_state.bid_scheduled_items = self.bid_scheduled_items.clone();
+        bop_shared_state.initiatives_to_player_index = self.initiatives_to_player_index.clone();
+        bop_shared_state.turn = self.turn;
+        bop_shared_state.phase_index = self.phase_index;
+        bop_shared_state.temporary_bid_history = self.temporary_bid_history.clone();
+        bop_shared_state.consumed_seq_no = self.consumed_seq_no;
+        bop_shared_state.bid_history = self.bid_history.clone();
+        bop_shared_state.use_item_history = self.use_item_history.clone();
+        bop_shared_state.attack_target_history = self.attack_target_history.clone();
+        let consumed_seq_no = self.consumed_seq_no;
+        bop_shared_state
+            .buffered_commands
+            .retain(|buffered| buffered.seq_no().unwrap() > consumed_seq_no);
+        bop_shared_state.ready_bid_input();
+        Ok(())
+    }
+}
+
+pub fn state_hash(bop_shared_state: &BoPSharedState) -> u64 {
+    hash_of(&HashedState {
+        player_statuses: bop_shared_state
+            .players
+            .iter()
+            .map(|player| &player.player_status)
+            .collect(),
+        own_item_lists: bop_shared_state
+            .players
+            .iter()
+            .map(|player| &player.own_item_list)
+            .collect(),
+        items_bid_on: &bop_shared_state.items_bid_on,
+        bid_scheduled_items: &bop_shared_state.bid_scheduled_items,
+        initiatives_to_player_index: &bop_shared_state.initiatives_to_player_index,
+        turn: bop_shared_state.turn,
+        phase_index: bop_shared_state.phase_index,
+    })
+}
+
+fn hash_of(hashed_state: &HashedState) -> u64 {
+    fnv1a_hash(&serde_json::to_vec(hashed_state).unwrap())
+}
+
+// std の DefaultHasher は Rust のバージョン間で結果が保証されないので FNV-1a を使う
+fn fnv1a_hash(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bop_core::rules::apply;
+    use crate::bop_core::rules::tests::{
+        advance_to_phase, create_attack, play_randomly, start_game,
+    };
+    use crate::bop_core::state::phase::PhaseType;
+    use crate::bop_core::state::save_data::SaveData;
+
+    #[test]
+    fn state_hash_is_stable_through_save_and_load() {
+        let mut state = start_game(1, 2);
+        play_randomly(&mut state, 1, 30);
+        let loaded_state = SaveData::from_json(&SaveData::new(&state).to_json())
+            .unwrap()
+            .bop_shared_state;
+        assert_eq!(state_hash(&loaded_state), state_hash(&state));
+    }
+
+    #[test]
+    fn state_hash_changes_after_command() {
+        let mut state = start_game(1, 2);
+        let player_index = advance_to_phase(&mut state, PhaseType::AttackTarget);
+        let hash_before = state_hash(&state);
+        let command = create_attack(
+            &state,
+            player_index,
+            state.opponent_player_index(player_index),
+        );
+        apply(&mut state, command).unwrap();
+        assert_ne!(state_hash(&state), hash_before);
+    }
+
+    #[test]
+    fn restore_overwrites_with_host_state() {
+        let mut host_state = start_game(1, 2);
+        let mut guest_state = host_state.clone();
+        play_randomly(&mut host_state, 1, 10);
+        let snapshot = GameplaySnapshot::from_state(&host_state);
+        assert_eq!(snapshot.restore_to(&mut guest_state), Ok(()));
+        assert_eq!(state_hash(&guest_state), state_hash(&host_state));
+        assert_eq!(guest_state.consumed_seq_no, host_state.consumed_seq_no);
+    }
+
+    #[test]
+    fn restore_rejects_wrong_hash_and_stale_seq_no() {
+        let mut host_state = start_game(1, 2);
+        play_randomly(&mut host_state, 1, 10);
+        let mut guest_state = host_state.clone();
+        let guest_hash = state_hash(&guest_state);
+
+        let mut snapshot = GameplaySnapshot::from_state(&host_state);
+        snapshot.player_statuses[0].current_hp += 1;
+        assert_eq!(
+            snapshot.restore_to(&mut guest_state),
+            Err(SnapshotError::HashMismatch)
+        );
+
+        let mut snapshot = GameplaySnapshot::from_state(&host_state);
+        snapshot.consumed_seq_no -= 1;
+        assert_eq!(
+            snapshot.restore_to(&mut guest_state),
+            Err(SnapshotError::StaleSeqNo {
+                consumed_seq_no: host_state.consumed_seq_no,
+                snapshot_seq_no: host_state.consumed_seq_no - 1,
+            })
+        );
+        assert_eq!(state_hash(&guest_state), guest_hash);
+    }
+}
@@ -1,10 +1,20 @@
-use crate::bop::state::message::GameRuleMessage;
+use crate::bop::render_cpu_thinking;
+use crate::bop::save::SavedSession;
+use crate::bop_core::mechanism::item_set::ItemSet;
+use crate::bop_core::replay::{Replay, ReplayPlayer};
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+use crate::bop_core::state::message::GameRuleMessage;
+use crate::bop_core::state::snapshot::STATE_HASH_INTERVAL;
 use crate::engine::application_types::StateType::BoPShared;
-use crate::features::animation::Animation;
+use crate::features::cpu_worker::{
+    get_fallback_search_config, get_search_config, CPUThinking, CPUWorkerWrapper,
+    CPU_THINKING_GRACE_MS,
+};
 use crate::features::websocket::{ChannelMessage, MessageType, WebSocketWrapper};
 use input::Input;
 use scene::Scene;
 use state::State;
+use std::sync::Arc;
 use wasm_bindgen::prelude::wasm_bindgen;
 use wasm_bindgen_test::console_log;
 
@@ -17,6 +27,7 @@ pub mod state;
 pub struct Engine {
     pub(crate) scenes: Vec<Scene>,
     pub(crate) web_socket_wrapper: WebSocketWrapper,
+    pub(crate) cpu_worker_wrapper: CPUWorkerWrapper,
     pub(crate) shared_state: State,
 }
 
@@ -31,11 +42,21 @@ impl Engine {
             scenes,
             shared_state,
             web_socket_wrapper,
+            cpu_worker_wrapper: CPUWorkerWrapper::new(),
         }
     }
 
     pub fn keydown(&mut self, key: String) {
         let input = Input::from(key);
+        if self.shared_state.replay_player.is_some() {
+            // リプレイ再生中は左右キーで1手ずつ戻す・進める
+            match input {
+                Input::ArrowRight => self.replay_step_forward(),
+                Input::ArrowLeft => self.replay_step_back(),
+                _ => {}
+            }
+            return;
+        }
         if self.shared_state.references.borrow_mut().has_block_message {
             if !self
                 .shared_state
@@ -45,7 +66,7 @@ impl Engine {
             {
                 self.shared_state.elements.message.hide();
             }
-            (*self.shared_state.references.borrow_mut()).has_block_message = false;
+            self.shared_state.references.borrow_mut().has_block_message = false;
             return;
         }
         if self.has_animation_blocking_scene_update() {
@@ -53,6 +74,11 @@ impl Engine {
             return;
         }
         let scene_index = self.shared_state.primitives.scene_index;
+        if scene_index == 0 {
+            // タイトルに戻ったら、CPUの思考は続けても使われない
+            self.cancel_cpu_thinking();
+            self.shared_state.has_cpu_task = false;
+        }
         let consume_func = self.scenes[scene_index].consume_func;
         consume_func(&mut self.scenes[scene_index], &mut self.shared_state, input);
         if !self.has_animation_blocking_scene_update() {
@@ -73,6 +99,138 @@ impl Engine {
         }
     }
 
+    // 対戦中の state を localStorage に保存する
+    // メッセージを処理するたびに自動で呼ばれる
+    pub fn save_game(&self) {
+        if self.shared_state.replay_player.is_some() {
+            return;
+        }
+        SavedSession::save(
+            &self.shared_state,
+            &self.web_socket_wrapper.state.borrow_mut().channel_name,
+        );
+    }
+
+    // 保存された対戦があれば復元し、同じチャンネルに接続し直して対戦画面に移る
+    // 届いていなかったメッセージは、接続時の Join で相手に再送信を依頼する
+    pub fn load_game(&mut self) -> bool {
+        let Some((saved_session, bop_shared_state)) = SavedSession::load() else {
+            return false;
+        };
+        console_log!(
+            "load game {} {} {}",
+            saved_session.user_name,
+            saved_session.channel_name,
+            bop_shared_state.consumed_seq_no
+        );
+        self.web_socket_wrapper.ws.close().unwrap();
+        self.web_socket_wrapper = WebSocketWrapper::new(
+            saved_session.user_name.to_owned(),
+            saved_session.channel_name.to_owned(),
+        );
+        self.shared_state.user_name = saved_session.user_name;
+        self.shared_state.is_matched = saved_session.is_matched;
+        self.shared_state.is_request_matching = false;
+        self.shared_state.state_type = BoPShared(Box::new(bop_shared_state));
+        self.shared_state.primitives.requested_scene_index = 1;
+        true
+    }
+
+    // 現在の対戦をリプレイとして JSON で書き出す
+    // 対戦の状態がなければ空文字列を返す
+    pub fn export_replay(&self) -> String {
+        let BoPShared(bop_shared_state) = &self.shared_state.state_type else {
+            console_log!("replay is not exported: no game state");
+            return String::new();
+        };
+        Replay::from_state(bop_shared_state).to_json()
+    }
+
+    // 対戦のログを JSON で書き出す
+    // 対戦の状態がなければ空文字列を返す
+    pub fn export_game_logs(&self) -> String {
+        let BoPShared(bop_shared_state) = &self.shared_state.state_type else {
+            console_log!("game logs are not exported: no game state");
+            return String::new();
+        };
+        serde_json::to_string(&bop_shared_state.game_logs).unwrap()
+    }
+
+    // リプレイを読み込んで、対戦画面で初期状態から再生する
+    pub fn load_replay(&mut self, json: String) -> bool {
+        let replay_player = Replay::from_json(&json).and_then(ReplayPlayer::new);
+        match replay_player {
+            Ok(replay_player) => {
+                self.shared_state.replay_player = Some(replay_player);
+                self.shared_state.is_matched = false;
+                self.shared_state.primitives.requested_scene_index = 1;
+                self.sync_replay_state();
+                true
+            }
+            Err(error) => {
+                console_log!("replay is not loaded: {}", error);
+                false
+            }
+        }
+    }
+
+    // CPU対戦で使うアイテムセットを JSON から読み込む
+    // オンライン対戦では使わず、マッチングを始める時に同梱のアイテムセットに戻す
+    pub fn load_item_set(&mut self, json: String) -> bool {
+        if self.shared_state.is_request_matching || self.shared_state.is_matched {
+            console_log!("item set is not loaded: online match uses the default item set");
+            return false;
+        }
+        match ItemSet::from_json(&json) {
+            Ok(item_set) => {
+                if let BoPShared(bop_shared_state) = &mut self.shared_state.state_type {
+                    bop_shared_state.update_item_set(Arc::new(item_set));
+                }
+                true
+            }
+            Err(error) => {
+                console_log!("item set is not loaded: {}", error);
+                false
+            }
+        }
+    }
+
+    pub fn replay_step_forward(&mut self) {
+        if let Some(replay_player) = &mut self.shared_state.replay_player {
+            if let Err(error) = replay_player.step_forward() {
+                console_log!("replay command is rejected: {}", error);
+            }
+        }
+        self.sync_replay_state();
+    }
+
+    pub fn replay_step_back(&mut self) {
+        if let Some(replay_player) = &mut self.shared_state.replay_player {
+            if let Err(error) = replay_player.step_back() {
+                console_log!("replay command is rejected: {}", error);
+            }
+        }
+        self.sync_replay_state();
+    }
+
+    pub fn close_replay(&mut self) {
+        self.shared_state.replay_player = None;
+        self.shared_state.state_type = BoPShared(Box::new(BoPSharedState::init()));
+        self.shared_state.primitives.requested_scene_index = 0;
+    }
+
+    // リプレイの state を画面に反映する
+    fn sync_replay_state(&mut self) {
+        if let Some(replay_player) = &self.shared_state.replay_player {
+            self.shared_state.state_type =
+                BoPShared(Box::new(replay_player.bop_shared_state.clone()));
+        }
+        for scene in self.scenes.iter_mut() {
+            let on_update_state_func = scene.on_update_state_func;
+            on_update_state_func(scene, &mut self.shared_state);
+        }
+    }
+
     fn on_scene_update(&mut self) {
         console_log!(
             "scene_updated {:?}",
@@ -112,13 +270,16 @@ impl Engine {
         self.shared_state
             .interrupt_animations
             .iter()
-            .find(|animation| animation.get(0).unwrap().block_scene_update)
-            .is_some()
+            .any(|animation| animation.first().unwrap().block_scene_update)
     }
 
     fn receive_channel_message(&mut self, channel_message: &mut ChannelMessage) {
         let message = channel_message.message.to_owned();
         console_log!("receive_channel_message {}", message);
+        if self.shared_state.replay_player.is_some() {
+            // リプレイ再生中は対戦のメッセージを処理しない
+            return;
+        }
         if !self.shared_state.is_request_matching {
             channel_message.message = message;
             self.shared_state.consume_channel_message(channel_message);
@@ -126,28 +287,33 @@ impl Engine {
                 let on_update_state_func = scene.on_update_state_func;
                 on_update_state_func(scene, &mut self.shared_state);
             }
+            self.save_game();
             return;
         }
         if let Ok(special_message) = serde_json::from_str::<ChannelMessage>(&message) {
             match special_message.message_type {
-                MessageType::MatchRequest => {
-                    if special_message.user_name != self.shared_state.user_name {
-                        let to_send_message = serde_json::to_string(&ChannelMessage {
-                            user_name: self.shared_state.user_name.to_string(),
-                            message_type: MessageType::MatchResponse,
-                            message: serde_json::to_string(&GameRuleMessage::from_state(
-                                &mut self.shared_state,
-                                special_message.user_name.clone(),
-                            ))
-                            .unwrap(),
-                        })
-                        .unwrap();
-                        console_log!("to_send_message {}", to_send_message);
-                        self.shared_state
-                            .to_send_channel_messages
-                            .push(to_send_message);
-                    }
-                    return;
+                MessageType::MatchRequest
+                    if special_message.user_name != self.shared_state.user_name =>
+                {
+                    let BoPShared(bop_shared_state) = &self.shared_state.state_type else {
+                        console_log!("match request is ignored: no game state");
+                        return;
+                    };
+                    let game_rule_message = GameRuleMessage::new(
+                        bop_shared_state,
+                        self.shared_state.user_name.to_string(),
+                        special_message.user_name.clone(),
+                    );
+                    let to_send_message = serde_json::to_string(&ChannelMessage {
+                        user_name: self.shared_state.user_name.to_string(),
+                        message_type: MessageType::MatchResponse,
+                        message: serde_json::to_string(&game_rule_message).unwrap(),
+                    })
+                    .unwrap();
+                    console_log!("to_send_message {}", to_send_message);
+                    self.shared_state
+                        .to_send_channel_messages
+                        .push(to_send_message);
                 }
                 MessageType::MatchResponse => {
                     if let Ok(message) =
@@ -157,14 +323,28 @@ impl Engine {
                             && message.guest_player_name == self.shared_state.user_name
                         {
                             if let BoPShared(bop_shared_state) = &mut self.shared_state.state_type {
-                                bop_shared_state.update_item_list(message.item_kind_list);
+                                if let Err(error) =
+                                    bop_shared_state.apply_game_rule_message(&message)
+                                {
+                                    // ルールや山札が同じにならないと同期がずれるので、マッチングを中止する
+                                    console_log!("match rejected: {}", error);
+                                    return;
+                                }
                                 bop_shared_state.own_player_index = message.guest_player_index;
+                                bop_shared_state.state_hash_interval = STATE_HASH_INTERVAL;
                                 console_log!("you are guest.");
                             }
                         } else if message.host_player_name == self.shared_state.user_name {
                             if let BoPShared(bop_shared_state) = &mut self.shared_state.state_type {
-                                bop_shared_state.update_item_list(message.item_kind_list);
+                                if let Err(error) =
+                                    bop_shared_state.apply_game_rule_message(&message)
+                                {
+                                    // ルールや山札が同じにならないと同期がずれるので、マッチングを中止する
+                                    console_log!("match rejected: {}", error);
+                                    return;
+                                }
                                 bop_shared_state.own_player_index = message.host_player_index;
+                                bop_shared_state.state_hash_interval = STATE_HASH_INTERVAL;
                                 console_log!("you are host.");
                             }
                         } else {
@@ -225,7 +405,7 @@ impl Engine {
             .iter_mut()
             .enumerate()
         {
-            let func = animation.get(0).unwrap().animation_func;
+            let func = animation.first().unwrap().animation_func;
             let result = func(
                 animation.get_mut(0).unwrap(),
                 self.shared_state.references.clone(),
@@ -253,10 +433,7 @@ impl Engine {
             .shared_state
             .interrupt_animations
             .iter()
-            .filter(|animation| animation.get(0).unwrap().block_scene_update)
-            .collect::<Vec<&Vec<Animation>>>()
-            .len()
-            == 0
+            .all(|animation| !animation.first().unwrap().block_scene_update)
         {
             if self.shared_state.primitives.scene_index
                 != self.shared_state.primitives.requested_scene_index
@@ -275,24 +452,77 @@ impl Engine {
         }
         if let State {
             state_type: BoPShared(bop_shared_state),
+            simple_binders,
             ..
         } = &mut self.shared_state
         {
-            for n in 0..bop_shared_state.simple_binders.len() {
-                // 自己参照を含んでいるので一旦 SimpleBinder の clone をして、戻す
-                let mut binder = bop_shared_state.simple_binders[n].clone();
-                let binder = binder.sync(bop_shared_state);
-                bop_shared_state.simple_binders[n] = binder.clone();
+            for binder in simple_binders.iter_mut() {
+                binder.sync(bop_shared_state);
             }
         }
-        if self.shared_state.has_cpu_task {
+        // Worker から届いた探索の途中経過と結果を処理する
+        loop {
+            let response = {
+                let mut responses = self.cpu_worker_wrapper.responses.borrow_mut();
+                if responses.is_empty() {
+                    break;
+                }
+                responses.remove(0)
+            };
+            self.shared_state.consume_cpu_search_response(response);
+        }
+        if self.shared_state.has_cpu_task && self.shared_state.cpu_thinking.is_none() {
             if self.shared_state.cpu_task_start_step == -1.0 {
                 self.shared_state.cpu_task_start_step = step;
-            } else {
-                if self.shared_state.cpu_task_start_step + 50.0 < step {
-                    self.shared_state.start_cpu_task();
-                }
+            } else if self.shared_state.cpu_task_start_step + 50.0 < step {
+                self.start_cpu_thinking(step);
             }
         }
+        // 思考時間を大きく過ぎても結果が届かなければ、探索を中断して少ない回数で決め直す
+        if matches!(&self.shared_state.cpu_thinking, Some(cpu_thinking) if cpu_thinking.deadline_step < step)
+        {
+            console_log!("cpu thinking is timed out");
+            self.cancel_cpu_thinking();
+            self.shared_state
+                .start_cpu_task(&get_fallback_search_config());
+        }
+    }
+
+    // CPUの入力の探索を Worker に依頼する。Worker が使えなければメインスレッドで探索する
+    fn start_cpu_thinking(&mut self, step: f64) {
+        let Some(player_index) = self.shared_state.get_cpu_player_index() else {
+            self.shared_state.has_cpu_task = false;
+            return;
+        };
+        if let BoPShared(bop_shared_state) = &self.shared_state.state_type {
+            let search_config = get_search_config(bop_shared_state);
+            let Some(request_id) = self
+                .cpu_worker_wrapper
+                .request(bop_shared_state, player_index)
+            else {
+                self.shared_state.start_cpu_task(&search_config);
+                return;
+            };
+            self.shared_state.cpu_thinking = Some(CPUThinking {
+                request_id,
+                player_index,
+                consumed_seq_no: bop_shared_state.consumed_seq_no,
+                deadline_step: step
+                    + search_config.time_limit_ms.unwrap_or(0.0)
+                    + CPU_THINKING_GRACE_MS,
+            });
+            render_cpu_thinking(Some(format!(
+                "{}さんが考えています…",
+                bop_shared_state.players[player_index].player_name
+            )));
+        }
+    }
+
+    // 探索中の Worker を止める。対戦を抜けた場合や、時間内に終わらなかった場合に使う
+    fn cancel_cpu_thinking(&mut self) {
+        if self.shared_state.cpu_thinking.take().is_some() {
+            self.cpu_worker_wrapper.cancel();
+            render_cpu_thinking(None);
+        }
     }
 }
@@ -1,9 +1,9 @@
 use crate::bop::scenes::game_main::GameMainState;
 use crate::bop::scenes::title::TitleState;
-use crate::bop::state::bop_shared_state::BoPSharedState;
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
 
 pub enum StateType {
-    BoPShared(BoPSharedState),
+    BoPShared(Box<BoPSharedState>),
     TBDStateType,
 }
 
@@ -17,6 +17,7 @@ impl Input {
         match key.as_str() {
             "a" => Input::Enter,
             "z" => Input::Cancel,
+            "x" => Input::Context,
             "ArrowRight" => Input::ArrowRight,
             "ArrowLeft" => Input::ArrowLeft,
             "ArrowUp" => Input::ArrowUp,
@@ -1,5 +1,8 @@
+use crate::bop_core::replay::ReplayPlayer;
 use crate::engine::application_types::StateType;
 use crate::features::animation::Animation;
+use crate::features::cpu_worker::CPUThinking;
+use crate::svg::simple_binder::SimpleBinder;
 use crate::svg::SharedElements;
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -20,6 +23,7 @@ pub struct State {
     pub user_name: String,
     pub to_send_channel_messages: Vec<String>,
     pub state_type: StateType,
+    pub simple_binders: Vec<SimpleBinder>,
     pub elements: SharedElements,
     pub interrupt_animations: Vec<Vec<Animation>>,
     pub primitives: Primitives,
@@ -29,4 +33,8 @@ pub struct State {
     pub keep_connection_request: bool,
     pub has_cpu_task: bool,
     pub cpu_task_start_step: f64,
+    // Worker でCPUの入力を探索している間だけ存在する
+    pub cpu_thinking: Option<CPUThinking>,
+    // リプレイ再生中のみ存在する
+    pub replay_player: Option<ReplayPlayer>,
 }
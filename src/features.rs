@@ -1,3 +1,5 @@
 pub mod animation;
+pub mod cpu_worker;
 pub mod emote;
+pub mod storage;
 pub mod websocket;
@@ -50,7 +50,7 @@ impl Animation {
                 let gap_sin = (gap / 250_f64).sin() / 2_f64 + 0.5;
                 for element in animation.elements.iter() {
                     element
-                        .set_attribute("fill-opacity", &*gap_sin.to_string())
+                        .set_attribute("fill-opacity", &gap_sin.to_string())
                         .unwrap();
                 }
 
@@ -84,18 +84,18 @@ impl Animation {
                 if gap < half_span {
                     animation
                         .elements
-                        .get(0)
+                        .first()
                         .unwrap()
-                        .set_attribute("fill-opacity", &*(gap / half_span).to_string())
+                        .set_attribute("fill-opacity", &(gap / half_span).to_string())
                         .expect("TODO: panic message");
                 } else {
                     animation
                         .elements
-                        .get(0)
+                        .first()
                         .unwrap()
                         .set_attribute(
                             "fill-opacity",
-                            &*(1.0 - ((gap - half_span) / half_span)).to_string(),
+                            &(1.0 - ((gap - half_span) / half_span)).to_string(),
                         )
                         .unwrap();
                 }
@@ -107,7 +107,7 @@ impl Animation {
                     console_log!("animation end");
                     animation
                         .elements
-                        .get(0)
+                        .first()
                         .unwrap()
                         .set_attribute("fill-opacity", "0")
                         .unwrap();
@@ -147,7 +147,7 @@ impl Animation {
                     .unwrap();
                 animation.elements[1].set_inner_html(&animation.messages.remove(0));
                 animation.elements[2].set_inner_html("");
-                return false;
+                false
             },
         }
     }
@@ -196,8 +196,8 @@ impl Animation {
                 animation.elements[3]
                     .set_attribute("display", display)
                     .unwrap();
-                (*references.borrow_mut()).has_continuous_message = has_continuous_message;
-                return false;
+                references.borrow_mut().has_continuous_message = has_continuous_message;
+                false
             },
         }
     }
@@ -237,16 +237,16 @@ impl Animation {
                 let character_direction_element = &animation.elements[1];
                 let wrapper_element = &animation.elements[2];
                 character_cursor_element
-                    .set_attribute("x", &*step_x.to_string())
+                    .set_attribute("x", &step_x.to_string())
                     .unwrap();
                 character_direction_element
-                    .set_attribute("x", &*step_x.to_string())
+                    .set_attribute("x", &step_x.to_string())
                     .unwrap();
                 character_cursor_element
-                    .set_attribute("y", &*step_y.to_string())
+                    .set_attribute("y", &step_y.to_string())
                     .unwrap();
                 character_direction_element
-                    .set_attribute("y", &*(step_y + 35.0).to_string())
+                    .set_attribute("y", &(step_y + 35.0).to_string())
                     .unwrap();
                 let wrapper_translate_x = 360.0 - step_x;
                 let wrapper_translate_y = 280.0 - step_y;
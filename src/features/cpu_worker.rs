@@ -0,0 +1,220 @@
+use crate::bop_core::cpu_player::mcts::{search_until, SearchConfig};
+use crate::bop_core::cpu_player::CPUPlayer;
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_test::console_log;
+use web_sys::{MessageEvent, Worker, WorkerOptions, WorkerType};
+
+// index.html と同じ場所に置く Worker のスクリプト
+const CPU_WORKER_SCRIPT_URL: &str = "./cpu_worker.js";
+// 思考中の表示を更新するために、何回の反復ごとに途中経過を送るか
+const PROGRESS_INTERVAL: usize = 500;
+// Worker の起動や state の受け渡しにかかる時間の見込み。思考時間にこれを足しても結果が届かなければ中断する
+pub const CPU_THINKING_GRACE_MS: f64 = 3000.0;
+// 中断した場合に、メインスレッドで決め直すときの探索の上限
+const CPU_FALLBACK_ITERATIONS: usize = 300;
+const CPU_FALLBACK_TIME_LIMIT_MS: f64 = 300.0;
+
+// Worker に渡す探索の依頼。state は丸ごと複製して送る
+#[derive(Serialize, Deserialize)]
+pub struct CPUSearchRequest {
+    pub request_id: u32,
+    pub player_index: usize,
+    pub bop_shared_state: BoPSharedState,
+}
+
+// Worker から届く途中経過と結果。message が None の間は途中経過
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CPUSearchResponse {
+    pub request_id: u32,
+    pub iterations: usize,
+    pub message: Option<String>,
+}
+
+// Worker で探索中の依頼
+pub struct CPUThinking {
+    pub request_id: u32,
+    pub player_index: usize,
+    // 依頼した時点のシーケンス番号。結果が届くまでに state が進んでいれば結果を捨てる
+    pub consumed_seq_no: usize,
+    // この時刻を過ぎても結果が届かなければ探索を中断する
+    pub deadline_step: f64,
+}
+
+// 探索で使う設定。思考時間はブラウザの時計で測る
+pub fn get_search_config(bop_shared_state: &BoPSharedState) -> SearchConfig {
+    SearchConfig {
+        now_ms: js_sys::Date::now,
+        ..bop_shared_state.cpu_profile.get_search_config()
+    }
+}
+
+// Worker が応答しない場合に、メインスレッドで短時間だけ探索する設定
+pub fn get_fallback_search_config() -> SearchConfig {
+    SearchConfig {
+        max_iterations: CPU_FALLBACK_ITERATIONS,
+        time_limit_ms: Some(CPU_FALLBACK_TIME_LIMIT_MS),
+        now_ms: js_sys::Date::now,
+        ..SearchConfig::default()
+    }
+}
+
+// 依頼された入力を探索して、送信するメッセージを返す（Worker 側で呼ばれる）
+pub fn search_in_worker(request: &str, on_progress: &js_sys::Function) -> String {
+    let request = serde_json::from_str::<CPUSearchRequest>(request).unwrap();
+    let mut cpu_player = CPUPlayer::new(&request.bop_shared_state);
+    cpu_player.bop_shared_state.own_player_index = request.player_index;
+    cpu_player.bop_shared_state.has_cpu = false;
+    let search_result = search_until(
+        &cpu_player.bop_shared_state,
+        request.player_index,
+        &get_search_config(&request.bop_shared_state),
+        &mut |iterations| {
+            if iterations % PROGRESS_INTERVAL == 0 {
+                let progress = CPUSearchResponse {
+                    request_id: request.request_id,
+                    iterations,
+                    message: None,
+                };
+                let _ = on_progress.call1(
+                    &JsValue::NULL,
+                    &JsValue::from_str(&serde_json::to_string(&progress).unwrap()),
+                );
+            }
+            false
+        },
+    );
+    serde_json::to_string(&CPUSearchResponse {
+        request_id: request.request_id,
+        iterations: search_result.iterations,
+        message: Some(cpu_player.create_cpu_message(search_result.input)),
+    })
+    .unwrap()
+}
+
+// メインスレッド側から Worker を操作する
+// Worker を作れないブラウザでは worker が None になり、呼び出し側がメインスレッドで探索する
+pub struct CPUWorkerWrapper {
+    worker: Option<Worker>,
+    pub responses: Rc<RefCell<Vec<CPUSearchResponse>>>,
+    last_request_id: u32,
+}
+
+impl CPUWorkerWrapper {
+    pub fn new() -> CPUWorkerWrapper {
+        let responses = Rc::new(RefCell::new(vec![]));
+        CPUWorkerWrapper {
+            worker: CPUWorkerWrapper::create_worker(responses.clone()),
+            responses,
+            last_request_id: 0,
+        }
+    }
+
+    fn create_worker(responses: Rc<RefCell<Vec<CPUSearchResponse>>>) -> Option<Worker> {
+        let options = WorkerOptions::new();
+        options.set_type(WorkerType::Module);
+        let worker = match Worker::new_with_options(CPU_WORKER_SCRIPT_URL, &options) {
+            Ok(worker) => worker,
+            Err(error) => {
+                console_log!("cpu worker is not available: {:?}", error);
+                return None;
+            }
+        };
+        let onmessage_callback = Closure::<dyn FnMut(_)>::new(move |e: MessageEvent| {
+            if let Some(raw_text) = e.data().as_string() {
+                if let Ok(response) = serde_json::from_str::<CPUSearchResponse>(&raw_text) {
+                    responses.borrow_mut().push(response);
+                }
+            }
+        });
+        worker.set_onmessage(Some(onmessage_callback.as_ref().unchecked_ref()));
+        onmessage_callback.forget();
+        Some(worker)
+    }
+
+    // 探索を依頼して依頼の番号を返す。Worker が使えなければ None
+    pub fn request(
+        &mut self,
+        bop_shared_state: &BoPSharedState,
+        player_index: usize,
+    ) -> Option<u32> {
+        let worker = self.worker.as_ref()?;
+        self.last_request_id += 1;
+        let request = CPUSearchRequest {
+            request_id: self.last_request_id,
+            player_index,
+            bop_shared_state: bop_shared_state.clone(),
+        };
+        worker
+            .post_message(&JsValue::from_str(
+                &serde_json::to_string(&request).unwrap(),
+            ))
+            .ok()?;
+        Some(self.last_request_id)
+    }
+
+    // 探索中の Worker は止められないので、破棄して作り直す
+    // 届いていない途中経過や結果も捨てる
+    pub fn cancel(&mut self) {
+        if let Some(worker) = self.worker.take() {
+            worker.terminate();
+        }
+        self.responses.borrow_mut().clear();
+        self.worker = CPUWorkerWrapper::create_worker(self.responses.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bop_core::rules::tests::{play_randomly, start_game};
+    use crate::bop_core::state::snapshot::state_hash;
+
+    #[test]
+    fn search_request_round_trips() {
+        let mut state = start_game(1, 3);
+        play_randomly(&mut state, 1, 30);
+        let request = CPUSearchRequest {
+            request_id: 7,
+            player_index: 2,
+            bop_shared_state: state,
+        };
+        let json = serde_json::to_string(&request).unwrap();
+        let loaded_request = serde_json::from_str::<CPUSearchRequest>(&json).unwrap();
+        assert_eq!(loaded_request.request_id, request.request_id);
+        assert_eq!(loaded_request.player_index, request.player_index);
+        // Worker 側で同じ局面から探索できるように、state は入力待ちの位置まで一致する
+        let (state, loaded_state) = (&request.bop_shared_state, &loaded_request.bop_shared_state);
+        assert_eq!(state_hash(loaded_state), state_hash(state));
+        assert_eq!(loaded_state.phase_index, state.phase_index);
+        assert_eq!(loaded_state.consumed_seq_no, state.consumed_seq_no);
+        assert_eq!(loaded_state.cpu_profile, state.cpu_profile);
+    }
+
+    #[test]
+    fn search_response_round_trips() {
+        let responses = [
+            CPUSearchResponse {
+                request_id: 3,
+                iterations: 500,
+                message: None,
+            },
+            CPUSearchResponse {
+                request_id: 3,
+                iterations: 1234,
+                message: Some("{\"player_index\":1}".to_string()),
+            },
+        ];
+        for response in responses {
+            let json = serde_json::to_string(&response).unwrap();
+            let loaded_response = serde_json::from_str::<CPUSearchResponse>(&json).unwrap();
+            assert_eq!(loaded_response.request_id, response.request_id);
+            assert_eq!(loaded_response.iterations, response.iterations);
+            assert_eq!(loaded_response.message, response.message);
+        }
+    }
+}
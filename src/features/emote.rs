@@ -161,20 +161,20 @@ impl Animation {
                     if gap > span * 0.95 {
                         let opacity = 1.0 - (gap - span * 0.95) / (span * 0.05);
                         emote_background_arrow
-                            .set_attribute("fill-opacity", &*opacity.to_string())
+                            .set_attribute("fill-opacity", &opacity.to_string())
                             .unwrap();
                         emote_background
-                            .set_attribute("fill-opacity", &*opacity.to_string())
+                            .set_attribute("fill-opacity", &opacity.to_string())
                             .unwrap();
                         emote_element
-                            .set_attribute("fill-opacity", &*opacity.to_string())
+                            .set_attribute("fill-opacity", &opacity.to_string())
                             .unwrap();
                     }
                     parent_element
-                        .append_child(&*emote_background_arrow)
+                        .append_child(&emote_background_arrow)
                         .unwrap();
-                    parent_element.append_child(&*emote_background).unwrap();
-                    parent_element.append_child(&*emote_element).unwrap();
+                    parent_element.append_child(&emote_background).unwrap();
+                    parent_element.append_child(&emote_element).unwrap();
                     false
                 } else {
                     true
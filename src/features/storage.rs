@@ -0,0 +1,23 @@
+use web_sys::Storage;
+
+// ブラウザの localStorage の薄いラッパー
+// プライベートブラウジングなどで使えない場合は何もしない
+fn local_storage() -> Option<Storage> {
+    web_sys::window().and_then(|window| window.local_storage().ok().flatten())
+}
+
+pub fn get_item(key: &str) -> Option<String> {
+    local_storage().and_then(|storage| storage.get_item(key).ok().flatten())
+}
+
+pub fn set_item(key: &str, value: &str) {
+    if let Some(storage) = local_storage() {
+        let _ = storage.set_item(key, value);
+    }
+}
+
+pub fn remove_item(key: &str) {
+    if let Some(storage) = local_storage() {
+        let _ = storage.remove_item(key);
+    }
+}
@@ -78,10 +78,10 @@ impl WebSocketWrapper {
                 .send_with_str(&serde_json::to_string(&channel_user).unwrap())
                 .unwrap();
             let mut state_clone = state_clone.borrow_mut();
-            (*state_clone).is_opened = true;
-            (*state_clone).has_connection_request = false;
-            (*state_clone).is_closed = false;
-            (*state_clone).is_joined = false;
+            state_clone.is_opened = true;
+            state_clone.has_connection_request = false;
+            state_clone.is_closed = false;
+            state_clone.is_joined = false;
         });
         self.ws
             .set_onopen(Some(onopen_callback.as_ref().unchecked_ref()));
@@ -105,7 +105,7 @@ impl WebSocketWrapper {
             console_log!("websocket connection closed.");
 
             let mut state_clone = state_clone.borrow_mut();
-            (*state_clone).is_closed = true;
+            state_clone.is_closed = true;
         });
         self.ws
             .set_onclose(Some(onclose_callback.as_ref().unchecked_ref()));
@@ -124,28 +124,6 @@ impl WebSocketWrapper {
         self.state.borrow_mut().has_connection_request
     }
 
-    pub fn join(&mut self) {
-        let join_message = ChannelMessage {
-            user_name: self.user_name.to_owned(),
-            message_type: MessageType::Join,
-            message: "join".to_string(),
-        };
-        self.ws
-            .send_with_str(&serde_json::to_string(&join_message).unwrap())
-            .unwrap();
-        self.state.borrow_mut().is_joined = true;
-    }
-    pub fn left(&mut self) {
-        let left_message = ChannelMessage {
-            user_name: self.user_name.to_owned(),
-            message_type: MessageType::Left,
-            message: "left".to_string(),
-        };
-        self.ws
-            .send_with_str(&serde_json::to_string(&left_message).unwrap())
-            .unwrap();
-        self.state.borrow_mut().is_joined = false;
-    }
     pub fn send_message(&mut self, message: String) {
         if !self.is_ready() {
             console_log!("websocket connection does not ready...");
@@ -1,13 +1,30 @@
+#[cfg(feature = "web")]
 mod bop;
+pub mod bop_core;
+#[cfg(feature = "web")]
 pub mod engine;
+#[cfg(feature = "web")]
 mod features;
+#[cfg(feature = "web")]
 mod svg;
+#[cfg(feature = "web")]
 mod utils;
 
+#[cfg(feature = "web")]
 use crate::engine::Engine;
+#[cfg(feature = "web")]
 use wasm_bindgen::prelude::wasm_bindgen;
 
+#[cfg(feature = "web")]
 #[wasm_bindgen]
 pub fn create_rpg_engine() -> Engine {
+    utils::set_panic_hook();
     bop::mount()
 }
+
+// CPUの探索を Web Worker で行う（cpu_worker.js から呼ばれる）
+#[cfg(feature = "web")]
+#[wasm_bindgen]
+pub fn search_cpu_input(request: String, on_progress: js_sys::Function) -> String {
+    features::cpu_worker::search_in_worker(&request, &on_progress)
+}
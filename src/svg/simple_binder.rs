@@ -1,4 +1,4 @@
-use crate::bop::state::bop_shared_state::BoPSharedState;
+use crate::bop_core::state::bop_shared_state::BoPSharedState;
 use web_sys::Element;
 
 #[derive(Clone)]
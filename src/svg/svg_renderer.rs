@@ -74,7 +74,7 @@ impl Cursor {
         match self.cursor_type {
             CursorType::Default => {
                 self.element
-                    .set_attribute("y", &*self.default_y.to_string())
+                    .set_attribute("y", &self.default_y.to_string())
                     .unwrap();
             }
         }
@@ -215,7 +215,7 @@ impl SvgRenderer {
             .set_attribute("id", self.get_rendered_id().as_str())
             .unwrap();
         if let Some(wrapper_element) = &self.wrapper_element {
-            wrapper_element.append_child(&*group_element).unwrap();
+            wrapper_element.append_child(&group_element).unwrap();
             wrapper_element.set_attribute("display", "block").unwrap();
         }
 
@@ -233,24 +233,24 @@ impl SvgRenderer {
                 let empty_element = document
                     .create_element_ns(Some("http://www.w3.org/2000/svg"), "text")
                     .unwrap();
-                node.append_child(&*empty_element).unwrap();
+                node.append_child(&empty_element).unwrap();
                 let element = empty_element.parent_element().unwrap();
                 element.set_inner_html(label);
                 match self.cursor.cursor_type {
                     CursorType::Default => {
                         element
-                            .set_attribute("x", &*self.item_x.to_string())
+                            .set_attribute("x", &self.item_x.to_string())
                             .unwrap();
                         element
                             .set_attribute(
                                 "y",
-                                &*(self.item_y + index as f64 * self.step_length).to_string(),
+                                &(self.item_y + index as f64 * self.step_length).to_string(),
                             )
                             .unwrap();
                     }
                 }
                 element.set_attribute("display", "block").unwrap();
-                group_element.append_child(&*element).unwrap();
+                group_element.append_child(&element).unwrap();
                 if descriptions.is_empty() {
                     continue;
                 }
@@ -263,23 +263,22 @@ impl SvgRenderer {
                             .unwrap()
                             .create_element_ns(Some("http://www.w3.org/2000/svg"), "text")
                             .unwrap();
-                        node.append_child(&*empty_element).unwrap();
+                        node.append_child(&empty_element).unwrap();
                         let element = empty_element.parent_element().unwrap();
                         element.set_inner_html(descriptions[index].as_str());
                         element.class_list().add_1("item-description").unwrap();
                         element
-                            .set_attribute("x", &*(self.item_x + 15.0).to_string())
+                            .set_attribute("x", &(self.item_x + 15.0).to_string())
                             .unwrap();
                         element
                             .set_attribute(
                                 "y",
-                                &*(self.item_y + index as f64 * self.step_length + 20.0)
-                                    .to_string(),
+                                &(self.item_y + index as f64 * self.step_length + 20.0).to_string(),
                             )
                             .unwrap();
                         element.set_attribute("font-size", "11").unwrap();
                         element.set_attribute("display", "block").unwrap();
-                        group_element.append_child(&*element).unwrap();
+                        group_element.append_child(&element).unwrap();
                     }
                 }
             }
@@ -315,6 +314,6 @@ pub fn get_element_by_id(id: String) -> Element {
         .unwrap()
         .document()
         .unwrap()
-        .get_element_by_id(&*id)
+        .get_element_by_id(&id)
         .unwrap()
 }